@@ -41,6 +41,7 @@ cfg_if! {
 
 extern crate progress_read;
 extern crate reqwest;
+extern crate sha2;
 extern crate tee;
 
 extern crate failure;
@@ -53,6 +54,63 @@ pub(crate) struct HttpError {
     code: ::reqwest::StatusCode,
 }
 
+/// An in-progress download of an archive, which may be resuming an earlier
+/// download that was interrupted.
+pub(crate) struct Download {
+    /// The response streaming the (remaining) archive data.
+    response: reqwest::Response,
+    /// The partial download file, opened for appending the remaining data.
+    file: File,
+    /// The number of bytes already downloaded in a previous attempt.
+    resumed_from: u64,
+}
+
+impl Download {
+    /// Starts downloading the archive at the given URL into `partial_file`. If the
+    /// file already contains data from an interrupted download, only the rest of
+    /// the archive is requested, provided the server supports byte ranges.
+    pub(crate) fn start(url: &str, partial_file: &Path) -> Result<Download, failure::Error> {
+        let existing = match partial_file.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+
+        let mut response = request_from(url, existing)?;
+
+        // A server that doesn't support ranges (or a partial file that is already
+        // complete) means starting over from scratch.
+        if existing > 0 && response.status() == reqwest::StatusCode::RangeNotSatisfiable {
+            response = request_from(url, 0)?;
+        }
+
+        if !response.status().is_success() {
+            Err(HttpError { code: response.status() })?;
+        }
+
+        let (file, resumed_from) = if response.status() == reqwest::StatusCode::PartialContent {
+            (OpenOptions::new().append(true).open(partial_file)?, existing)
+        } else {
+            (File::create(partial_file)?, 0)
+        };
+
+        Ok(Download {
+            response,
+            file,
+            resumed_from,
+        })
+    }
+}
+
+/// Sends a GET request for the given URL, starting at the given byte offset.
+fn request_from(url: &str, offset: u64) -> Result<reqwest::Response, failure::Error> {
+    let client = reqwest::Client::new()?;
+    let mut request = client.get(url)?;
+    if offset > 0 {
+        request.header(Range::Bytes(vec![ByteRangeSpec::AllFrom(offset)]));
+    }
+    Ok(request.send()?)
+}
+
 cfg_if! {
     if #[cfg(unix)] {
         pub use tarball::Tarball;
@@ -63,14 +121,26 @@ cfg_if! {
     }
 }
 
-use std::fs::File;
+use std::fs::{remove_file, File, OpenOptions};
+use std::io::{self, Read};
 use std::path::Path;
 
+use reqwest::header::{ByteRangeSpec, Range};
+use sha2::{Digest, Sha256};
+
 pub trait Archive {
     fn compressed_size(&self) -> u64;
     fn uncompressed_size(&self) -> Option<u64>;
 
-    /// Unpacks the zip archive to the specified destination folder.
+    /// Reads the archive data through to the end without unpacking it, which
+    /// completes the download of a remote archive, and returns the hex-encoded
+    /// SHA-256 digest of the archive file.
+    fn digest(
+        self: Box<Self>,
+        progress: &mut FnMut(&(), usize),
+    ) -> Result<String, failure::Error>;
+
+    /// Unpacks the archive to the specified destination folder.
     fn unpack(
         self: Box<Self>,
         dest: &Path,
@@ -78,6 +148,40 @@ pub trait Archive {
     ) -> Result<(), failure::Error>;
 }
 
+/// A reader that computes the SHA-256 digest of all the data read through it.
+pub(crate) struct HashRead<R: Read> {
+    source: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashRead<R> {
+    pub(crate) fn new(source: R) -> Self {
+        HashRead {
+            source,
+            hasher: Sha256::default(),
+        }
+    }
+
+    /// Reads any data remaining in the underlying reader and produces the
+    /// hex-encoded digest of everything that was read.
+    pub(crate) fn finish(mut self) -> io::Result<String> {
+        io::copy(&mut self, &mut io::sink())?;
+        Ok(self.hasher
+            .result()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect())
+    }
+}
+
+impl<R: Read> Read for HashRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.source.read(buf)?;
+        self.hasher.input(&buf[..len]);
+        Ok(len)
+    }
+}
+
 cfg_if! {
     if #[cfg(feature = "universal-docs")] {
         /// Load a cached Node archive from the specified file.
@@ -85,9 +189,10 @@ cfg_if! {
             unimplemented!()
         }
 
-        /// Fetch a remote Node archive from the given URL and cache its results
-        /// at the given file path.
-        pub fn fetch(url: &str, cache_file: &Path) -> Result<Box<Archive>, failure::Error> {
+        /// Fetch a remote Node archive from the given URL and save its contents
+        /// at the given file path. If the file holds the beginning of an earlier,
+        /// interrupted download, the download is resumed where it left off.
+        pub fn fetch(url: &str, partial_file: &Path) -> Result<Box<Archive>, failure::Error> {
             unimplemented!()
         }
     } else if #[cfg(unix)] {
@@ -95,18 +200,36 @@ cfg_if! {
             Ok(Box::new(Tarball::load(source)?))
         }
 
-        pub fn fetch(url: &str, cache_file: &Path) -> Result<Box<Archive>, failure::Error> {
-            Ok(Box::new(Tarball::fetch(url, cache_file)?))
+        pub fn fetch(url: &str, partial_file: &Path) -> Result<Box<Archive>, failure::Error> {
+            Ok(Box::new(Tarball::fetch(url, partial_file)?))
         }
     } else if #[cfg(windows)] {
         pub fn load(source: File) -> Result<Box<Archive>, failure::Error> {
             Ok(Box::new(Zip::load(source)?))
         }
 
-        pub fn fetch(url: &str, cache_file: &Path) -> Result<Box<Archive>, failure::Error> {
-            Ok(Box::new(Zip::fetch(url, cache_file)?))
+        pub fn fetch(url: &str, partial_file: &Path) -> Result<Box<Archive>, failure::Error> {
+            Ok(Box::new(Zip::fetch(url, partial_file)?))
         }
     } else {
         compile_error!("Unsupported OS (expected 'unix' or 'windows').");
     }
 }
+
+/// Load a Node archive from an arbitrary stream of archive data (such as the
+/// output of a plugin process), saving its contents at the given file path.
+/// Since a stream can't be resumed, the file is removed if the stream fails
+/// or doesn't hold a valid archive.
+pub fn stream<R: Read>(mut source: R, file: &Path) -> Result<Box<Archive>, failure::Error> {
+    let archive = save_stream(&mut source, file).and_then(|_| load(File::open(file)?));
+    if archive.is_err() {
+        let _ = remove_file(file);
+    }
+    archive
+}
+
+fn save_stream<R: Read>(source: &mut R, file: &Path) -> Result<(), failure::Error> {
+    let mut dest = File::create(file)?;
+    io::copy(source, &mut dest)?;
+    Ok(())
+}
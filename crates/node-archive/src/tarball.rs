@@ -1,7 +1,7 @@
 //! Provides types and functions for fetching and unpacking a Node installation
 //! tarball in Unix operating systems.
 
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{self, Chain, Read, Seek, SeekFrom, Take};
 use std::path::Path;
 use std::fs::File;
 
@@ -14,7 +14,7 @@ use tee::TeeReader;
 use progress_read::ProgressRead;
 use failure;
 
-use super::Archive;
+use super::{Archive, Download, HashRead};
 
 /// A Node installation tarball.
 pub struct Tarball<S: Read> {
@@ -55,22 +55,19 @@ fn content_length(response: &Response) -> Result<u64, failure::Error> {
     })
 }
 
-impl Tarball<TeeReader<reqwest::Response, File>> {
+impl Tarball<Chain<Take<File>, TeeReader<reqwest::Response, File>>> {
 
     /// Initiate fetching of a Node tarball from the given URL, returning
-    /// a tarball that can be streamed (and that tees its data to a cache
-    /// file as it streams).
-    pub fn fetch(url: &str, cache_file: &Path) -> Result<Self, failure::Error> {
+    /// a tarball that can be streamed (and that tees its data to the partial
+    /// download file as it streams). Any data already in the partial file
+    /// from an interrupted download is streamed first.
+    pub fn fetch(url: &str, partial_file: &Path) -> Result<Self, failure::Error> {
         let uncompressed_size = fetch_uncompressed_size(url)?;
-        let response = reqwest::get(url)?;
+        let download = Download::start(url, partial_file)?;
 
-        if !response.status().is_success() {
-            Err(super::HttpError { code: response.status() })?;
-        }
-
-        let compressed_size = content_length(&response)?;
-        let file = File::create(cache_file)?;
-        let data = TeeReader::new(response, file);
+        let compressed_size = download.resumed_from + content_length(&download.response)?;
+        let existing = File::open(partial_file)?.take(download.resumed_from);
+        let data = existing.chain(TeeReader::new(download.response, download.file));
 
         Ok(Tarball {
             uncompressed_size,
@@ -84,10 +81,16 @@ impl Tarball<TeeReader<reqwest::Response, File>> {
 impl<S: Read> Archive for Tarball<S> {
     fn compressed_size(&self) -> u64 { self.compressed_size }
     fn uncompressed_size(&self) -> Option<u64> { Some(self.uncompressed_size) }
+    fn digest(self: Box<Self>, progress: &mut FnMut(&(), usize)) -> Result<String, failure::Error> {
+        Ok(HashRead::new(ProgressRead::new(self.data, (), progress)).finish()?)
+    }
     fn unpack(self: Box<Self>, dest: &Path, progress: &mut FnMut(&(), usize)) -> Result<(), failure::Error> {
         let decoded = GzDecoder::new(self.data);
         let mut tarball = tar::Archive::new(ProgressRead::new(decoded, (), progress));
         tarball.unpack(dest)?;
+        // Drain whatever padding and gzip trailer the tar reader left, so that a
+        // download teed to the partial file is complete.
+        io::copy(&mut tarball.into_inner(), &mut io::sink())?;
         Ok(())
     }
 }
@@ -200,8 +203,10 @@ fn load_uncompressed_size(file: &mut File) -> Result<u64, failure::Error> {
 pub mod tests {
 
     use tarball::Tarball;
+    use {stream, Archive};
+    use std::env;
     use std::path::PathBuf;
-    use std::fs::File;
+    use std::fs::{self, File};
 
     fn fixture_path(fixture_dir: &str) -> PathBuf {
         let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -220,4 +225,36 @@ pub mod tests {
         assert_eq!(tarball.uncompressed_size, 10240);
         assert_eq!(tarball.compressed_size, 402);
     }
+
+    #[test]
+    fn test_digest() {
+        let mut test_file_path = fixture_path("tarballs");
+        test_file_path.push("test-file.tar.gz");
+        let test_file = File::open(test_file_path).expect("Couldn't open test file");
+        let tarball = Box::new(Tarball::load(test_file).expect("Failed to load tarball"));
+
+        let digest = tarball.digest(&mut |_, _| ()).expect("Failed to read tarball");
+        assert_eq!(digest, "e118ac15be2e0729ab31982c8eaf4dc05b17b1801516183754a9ccc9de630a97");
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut test_file_path = fixture_path("tarballs");
+        test_file_path.push("test-file.tar.gz");
+        let test_file = File::open(test_file_path).expect("Couldn't open test file");
+
+        let file = env::temp_dir().join("node-archive-tarball-stream.tar.gz");
+        let tarball = stream(test_file, &file).expect("Failed to stream tarball");
+        assert!(file.is_file());
+        assert_eq!(tarball.compressed_size(), 402);
+        let _ = fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_stream_invalid() {
+        let file = env::temp_dir().join("node-archive-tarball-stream-invalid.tar.gz");
+        // too short to hold even the trailer of a gzip file
+        assert!(stream(&b"gz"[..], &file).is_err());
+        assert!(!file.exists());
+    }
 }
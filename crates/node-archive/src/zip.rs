@@ -1,18 +1,17 @@
 //! Provides types and functions for fetching and unpacking a Node installation
 //! zip file in Windows operating systems.
 
-use std::io::{self, Read, Seek, copy};
+use std::io::{self, Read, Seek, SeekFrom, copy};
 use std::path::Path;
 use std::fs::{File, create_dir_all};
 
-use reqwest;
 use progress_read::ProgressRead;
 use zip_rs::ZipArchive;
 use verbatim::PathExt;
 
 use failure;
 
-use super::Archive;
+use super::{Archive, Download, HashRead};
 
 pub struct Zip<S: Read + Seek> {
     compressed_size: u64,
@@ -32,20 +31,13 @@ impl Zip<File> {
     }
 
     /// Initiate fetching of a Node zip archive from the given URL, returning
-    /// a `Remote` data source.
-    pub fn fetch(url: &str, cache_file: &Path) -> Result<Self, failure::Error> {
-        let mut response = reqwest::get(url)?;
+    /// a `Remote` data source. The archive is downloaded to the partial download
+    /// file, resuming an interrupted download if there is one.
+    pub fn fetch(url: &str, partial_file: &Path) -> Result<Self, failure::Error> {
+        let mut download = Download::start(url, partial_file)?;
+        copy(&mut download.response, &mut download.file)?;
 
-        if !response.status().is_success() {
-            Err(super::HttpError { code: response.status() })?;
-        }
-
-        {
-            let mut file = File::create(cache_file)?;
-            copy(&mut response, &mut file)?;
-        }
-
-        let file = File::create(cache_file)?;
+        let file = File::open(partial_file)?;
         let compressed_size = file.metadata()?.len();
 
         Ok(Zip {
@@ -59,6 +51,11 @@ impl Zip<File> {
 impl<S: Read + Seek> Archive for Zip<S> {
     fn compressed_size(&self) -> u64 { self.compressed_size }
     fn uncompressed_size(&self) -> Option<u64> { None }
+    fn digest(self: Box<Self>, progress: &mut FnMut(&(), usize)) -> Result<String, failure::Error> {
+        let mut data = self.data;
+        data.seek(SeekFrom::Start(0))?;
+        Ok(HashRead::new(ProgressRead::new(data, (), progress)).finish()?)
+    }
     fn unpack(self: Box<Self>, dest: &Path, progress: &mut FnMut(&(), usize)) -> Result<(), failure::Error> {
         // Use a verbatim path to avoid the legacy Windows 260 byte path limit.
         let dest: &Path = &dest.to_verbatim();
@@ -96,6 +93,7 @@ impl<S: Read + Seek> Archive for Zip<S> {
 pub mod tests {
 
     use zip::Zip;
+    use Archive;
     use std::path::PathBuf;
     use std::fs::File;
 
@@ -115,4 +113,15 @@ pub mod tests {
 
         assert_eq!(zip.compressed_size, 214);
     }
+
+    #[test]
+    fn test_digest() {
+        let mut test_file_path = fixture_path("zips");
+        test_file_path.push("test-file.zip");
+        let test_file = File::open(test_file_path).expect("Couldn't open test file");
+        let zip = Box::new(Zip::load(test_file).expect("Failed to load zip file"));
+
+        let digest = zip.digest(&mut |_, _| ()).expect("Failed to read zip file");
+        assert_eq!(digest, "9d0661a76c9672032b641f8bb78675d5b5ac385681577dd77910dcbe910f237e");
+    }
 }
@@ -0,0 +1,6 @@
+#!/bin/sh
+# A resolve plugin that streams its archive over stderr and then exits with the
+# status given as its argument.
+echo '{ "version": "8.9.4", "stream": true }'
+printf 'archive data' >&2
+exit "$1"
@@ -0,0 +1,10 @@
+#!/bin/sh
+# A resolve plugin that responds with a URL, writes more diagnostics to stderr than
+# fit in a pipe buffer and then exits with the status given as its argument.
+echo '{ "version": "8.9.4", "url": "https://example.com/node-v8.9.4.tar.gz" }'
+i=0
+while [ "$i" -lt 2048 ]; do
+    echo "resolving: line $i of diagnostics padded to fill the pipe buffer quickly" >&2
+    i=$((i + 1))
+done
+exit "$1"
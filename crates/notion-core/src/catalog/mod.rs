@@ -1,9 +1,10 @@
 //! Provides types for working with Notion's local _catalog_, the local repository
 //! of available tool versions.
 
-use std::collections::{BTreeSet, HashSet};
-use std::fs::{remove_dir_all, File};
-use std::io::{self, Write};
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::{read_dir, remove_dir_all, remove_file, File};
+use std::io::Write;
 use std::marker::PhantomData;
 use std::str::FromStr;
 use std::string::ToString;
@@ -17,14 +18,20 @@ use serde_json;
 use tempfile::NamedTempFile;
 use toml;
 
-use config::{Config, ToolConfig};
+use config::{edit, Config, ToolConfig};
 use distro::node::NodeDistro;
+use distro::npm::NpmDistro;
+use distro::pnpm::PnpmDistro;
 use distro::yarn::YarnDistro;
 use distro::{Distro, Fetched};
-use fs::{ensure_containing_dir_exists, read_file_opt, touch};
-use notion_fail::{ExitCode, Fallible, NotionError, NotionFail, ResultExt};
+use env;
+use fs::{
+    create_staging_file, dir_size, ensure_containing_dir_exists, read_file_opt, touch,
+    FileParseError,
+};
+use notion_fail::{ExitCode, FailExt, Fallible, NotionError, NotionFail, ResultExt};
 use path::{self, user_catalog_file};
-use semver::{Version, VersionReq};
+use semver::Version;
 use style::progress_spinner;
 use version::VersionSpec;
 
@@ -33,34 +40,54 @@ pub(crate) mod serial;
 #[cfg(feature = "mock-network")]
 use mockito;
 
-// ISSUE (#86): Move public repository URLs to config file
 cfg_if! {
     if #[cfg(feature = "mock-network")] {
         fn public_node_version_index() -> String {
             format!("{}/node-dist/index.json", mockito::SERVER_URL)
         }
-        fn public_yarn_version_index() -> String {
-            format!("{}/yarn-releases/index.json", mockito::SERVER_URL)
-        }
-        fn public_yarn_latest_version() -> String {
-            format!("{}/yarn-latest", mockito::SERVER_URL)
+        fn public_npm_registry() -> String {
+            format!("{}/npm-registry", mockito::SERVER_URL)
         }
     } else {
         /// Returns the URL of the index of available Node versions on the public Node server.
         fn public_node_version_index() -> String {
             "https://nodejs.org/dist/index.json".to_string()
         }
-        /// Return the URL of the index of available Yarn versions on the public git repository.
-        fn public_yarn_version_index() -> String {
-            "https://github.com/notion-cli/yarn-releases/raw/master/index.json".to_string()
-        }
-        /// URL of the latest Yarn version on the public yarnpkg.com
-        fn public_yarn_latest_version() -> String {
-            "https://yarnpkg.com/latest-version".to_string()
+        /// Returns the URL of the public npm registry.
+        fn public_npm_registry() -> String {
+            "https://registry.npmjs.org".to_string()
         }
     }
 }
 
+/// Returns the URL of the index of available Node versions, on the given mirror if any.
+fn node_version_index(mirror: Option<&str>) -> String {
+    match mirror {
+        Some(mirror) => format!("{}/index.json", mirror.trim_right_matches('/')),
+        None => public_node_version_index(),
+    }
+}
+
+/// Returns the URL of the npm registry, or of the given mirror of it.
+pub(crate) fn npm_registry(mirror: Option<&str>) -> String {
+    match mirror {
+        Some(mirror) => mirror.trim_right_matches('/').to_string(),
+        None => public_npm_registry(),
+    }
+}
+
+/// Returns the URL of the metadata of a package, on the given mirror of the npm registry if any.
+fn registry_package_index(name: &str, mirror: Option<&str>) -> String {
+    format!("{}/{}", npm_registry(mirror), name)
+}
+
+/// Returns the mirror configured for a tool, if any.
+fn mirror(config: Option<&ToolConfig>) -> Option<&str> {
+    config
+        .and_then(|config| config.mirror.as_ref())
+        .map(|mirror| mirror.as_str())
+}
+
 /// Lazily loaded tool catalog.
 pub struct LazyCatalog {
     catalog: LazyCell<Catalog>,
@@ -86,7 +113,7 @@ impl LazyCatalog {
 }
 
 pub struct Collection<D: Distro> {
-    /// The currently activated Node version, if any.
+    /// The user's default version of the tool, if any.
     pub default: Option<Version>,
 
     // A sorted collection of the available versions in the catalog.
@@ -97,11 +124,63 @@ pub struct Collection<D: Distro> {
 
 pub type NodeCollection = Collection<NodeDistro>;
 pub type YarnCollection = Collection<YarnDistro>;
+pub type NpmCollection = Collection<NpmDistro>;
+pub type PnpmCollection = Collection<PnpmDistro>;
+
+/// A tool version installed in the catalog, along with its footprint on disk.
+pub struct Installed {
+    pub version: Version,
+    /// Whether this is the user's default version of the tool.
+    pub default: bool,
+    /// The size in bytes of the unpacked version directory.
+    pub size: u64,
+    /// Whether the downloaded archive for this version is still in the cache.
+    pub cached: bool,
+}
+
+/// A `Collection` of any tool, as held by the catalog.
+trait AnyCollection {
+    fn as_any(&self) -> &Any;
+    fn as_any_mut(&mut self) -> &mut Any;
+    fn to_serial(&self) -> serial::Collection;
+}
+
+impl<D: Distro> AnyCollection for Collection<D> {
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+
+    fn to_serial(&self) -> serial::Collection {
+        serial::Collection::from_collection(self)
+    }
+}
 
 /// The catalog of tool versions available locally.
 pub struct Catalog {
-    pub node: NodeCollection,
-    pub yarn: YarnCollection,
+    /// The collection of each tool in the registry, keyed by the tool's name.
+    collections: BTreeMap<&'static str, Box<AnyCollection>>,
+
+    /// The packages installed with `notion install`, keyed by the package's name.
+    pub packages: BTreeMap<String, Package>,
+
+    /// The tables this version of Notion doesn't know about, such as the collections of
+    /// unknown tools, which are kept as they are so that saving the catalog doesn't lose them.
+    others: BTreeMap<String, toml::Value>,
+}
+
+/// A package installed with `notion install`, which keeps running on the version of Node
+/// it was installed with.
+#[derive(Clone)]
+pub struct Package {
+    pub version: Version,
+    /// The version of Node the package was installed with.
+    pub node: Version,
+    /// The names of the executables the package provides.
+    pub bins: Vec<String>,
 }
 
 impl Catalog {
@@ -109,12 +188,17 @@ impl Catalog {
     fn current() -> Fallible<Catalog> {
         let path = user_catalog_file()?;
         let src = touch(&path)?.read_into_string().unknown()?;
-        src.parse()
+        let tables = toml::from_str(&src).with_context(FileParseError::for_toml(&path))?;
+        // Checking each collection and package on its own locates any invalid one.
+        edit::check_entries(&path, &src, &tables, |tables| {
+            serial::Catalog(tables).into_catalog().map(|_| ())
+        })?;
+        serial::Catalog(tables).into_catalog()
     }
 
     /// Returns a pretty-printed TOML representation of the contents of the catalog.
     pub fn to_string(&self) -> String {
-        toml::to_string_pretty(&self.to_serial()).unwrap()
+        toml::to_string_pretty(&self.to_serial().0).unwrap()
     }
 
     /// Saves the contents of the catalog to the user's catalog file.
@@ -125,109 +209,174 @@ impl Catalog {
         Ok(())
     }
 
-    /// Sets the Node version in the user toolchain to one matching the specified semantic versioning requirements.
-    pub fn set_user_node(&mut self, matching: &VersionSpec, config: &Config) -> Fallible<()> {
-        let fetched = self.fetch_node(matching, config)?;
+    /// Produces a reference to the collection of a tool. This fails if the tool is missing
+    /// from the registry.
+    pub fn collection<D: Distro>(&self) -> Fallible<&Collection<D>> {
+        match self
+            .collections
+            .get(D::NAME)
+            .and_then(|collection| collection.as_any().downcast_ref())
+        {
+            Some(collection) => Ok(collection),
+            None => throw!(UnregisteredToolError::new(D::NAME).unknown()),
+        }
+    }
+
+    /// Produces a mutable reference to the collection of a tool. This fails if the tool is
+    /// missing from the registry.
+    pub fn collection_mut<D: Distro>(&mut self) -> Fallible<&mut Collection<D>> {
+        match self
+            .collections
+            .get_mut(D::NAME)
+            .and_then(|collection| collection.as_any_mut().downcast_mut())
+        {
+            Some(collection) => Ok(collection),
+            None => throw!(UnregisteredToolError::new(D::NAME).unknown()),
+        }
+    }
+
+    /// Sets the version of a tool in the user toolchain to one matching the specified semantic
+    /// versioning requirements.
+    pub fn set_user<D: Distro>(&mut self, matching: &VersionSpec, config: &Config) -> Fallible<()> {
+        let fetched = self.fetch::<D>(matching, config)?;
         let version = Some(fetched.into_version());
 
-        if self.node.default != version {
-            self.node.default = version;
+        if self.collection::<D>()?.default != version {
+            self.collection_mut::<D>()?.default = version;
             self.save()?;
         }
 
         Ok(())
     }
 
-    /// Fetches a Node version matching the specified semantic versioning requirements.
-    pub fn fetch_node(&mut self, matching: &VersionSpec, config: &Config) -> Fallible<Fetched> {
-        let distro = self.node.resolve_remote(matching, config.node.as_ref())?;
-        let fetched = distro.fetch(&self.node).unknown()?;
+    /// Fetches a version of a tool matching the specified semantic versioning requirements.
+    pub fn fetch<D: Distro>(&mut self, matching: &VersionSpec, config: &Config) -> Fallible<Fetched> {
+        let fetched = {
+            let collection = self.collection::<D>()?;
+
+            if env::offline() {
+                collection.fetch_offline(matching, config.tool::<D>())?
+            } else {
+                let distro = collection.resolve_remote(matching, config.tool::<D>())?;
+                distro.fetch(collection, config.tool::<D>()).unknown()?
+            }
+        };
 
         if let &Fetched::Now(ref version) = &fetched {
-            self.node.versions.insert(version.clone());
+            self.collection_mut::<D>()?.versions.insert(version.clone());
             self.save()?;
         }
 
         Ok(fetched)
     }
 
-    /// Resolves a Node version matching the specified semantic versioning requirements.
-    pub fn resolve_node(&self, matching: &VersionSpec, config: &Config) -> Fallible<Version> {
-        let distro = self.node.resolve_remote(&matching, config.node.as_ref())?;
+    /// Resolves a version of a tool matching the specified semantic versioning requirements.
+    pub fn resolve<D: Distro>(&self, matching: &VersionSpec, config: &Config) -> Fallible<Version> {
+        let collection = self.collection::<D>()?;
+
+        if env::offline() {
+            return collection.resolve_offline(matching);
+        }
+
+        let distro = collection.resolve_remote(matching, config.tool::<D>())?;
         Ok(distro.version().clone())
     }
 
-    /// Uninstalls a specific Node version from the local catalog.
-    pub fn uninstall_node(&mut self, version: &Version) -> Fallible<()> {
-        if self.node.contains(version) {
-            let home = path::node_version_dir(&version.to_string())?;
-
-            if !home.is_dir() {
-                Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("{} is not a directory", home.to_string_lossy()),
-                )).unknown()?;
-            }
+    /// Resolves a version of a tool matching the specified semantic versioning requirements
+    /// among the installed versions, without accessing the network.
+    pub fn resolve_installed<D: Distro>(&self, matching: &VersionSpec) -> Fallible<Option<Version>> {
+        let collection = self.collection::<D>()?;
+        Ok(find_match(&collection.versions, matching, &D::cached_index()?))
+    }
 
-            remove_dir_all(home).unknown()?;
+    /// Lists the versions of a tool available from the remote distributor.
+    pub fn ls_remote<D: Distro>(&self, config: &Config) -> Fallible<Vec<Version>> {
+        self.collection::<D>()?.ls_remote(config.tool::<D>())
+    }
 
-            self.node.versions.remove(version);
+    /// Lists the installed versions of a tool, sorted from oldest to newest.
+    pub fn installed<D: Distro>(&self) -> Fallible<Vec<Installed>> {
+        let collection = self.collection::<D>()?;
+        collection
+            .versions
+            .iter()
+            .map(|version| {
+                let version_str = version.to_string();
+                Ok(Installed {
+                    version: version.clone(),
+                    default: collection.default.as_ref() == Some(version),
+                    size: dir_size(&D::version_dir(&version_str)?).unknown()?,
+                    cached: D::cache_dir()?
+                        .join(D::archive_file(&version_str))
+                        .is_file(),
+                })
+            })
+            .collect()
+    }
 
-            self.save()?;
+    /// Returns the name and the installation of the package that provides an executable, if any.
+    /// It is an error for more than one package to provide it.
+    pub fn package_for_bin(&self, bin: &str) -> Fallible<Option<(&String, &Package)>> {
+        let providers: Vec<(&String, &Package)> = self
+            .packages
+            .iter()
+            .filter(|&(_, package)| package.bins.iter().any(|name| name == bin))
+            .collect();
+        if providers.len() > 1 {
+            throw!(BinConflictError::new(
+                bin,
+                providers.iter().map(|&(name, _)| name.as_str())
+            ));
         }
-
-        Ok(())
+        Ok(providers.into_iter().next())
     }
 
-    // ISSUE (#87) Abstract Catalog's activate, install and uninstall methods
-    // And potentially share code between node and yarn
-    /// Sets the Yarn version in the user toolchain to one matching the specified semantic versioning requirements.
-    pub fn set_user_yarn(&mut self, matching: &VersionSpec, config: &Config) -> Fallible<()> {
-        let fetched = self.fetch_yarn(matching, config)?;
-        let version = Some(fetched.into_version());
-
-        if self.yarn.default != version {
-            self.yarn.default = version;
-            self.save()?;
+    /// Checks that none of the executables of a package are provided by another installed
+    /// package.
+    pub fn check_package_bins(&self, name: &str, bins: &[String]) -> Fallible<()> {
+        for bin in bins {
+            let other = self
+                .packages
+                .iter()
+                .find(|&(other, package)| other != name && package.bins.contains(bin));
+            if let Some((other, _)) = other {
+                throw!(BinConflictError::new(bin, vec![other.as_str(), name]));
+            }
         }
-
         Ok(())
     }
 
-    /// Fetches a Yarn version matching the specified semantic versioning requirements.
-    pub fn fetch_yarn(&mut self, matching: &VersionSpec, config: &Config) -> Fallible<Fetched> {
-        let distro = self.yarn.resolve_remote(&matching, config.yarn.as_ref())?;
-        let fetched = distro.fetch(&self.yarn).unknown()?;
-
-        if let &Fetched::Now(ref version) = &fetched {
-            self.yarn.versions.insert(version.clone());
-            self.save()?;
-        }
-
-        Ok(fetched)
+    /// Records a package installed with `notion install` in the catalog, returning the
+    /// installation it replaces, if any.
+    pub fn add_package(&mut self, name: &str, package: Package) -> Fallible<Option<Package>> {
+        let replaced = self.packages.insert(name.to_string(), package);
+        self.save()?;
+        Ok(replaced)
     }
 
-    /// Resolves a Yarn version matching the specified semantic versioning requirements.
-    pub fn resolve_yarn(&self, matching: &VersionSpec, config: &Config) -> Fallible<Version> {
-        let distro = self.yarn.resolve_remote(&matching, config.yarn.as_ref())?;
-        Ok(distro.version().clone())
-    }
+    /// Uninstalls a specific version of a tool from the local catalog.
+    pub fn uninstall<D: Distro>(&mut self, version: &Version) -> Fallible<()> {
+        if self.collection::<D>()?.contains(version) {
+            let version_str = version.to_string();
+            let home = D::version_dir(&version_str)?;
 
-    /// Uninstalls a specific Yarn version from the local catalog.
-    pub fn uninstall_yarn(&mut self, version: &Version) -> Fallible<()> {
-        if self.yarn.contains(version) {
-            let home = path::yarn_version_dir(&version.to_string())?;
+            // The directory may already have been removed by hand, in which case
+            // there's nothing left to clean up besides the catalog entry.
+            if home.is_dir() {
+                remove_dir_all(home).unknown()?;
+            }
 
-            if !home.is_dir() {
-                Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("{} is not a directory", home.to_string_lossy()),
-                )).unknown()?;
+            let archive = D::cache_dir()?.join(D::archive_file(&version_str));
+            if archive.is_file() {
+                remove_file(archive).unknown()?;
             }
 
-            remove_dir_all(home).unknown()?;
+            let collection = self.collection_mut::<D>()?;
+            collection.versions.remove(version);
 
-            self.yarn.versions.remove(version);
+            if collection.default.as_ref() == Some(version) {
+                collection.default = None;
+            }
 
             self.save()?;
         }
@@ -236,20 +385,84 @@ impl Catalog {
     }
 }
 
-/// Thrown when there is no Node version matching a requested semver specifier.
+/// Thrown when the catalog has no collection for a tool, because it is missing from the registry.
+#[derive(Debug, Fail)]
+#[fail(display = "{} is missing from the tool registry", tool)]
+struct UnregisteredToolError {
+    tool: String,
+}
+
+impl UnregisteredToolError {
+    fn new(tool: &str) -> Self {
+        UnregisteredToolError {
+            tool: tool.to_string(),
+        }
+    }
+}
+
+/// Thrown when more than one installed package provides an executable of the same name.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Executable '{}' is provided by more than one package: {}", bin, packages)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct BinConflictError {
+    bin: String,
+    packages: String,
+}
+
+impl BinConflictError {
+    fn new<'a, I: IntoIterator<Item = &'a str>>(bin: &str, packages: I) -> Self {
+        BinConflictError {
+            bin: bin.to_string(),
+            packages: packages.into_iter().collect::<Vec<_>>().join(", "),
+        }
+    }
+}
+
+/// Thrown when there is no version of a package on the npm registry (such as npm itself)
+/// matching a requested semver specifier.
 #[derive(Debug, Fail, NotionFail)]
-#[fail(display = "No Node version found for {}", matching)]
+#[fail(display = "No {} version found for {}", package, matching)]
 #[notion_fail(code = "NoVersionMatch")]
-struct NoNodeVersionFoundError {
+struct NoPackageVersionFoundError {
+    package: String,
     matching: VersionSpec,
 }
 
-/// Thrown when there is no Yarn version matching a requested semver specifier.
+/// Thrown when a long-term support release is requested for a tool that does not have them.
 #[derive(Debug, Fail, NotionFail)]
-#[fail(display = "No Yarn version found for {}", matching)]
+#[fail(display = "{} does not publish long-term support releases", tool)]
 #[notion_fail(code = "NoVersionMatch")]
-struct NoYarnVersionFoundError {
-    matching: VersionReq,
+pub(crate) struct NoLtsReleasesError {
+    pub(crate) tool: String,
+}
+
+/// Thrown when offline and no locally available version matches a requested semver specifier.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No {} version found for {} while offline\nAvailable locally: {}", tool,
+       matching, available)]
+#[notion_fail(code = "NoVersionMatch")]
+struct NoOfflineVersionFoundError {
+    tool: String,
+    matching: VersionSpec,
+    available: String,
+}
+
+impl NoOfflineVersionFoundError {
+    fn new(tool: &str, matching: &VersionSpec, available: &BTreeSet<Version>) -> Self {
+        NoOfflineVersionFoundError {
+            tool: tool.to_string(),
+            matching: matching.clone(),
+            available: if available.is_empty() {
+                "none".to_string()
+            } else {
+                available
+                    .iter()
+                    .map(|version| version.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            },
+        }
+    }
 }
 
 impl<D: Distro> Collection<D> {
@@ -257,26 +470,142 @@ impl<D: Distro> Collection<D> {
     pub fn contains(&self, version: &Version) -> bool {
         self.versions.contains(version)
     }
-}
 
-pub trait Resolve<D: Distro> {
     /// Resolves the specified semantic versioning requirements from a remote distributor.
-    fn resolve_remote(
-        &self,
-        matching: &VersionSpec,
-        config: Option<&ToolConfig<D>>,
-    ) -> Fallible<D> {
+    fn resolve_remote(&self, matching: &VersionSpec, config: Option<&ToolConfig>) -> Fallible<D> {
         match config {
             Some(ToolConfig {
                 resolve: Some(ref plugin),
                 ..
             }) => plugin.resolve(matching),
-            _ => self.resolve_public(matching),
+            _ => D::resolve_public(matching, mirror(config)),
+        }
+    }
+
+    /// Lists the versions available from a remote distributor, sorted from oldest to newest.
+    fn ls_remote(&self, config: Option<&ToolConfig>) -> Fallible<Vec<Version>> {
+        let mut versions = match config {
+            Some(ToolConfig {
+                ls_remote: Some(ref plugin),
+                ..
+            }) => plugin.list()?,
+            _ => D::ls_public(mirror(config))?,
+        };
+        versions.sort();
+        versions.dedup();
+        Ok(versions)
+    }
+
+    /// Lists the versions whose archive is in the local cache.
+    fn cached_archives(&self) -> Fallible<BTreeSet<Version>> {
+        let dir = D::cache_dir()?;
+        let mut versions = BTreeSet::new();
+
+        if !dir.is_dir() {
+            return Ok(versions);
+        }
+
+        for entry in read_dir(dir).unknown()? {
+            let file_name = entry.unknown()?.file_name();
+            let file_name = file_name.to_string_lossy();
+            if let Some(version) = D::archive_version(&file_name) {
+                if let Ok(version) = Version::parse(version) {
+                    versions.insert(version);
+                }
+            }
+        }
+
+        Ok(versions)
+    }
+
+    /// Provisions a distribution from its archive in the local cache.
+    fn load_cached(&self, version: Version) -> Fallible<D> {
+        let archive_file = D::archive_file(&version.to_string());
+        let file = File::open(D::cache_dir()?.join(archive_file)).unknown()?;
+        D::cached(version, file)
+    }
+
+    /// Lists the versions that can be used without accessing the network: those that are
+    /// installed, and those whose archive is in the local cache.
+    pub(crate) fn local_versions(&self) -> Fallible<BTreeSet<Version>> {
+        let mut versions = self.versions.clone();
+        versions.extend(self.cached_archives()?);
+        Ok(versions)
+    }
+
+    /// Resolves the specified semantic versioning requirements without accessing the network,
+    /// among the versions available locally, since only those can be fetched offline. The
+    /// cached index of the public distributor, even if it has expired, is only consulted for
+    /// the long-term support lines of those versions.
+    fn resolve_offline(&self, matching: &VersionSpec) -> Fallible<Version> {
+        let local = self.local_versions()?;
+        let index = D::cached_index()?;
+
+        if let Some(version) = find_match(&local, matching, &index) {
+            return Ok(version);
         }
+
+        throw!(NoOfflineVersionFoundError::new(
+            D::DISPLAY_NAME,
+            matching,
+            &local
+        ));
+    }
+
+    /// Fetches a version matching the specified semantic versioning requirements without
+    /// accessing the network, from its archive in the local cache.
+    fn fetch_offline(
+        &self,
+        matching: &VersionSpec,
+        config: Option<&ToolConfig>,
+    ) -> Fallible<Fetched> {
+        let local = self.local_versions()?;
+        let index = D::cached_index()?;
+
+        let version = match find_match(&local, matching, &index) {
+            Some(version) => version,
+            None => throw!(NoOfflineVersionFoundError::new(
+                D::DISPLAY_NAME,
+                matching,
+                &local
+            )),
+        };
+
+        if self.contains(&version) {
+            return Ok(Fetched::Already(version));
+        }
+
+        self.load_cached(version)?.fetch(self, config)
     }
+}
 
-    /// Resolves the specified semantic versioning requirements from the public distributor (e.g. `https://nodejs.org`).
-    fn resolve_public(&self, matching: &VersionSpec) -> Fallible<D>;
+/// Finds the newest version in the set that matches the specified semantic versioning
+/// requirements, looking up long-term support lines in the given index.
+fn find_match(
+    versions: &BTreeSet<Version>,
+    matching: &VersionSpec,
+    index: &BTreeMap<Version, Option<String>>,
+) -> Option<Version> {
+    versions
+        .iter()
+        .rev()
+        .find(|version| {
+            let lts = index.get(version).and_then(|lts| lts.as_ref());
+            spec_matches(matching, version, lts)
+        })
+        .cloned()
+}
+
+/// Tests whether a version, which belongs to the given long-term support line if any, matches
+/// the specified semantic versioning requirements. Every version matches `latest`, so callers
+/// are expected to search from newest to oldest.
+pub(crate) fn spec_matches(matching: &VersionSpec, version: &Version, lts: Option<&String>) -> bool {
+    match *matching {
+        VersionSpec::Latest => true,
+        VersionSpec::Semver(ref matching) => matching.matches(version),
+        VersionSpec::Lts => lts.is_some(),
+        VersionSpec::LtsLine(ref name) => lts.map_or(false, |lts| lts.to_lowercase() == *name),
+    }
 }
 
 /// Thrown when the public registry for Node or Yarn could not be downloaded.
@@ -295,95 +624,101 @@ impl RegistryFetchError {
     }
 }
 
-impl Resolve<NodeDistro> for NodeCollection {
-    fn resolve_public(&self, matching: &VersionSpec) -> Fallible<NodeDistro> {
-        let version_opt = {
-            let index: Index = resolve_node_versions()?.into_index()?;
-            let mut entries = index.entries.into_iter();
-            let entry = match *matching {
-                VersionSpec::Latest => {
-                    // NOTE: This assumes the registry always produces a list in sorted order
-                    //       from newest to oldest. This should be specified as a requirement
-                    //       when we document the plugin API.
-                    entries.next()
-                }
-                VersionSpec::Semver(ref matching) => {
-                    // ISSUE #34: also make sure this OS is available for this version
-                    entries.find(|&(ref k, _)| matching.matches(k))
-                }
-            };
-            entry.map(|(k, _)| k)
-        };
+/// Fetches the index of the public Node server, or of the given mirror of it, unless it is
+/// cached and hasn't expired.
+pub(crate) fn fetch_node_index(mirror: Option<&str>) -> Fallible<Index> {
+    resolve_node_versions(mirror)?.into_index()
+}
 
-        if let Some(version) = version_opt {
-            NodeDistro::public(version)
-        } else {
-            throw!(NoNodeVersionFoundError {
-                matching: matching.clone()
-            })
-        }
+/// Reads the locally cached index of the public Node server, if any, even if it has expired.
+pub(crate) fn read_node_index() -> Fallible<Option<Index>> {
+    match read_index_file()? {
+        Some(serial) => Ok(Some(serial.into_index()?)),
+        None => Ok(None),
     }
 }
 
-impl Resolve<YarnDistro> for YarnCollection {
-    /// Resolves the specified semantic versioning requirements from the public distributor.
-    fn resolve_public(&self, matching: &VersionSpec) -> Fallible<YarnDistro> {
-        let version = match *matching {
-            VersionSpec::Latest => {
-                let mut response: reqwest::Response =
-                    reqwest::get(public_yarn_latest_version().as_str())
-                        .with_context(RegistryFetchError::from_error)?;
-                response.text().unknown()?
-            }
-            VersionSpec::Semver(ref matching) => {
-                let spinner = progress_spinner(&format!(
-                    "Fetching public registry: {}",
-                    public_yarn_version_index()
-                ));
-                let releases: Vec<String> = reqwest::get(public_yarn_version_index().as_str())
-                    .with_context(RegistryFetchError::from_error)?
-                    .json()
-                    .unknown()?;
-                spinner.finish_and_clear();
-                let version = releases.into_iter().find(|v| {
-                    let v = Version::parse(v).unwrap();
-                    matching.matches(&v)
-                });
-
-                if let Some(version) = version {
-                    version
-                } else {
-                    throw!(NoYarnVersionFoundError {
-                        matching: matching.clone(),
-                    });
-                }
-            }
-        };
-        YarnDistro::public(Version::parse(&version).unknown()?)
+/// Resolves the specified semantic versioning requirements from the versions of a package
+/// published to the public npm registry, or to the given mirror of it.
+pub(crate) fn resolve_registry_package(
+    name: &str,
+    matching: &VersionSpec,
+    mirror: Option<&str>,
+) -> Fallible<Version> {
+    let package = fetch_registry_package(name, mirror)?;
+
+    let version = match matching {
+        &VersionSpec::Latest => Some(Version::parse(&package.dist_tags.latest).unknown()?),
+        &VersionSpec::Semver(ref requirements) => package
+            .into_versions()?
+            .into_iter()
+            .find(|version| requirements.matches(version)),
+        &VersionSpec::Lts | &VersionSpec::LtsLine(_) => {
+            throw!(NoLtsReleasesError {
+                tool: name.to_string(),
+            });
+        }
+    };
+
+    if let Some(version) = version {
+        Ok(version)
+    } else {
+        throw!(NoPackageVersionFoundError {
+            package: name.to_string(),
+            matching: matching.clone(),
+        })
     }
 }
 
+/// Lists the versions of a package published to the public npm registry, or to the given
+/// mirror of it, sorted from newest to oldest.
+pub(crate) fn ls_registry_package(name: &str, mirror: Option<&str>) -> Fallible<Vec<Version>> {
+    fetch_registry_package(name, mirror)?.into_versions()
+}
+
+/// Fetches the metadata of a package from the public npm registry, or from the given
+/// mirror of it.
+fn fetch_registry_package(name: &str, mirror: Option<&str>) -> Fallible<serial::RegistryPackage> {
+    let url = registry_package_index(name, mirror);
+    let spinner = progress_spinner(&format!("Fetching public registry: {}", url));
+    let package: serial::RegistryPackage = reqwest::get(url.as_str())
+        .with_context(RegistryFetchError::from_error)?
+        .json()
+        .unknown()?;
+    spinner.finish_and_clear();
+    Ok(package)
+}
+
 /// The index of the public Node server.
 pub struct Index {
-    entries: Vec<(Version, VersionData)>,
+    pub(crate) entries: Vec<(Version, VersionData)>,
 }
 
-/// The set of available files on the public Node server for a given Node version.
+/// The data the public Node server provides for a given Node version.
 pub struct VersionData {
+    /// The set of available files.
     pub files: HashSet<String>,
+    /// The codename of the long-term support line the version belongs to, if any.
+    pub lts: Option<String>,
 }
 
 impl FromStr for Catalog {
     type Err = NotionError;
 
     fn from_str(src: &str) -> Result<Self, Self::Err> {
-        let serial: serial::Catalog = toml::from_str(src).unknown()?;
+        let serial = serial::Catalog(toml::from_str(src).unknown()?);
         Ok(serial.into_catalog()?)
     }
 }
 
-/// Reads a public index from the Node cache, if it exists and hasn't expired.
-fn read_cached_opt() -> Fallible<Option<serial::Index>> {
+/// Reads a public index from the Node cache, if it was fetched from the given URL and hasn't
+/// expired. (An index cached from a different mirror may not list the same versions.)
+fn read_cached_opt(url: &str) -> Fallible<Option<serial::Index>> {
+    let source: Option<String> = read_file_opt(&path::node_index_source_file()?).unknown()?;
+    if source.as_ref().map(|source| source.as_str()) != Some(url) {
+        return Ok(None);
+    }
+
     let expiry: Option<String> = read_file_opt(&path::node_index_expiry_file()?).unknown()?;
 
     if let Some(string) = expiry {
@@ -391,17 +726,24 @@ fn read_cached_opt() -> Fallible<Option<serial::Index>> {
         let current_date: HttpDate = HttpDate::from(SystemTime::now());
 
         if current_date < expiry_date {
-            let cached: Option<String> = read_file_opt(&path::node_index_file()?).unknown()?;
-
-            if let Some(string) = cached {
-                return Ok(serde_json::de::from_str(&string).unknown()?);
-            }
+            return read_index_file();
         }
     }
 
     Ok(None)
 }
 
+/// Reads the public index from the Node cache, if it exists, regardless of whether it has expired.
+fn read_index_file() -> Fallible<Option<serial::Index>> {
+    let cached: Option<String> = read_file_opt(&path::node_index_file()?).unknown()?;
+
+    if let Some(string) = cached {
+        return Ok(serde_json::de::from_str(&string).unknown()?);
+    }
+
+    Ok(None)
+}
+
 /// Get the cache max-age of an HTTP reponse.
 fn max_age(response: &reqwest::Response) -> u32 {
     if let Some(cache_control_header) = response.headers().get::<CacheControl>() {
@@ -416,19 +758,16 @@ fn max_age(response: &reqwest::Response) -> u32 {
     4 * 60 * 60
 }
 
-fn resolve_node_versions() -> Result<serial::Index, NotionError> {
-    match read_cached_opt().unknown()? {
+fn resolve_node_versions(mirror: Option<&str>) -> Result<serial::Index, NotionError> {
+    let url = node_version_index(mirror);
+    match read_cached_opt(&url).unknown()? {
         Some(serial) => Ok(serial),
         None => {
-            let spinner = progress_spinner(&format!(
-                "Fetching public registry: {}",
-                public_node_version_index()
-            ));
-            let mut response: reqwest::Response = reqwest::get(
-                public_node_version_index().as_str(),
-            ).with_context(RegistryFetchError::from_error)?;
+            let spinner = progress_spinner(&format!("Fetching public registry: {}", url));
+            let mut response: reqwest::Response =
+                reqwest::get(url.as_str()).with_context(RegistryFetchError::from_error)?;
             let response_text: String = response.text().unknown()?;
-            let cached: NamedTempFile = NamedTempFile::new().unknown()?;
+            let cached: NamedTempFile = create_staging_file()?;
 
             // Block to borrow cached for cached_file.
             {
@@ -436,11 +775,17 @@ fn resolve_node_versions() -> Result<serial::Index, NotionError> {
                 cached_file.write(response_text.as_bytes()).unknown()?;
             }
 
+            // Forget where the old index came from until the new one is in place.
+            let index_source_file = path::node_index_source_file()?;
+            if index_source_file.exists() {
+                remove_file(&index_source_file).unknown()?;
+            }
+
             let index_cache_file = path::node_index_file()?;
             ensure_containing_dir_exists(&index_cache_file)?;
             cached.persist(index_cache_file).unknown()?;
 
-            let expiry: NamedTempFile = NamedTempFile::new().unknown()?;
+            let expiry: NamedTempFile = create_staging_file()?;
 
             // Block to borrow expiry for expiry_file.
             {
@@ -460,6 +805,16 @@ fn resolve_node_versions() -> Result<serial::Index, NotionError> {
             ensure_containing_dir_exists(&index_expiry_file)?;
             expiry.persist(index_expiry_file).unknown()?;
 
+            let source: NamedTempFile = create_staging_file()?;
+
+            // Block to borrow source for source_file.
+            {
+                let mut source_file: &File = source.as_file();
+                source_file.write_all(url.as_bytes()).unknown()?;
+            }
+
+            source.persist(index_source_file).unknown()?;
+
             let serial: serial::Index = serde_json::de::from_str(&response_text).unknown()?;
 
             spinner.finish_and_clear();
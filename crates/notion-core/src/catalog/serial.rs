@@ -1,84 +1,97 @@
-use std::collections::{BTreeSet, HashSet};
-use std::default::Default;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::string::ToString;
 
+use distro::Distro;
 use notion_fail::{Fallible, ResultExt};
+use registry::{self, Visitor};
 
 use semver::{SemVerError, Version};
+use serde::de::IgnoredAny;
+use toml;
 
-#[derive(Serialize, Deserialize)]
-pub struct Catalog {
-    #[serde(default)]
-    node: NodeCollection,
-    #[serde(default)]
-    yarn: YarnCollection,
-}
+/// The catalog file, with a table for the collection of each tool and a `packages` table.
+pub struct Catalog(pub BTreeMap<String, toml::Value>);
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename = "node")]
-pub struct NodeCollection {
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Collection {
     default: Option<String>,
+    #[serde(default)]
     versions: Vec<String>,
 }
 
+/// A package installed with `notion install`.
 #[derive(Serialize, Deserialize)]
-#[serde(rename = "yarn")]
-pub struct YarnCollection {
-    default: Option<String>,
-    versions: Vec<String>,
+pub struct Package {
+    version: String,
+    node: String,
+    #[serde(default)]
+    bins: Vec<String>,
 }
 
-impl Default for NodeCollection {
-    fn default() -> Self {
-        NodeCollection {
-            default: None,
-            versions: vec![],
-        }
-    }
-}
+impl Catalog {
+    pub fn into_catalog(mut self) -> Fallible<super::Catalog> {
+        let packages: BTreeMap<String, Package> = match self.0.remove("packages") {
+            Some(value) => value.try_into().unknown()?,
+            None => BTreeMap::new(),
+        };
 
-impl Default for YarnCollection {
-    fn default() -> Self {
-        YarnCollection {
-            default: None,
-            versions: vec![],
-        }
-    }
-}
+        let mut loader = Loader {
+            serial: self.0,
+            collections: BTreeMap::new(),
+        };
+        registry::visit(&mut loader)?;
 
-impl Catalog {
-    pub fn into_catalog(self) -> Fallible<super::Catalog> {
         Ok(super::Catalog {
-            node: self.node.into_node_collection().unknown()?,
-            yarn: self.yarn.into_yarn_collection().unknown()?,
+            collections: loader.collections,
+            packages: packages
+                .into_iter()
+                .map(|(name, package)| Ok((name, package.into_package()?)))
+                .collect::<Fallible<_>>()?,
+            others: loader.serial,
         })
     }
 }
 
-impl NodeCollection {
-    fn into_node_collection(self) -> Fallible<super::NodeCollection> {
-        let default = match self.default {
-            Some(v) => Some(Version::parse(&v[..]).unknown()?),
-            None => None,
-        };
+/// Takes the collection of each tool in the registry out of the catalog file.
+struct Loader {
+    serial: BTreeMap<String, toml::Value>,
+    collections: BTreeMap<&'static str, Box<super::AnyCollection>>,
+}
 
-        let versions: Result<Vec<Version>, SemVerError> = self.versions
-            .into_iter()
-            .map(|s| Ok(Version::parse(&s[..])?))
-            .collect();
+impl Visitor for Loader {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        let collection: Collection = match self.serial.remove(D::NAME) {
+            Some(value) => value.try_into().unknown()?,
+            None => Collection::default(),
+        };
+        self.collections
+            .insert(D::NAME, Box::new(collection.into_collection::<D>()?));
+        Ok(())
+    }
+}
 
-        Ok(super::NodeCollection {
-            default,
-            versions: BTreeSet::from_iter(versions.unknown()?),
-            phantom: PhantomData,
+impl Package {
+    fn into_package(self) -> Fallible<super::Package> {
+        Ok(super::Package {
+            version: Version::parse(&self.version).unknown()?,
+            node: Version::parse(&self.node).unknown()?,
+            bins: self.bins,
         })
     }
+
+    fn from_package(package: &super::Package) -> Self {
+        Package {
+            version: package.version.to_string(),
+            node: package.node.to_string(),
+            bins: package.bins.clone(),
+        }
+    }
 }
 
-impl YarnCollection {
-    fn into_yarn_collection(self) -> Fallible<super::YarnCollection> {
+impl Collection {
+    fn into_collection<D: Distro>(self) -> Fallible<super::Collection<D>> {
         let default = match self.default {
             Some(v) => Some(Version::parse(&v[..]).unknown()?),
             None => None,
@@ -89,37 +102,45 @@ impl YarnCollection {
             .map(|s| Ok(Version::parse(&s[..])?))
             .collect();
 
-        Ok(super::YarnCollection {
+        Ok(super::Collection {
             default,
             versions: BTreeSet::from_iter(versions.unknown()?),
             phantom: PhantomData,
         })
     }
-}
 
-impl super::Catalog {
-    pub fn to_serial(&self) -> Catalog {
-        Catalog {
-            node: self.node.to_serial(),
-            yarn: self.yarn.to_serial(),
+    pub(super) fn from_collection<D: Distro>(collection: &super::Collection<D>) -> Self {
+        Collection {
+            default: collection.default.clone().map(|v| v.to_string()),
+            versions: collection.versions.iter().map(|v| v.to_string()).collect(),
         }
     }
 }
-impl super::NodeCollection {
-    fn to_serial(&self) -> NodeCollection {
-        NodeCollection {
-            default: self.default.clone().map(|v| v.to_string()),
-            versions: self.versions.iter().map(|v| v.to_string()).collect(),
+
+impl super::Catalog {
+    pub fn to_serial(&self) -> Catalog {
+        // Converting tables of strings to TOML values can't fail.
+        let mut tables = self.others.clone();
+        for (name, collection) in self.collections.iter() {
+            tables.insert(
+                name.to_string(),
+                toml::Value::try_from(collection.to_serial()).unwrap(),
+            );
         }
-    }
-}
 
-impl super::YarnCollection {
-    fn to_serial(&self) -> YarnCollection {
-        YarnCollection {
-            default: self.default.clone().map(|v| v.to_string()),
-            versions: self.versions.iter().map(|v| v.to_string()).collect(),
+        if !self.packages.is_empty() {
+            let packages: BTreeMap<&String, Package> = self
+                .packages
+                .iter()
+                .map(|(name, package)| (name, Package::from_package(package)))
+                .collect();
+            tables.insert(
+                "packages".to_string(),
+                toml::Value::try_from(packages).unwrap(),
+            );
         }
+
+        Catalog(tables)
     }
 }
 
@@ -130,6 +151,16 @@ pub struct Index(Vec<Entry>);
 pub struct Entry {
     pub version: String,
     pub files: Vec<String>,
+    pub lts: Option<Lts>,
+}
+
+/// The `lts` field of an index entry, which is either the codename of the LTS line the
+/// version belongs to or `false`.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Lts {
+    Codename(String),
+    Flag(bool),
 }
 
 impl Index {
@@ -138,6 +169,10 @@ impl Index {
         for entry in self.0 {
             let data = super::VersionData {
                 files: HashSet::from_iter(entry.files.into_iter()),
+                lts: match entry.lts {
+                    Some(Lts::Codename(name)) => Some(name),
+                    _ => None,
+                },
             };
             let mut version = &entry.version[..];
             version = version.trim();
@@ -149,3 +184,97 @@ impl Index {
         Ok(super::Index { entries })
     }
 }
+
+/// The metadata the npm registry publishes about a package.
+#[derive(Deserialize)]
+pub struct RegistryPackage {
+    #[serde(rename = "dist-tags")]
+    pub dist_tags: DistTags,
+    pub versions: HashMap<String, IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+pub struct DistTags {
+    pub latest: String,
+}
+
+impl RegistryPackage {
+    /// Returns the published versions of the package, sorted from newest to oldest.
+    pub fn into_versions(self) -> Fallible<Vec<Version>> {
+        let mut versions = self
+            .versions
+            .keys()
+            .map(|version| Version::parse(version).unknown())
+            .collect::<Fallible<Vec<Version>>>()?;
+        versions.sort_by(|a, b| b.cmp(a));
+        Ok(versions)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use catalog::Catalog;
+
+    const CATALOG: &'static str = "\
+[node]
+default = '10.13.0'
+versions = [ '8.9.4', '10.13.0' ]
+
+[packages.ember-cli]
+version = '3.5.0'
+node = '8.9.4'
+bins = [ 'ember' ]
+
+[packages.typescript]
+version = '3.1.6'
+node = '10.13.0'
+bins = [ 'tsc', 'tsserver' ]
+
+[packages.left-pad]
+version = '1.3.0'
+node = '10.13.0'
+";
+
+    #[test]
+    fn packages_round_trip() {
+        let catalog: Catalog = CATALOG.parse().expect("Could not parse catalog");
+        let reparsed: Catalog = catalog
+            .to_string()
+            .parse()
+            .expect("Could not parse serialized catalog");
+
+        for catalog in &[catalog, reparsed] {
+            assert_eq!(catalog.packages.len(), 3);
+
+            let ember = &catalog.packages["ember-cli"];
+            assert_eq!(ember.version.to_string(), "3.5.0");
+            assert_eq!(ember.node.to_string(), "8.9.4");
+            assert_eq!(ember.bins, vec!["ember".to_string()]);
+
+            let typescript = &catalog.packages["typescript"];
+            assert_eq!(
+                typescript.bins,
+                vec!["tsc".to_string(), "tsserver".to_string()]
+            );
+
+            // a package without executables has no `bins`
+            assert!(catalog.packages["left-pad"].bins.is_empty());
+        }
+    }
+
+    #[test]
+    fn no_packages_table_without_packages() {
+        let catalog: Catalog = "[node]\nversions = [ '10.13.0' ]\n"
+            .parse()
+            .expect("Could not parse catalog");
+        assert!(catalog.packages.is_empty());
+        assert!(!catalog.to_serial().0.contains_key("packages"));
+    }
+
+    #[test]
+    fn invalid_package_version() {
+        let src = "[packages.ember-cli]\nversion = 'three'\nnode = '8.9.4'\n";
+        assert!(src.parse::<Catalog>().is_err());
+    }
+}
@@ -0,0 +1,614 @@
+//! Provides format-preserving edits of the entries of a TOML document, so that updating
+//! `config.toml` leaves its comments, whitespace and ordering exactly as they were, and
+//! locates the entries of TOML files such as `config.toml` and `catalog.toml` for error
+//! messages.
+
+use std::ops::Range;
+use std::path::Path;
+
+use fs::{FileLocation, InvalidFileValueError};
+use notion_fail::{Fallible, ResultExt};
+use serde_json;
+use toml;
+
+/// A `key = value` entry of a TOML document.
+struct Entry {
+    /// The path of the table the entry belongs to, e.g. `["node", "resolve"]`.
+    table: Vec<String>,
+    key: String,
+    /// The span of the line(s) of the entry, including its comment and line ending.
+    line: Range<usize>,
+    /// The span of the entry's value.
+    value: Range<usize>,
+}
+
+/// A `[table]` header of a TOML document.
+struct Header {
+    table: Vec<String>,
+    /// The span of the header's line, including its comment and line ending.
+    line: Range<usize>,
+}
+
+/// The headers and entries of a TOML document, in source order.
+struct Document {
+    headers: Vec<Header>,
+    entries: Vec<Entry>,
+}
+
+impl Document {
+    fn header(&self, table: &[&str]) -> Option<&Header> {
+        self.headers.iter().find(|header| header.table == table)
+    }
+
+    fn entries<'a>(&'a self, table: &'a [&str]) -> impl Iterator<Item = &'a Entry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.table == table)
+    }
+}
+
+/// Returns true if a TOML document has a `[table]` header for the given table.
+pub(crate) fn has_table(src: &str, table: &[&str]) -> Fallible<bool> {
+    Ok(parse_document(src)?.header(table).is_some())
+}
+
+/// Sets the key of a table to a value (already rendered as TOML), replacing the current value
+/// in place or, if it's missing, adding it after the last entry of the table. If the table
+/// has no header, one is appended to the document. Everything else is left unchanged.
+pub(crate) fn set_entry(src: &str, table: &[&str], key: &str, value: &str) -> Fallible<String> {
+    let document = parse_document(src)?;
+    let eol = line_ending(src);
+
+    let mut result = String::with_capacity(src.len() + value.len());
+    if let Some(entry) = document.entries(table).find(|entry| entry.key == key) {
+        result.push_str(&src[..entry.value.start]);
+        result.push_str(value);
+        result.push_str(&src[entry.value.end..]);
+        return Ok(result);
+    }
+
+    let line = format!("{} = {}{}", render_key(key), value, eol);
+    match document.header(table) {
+        Some(header) => {
+            let pos = document
+                .entries(table)
+                .last()
+                .map_or(header.line.end, |entry| entry.line.end);
+            result.push_str(&src[..pos]);
+            if pos > 0 && !src[..pos].ends_with('\n') {
+                result.push_str(eol);
+            }
+            result.push_str(&line);
+            result.push_str(&src[pos..]);
+        }
+        None => {
+            result.push_str(src);
+            if !src.is_empty() && !src.ends_with('\n') {
+                result.push_str(eol);
+            }
+            if !src.trim().is_empty() {
+                result.push_str(eol);
+            }
+            let path: Vec<String> = table.iter().map(|name| render_key(name)).collect();
+            result.push_str(&format!("[{}]{}", path.join("."), eol));
+            result.push_str(&line);
+        }
+    }
+    Ok(result)
+}
+
+/// Removes the key of a table, along with the rest of its line, leaving the rest of the source
+/// unchanged. Returns `None` if the key is missing.
+pub(crate) fn remove_entry(src: &str, table: &[&str], key: &str) -> Fallible<Option<String>> {
+    let document = parse_document(src)?;
+    let removed = document
+        .entries(table)
+        .find(|entry| entry.key == key)
+        .map(|entry| remove_span(src, &entry.line));
+    Ok(removed)
+}
+
+/// Removes a table, from its header up to the next header, leaving the rest of the source
+/// unchanged. Returns `None` if the table has no header.
+pub(crate) fn remove_table(src: &str, table: &[&str]) -> Fallible<Option<String>> {
+    let document = parse_document(src)?;
+    let start = match document.header(table) {
+        Some(header) => header.line.start,
+        None => {
+            return Ok(None);
+        }
+    };
+    let end = document
+        .headers
+        .iter()
+        .map(|header| header.line.start)
+        .find(|&pos| pos > start)
+        .unwrap_or(src.len());
+    Ok(Some(remove_span(src, &(start..end))))
+}
+
+/// Returns the position of the key of an entry, or of the header of a table, given its dotted
+/// path, e.g. `["node", "mirror"]`. An entry of an inline table is located at the key of the
+/// inline table. Returns `None` if there's no such entry or the document is invalid.
+pub(crate) fn locate(src: &str, path: &[&str]) -> Option<usize> {
+    let document = parse_document(src).ok()?;
+    let mut path = path;
+    while let Some((key, table)) = path.split_last() {
+        let start = match document.header(path) {
+            Some(header) => Some(header.line.start),
+            None => document
+                .entries(table)
+                .find(|entry| entry.key == *key)
+                .map(|entry| entry.line.start),
+        };
+        if let Some(start) = start {
+            return Some(skip_blank(src.as_bytes(), start));
+        }
+        path = table;
+    }
+    None
+}
+
+/// Checks each entry of each table of a parsed TOML document on its own, as a document with
+/// just that entry, so that an invalid entry is reported along with its key and position.
+/// (A top-level value that isn't a table is checked as a whole.)
+pub(crate) fn check_entries<F>(
+    path: &Path,
+    src: &str,
+    tables: &toml::value::Table,
+    check: F,
+) -> Fallible<()>
+where
+    F: Fn(toml::value::Table) -> Fallible<()>,
+{
+    for (name, table) in tables {
+        let entries: Vec<(Vec<&str>, toml::Value)> = match table.as_table() {
+            Some(entries) => entries
+                .iter()
+                .map(|(key, value)| {
+                    let mut entry = toml::value::Table::new();
+                    entry.insert(key.clone(), value.clone());
+                    (vec![&name[..], &key[..]], toml::Value::Table(entry))
+                })
+                .collect(),
+            None => vec![(vec![&name[..]], table.clone())],
+        };
+        for (key, value) in entries {
+            let mut single = toml::value::Table::new();
+            single.insert(name.clone(), value);
+            if let Err(error) = check(single) {
+                let location = FileLocation::at_offset(path, src, locate(src, &key));
+                throw!(InvalidFileValueError::new(key.join("."), location, &error));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Renders a string as a TOML basic string.
+fn render_string(s: &str) -> String {
+    let mut rendered = String::with_capacity(s.len() + 2);
+    rendered.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => rendered.push_str("\\\""),
+            '\\' => rendered.push_str("\\\\"),
+            '\n' => rendered.push_str("\\n"),
+            '\r' => rendered.push_str("\\r"),
+            '\t' => rendered.push_str("\\t"),
+            c if c.is_control() => rendered.push_str(&format!("\\u{:04X}", c as u32)),
+            c => rendered.push(c),
+        }
+    }
+    rendered.push('"');
+    rendered
+}
+
+/// Renders a key, quoting it unless it's a valid bare key.
+fn render_key(key: &str) -> String {
+    if !key.is_empty() && key.bytes().all(is_bare_key_byte) {
+        key.to_string()
+    } else {
+        render_string(key)
+    }
+}
+
+fn remove_span(src: &str, span: &Range<usize>) -> String {
+    let mut result = String::with_capacity(src.len());
+    result.push_str(&src[..span.start]);
+    result.push_str(&src[span.end..]);
+    result
+}
+
+/// Returns the line ending used by a source file.
+fn line_ending(src: &str) -> &'static str {
+    if src.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// Locates the headers and entries of a TOML document.
+fn parse_document(src: &str) -> Fallible<Document> {
+    // Validating the document up front means the scan below only sees well-formed TOML.
+    let _: toml::Value = toml::from_str(src).unknown()?;
+
+    let bytes = src.as_bytes();
+    let mut headers = vec![];
+    let mut entries = vec![];
+    let mut table = vec![];
+    let mut pos = 0;
+    loop {
+        pos = skip_trivia(bytes, pos);
+        if pos == bytes.len() {
+            break;
+        }
+        let line_start = start_of_line(bytes, pos);
+
+        if bytes[pos] == b'[' {
+            // an array of tables has a double bracket
+            let brackets = if bytes[pos + 1] == b'[' { 2 } else { 1 };
+            let (path, end) = parse_key_path(src, pos + brackets)?;
+            pos = end_of_line(bytes, end + brackets);
+            table = path;
+            headers.push(Header {
+                table: table.clone(),
+                line: line_start..pos,
+            });
+        } else {
+            let (key, end) = parse_key(src, pos)?;
+            // the equals sign
+            let value_start = skip_blank(bytes, skip_blank(bytes, end) + 1);
+            let value_end = skip_value(bytes, value_start);
+            pos = end_of_line(bytes, value_end);
+            entries.push(Entry {
+                table: table.clone(),
+                key,
+                line: line_start..pos,
+                value: value_start..value_end,
+            });
+        }
+    }
+
+    Ok(Document { headers, entries })
+}
+
+fn is_bare_key_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-'
+}
+
+/// Parses the dotted key path of a table header, returning it and the position of the
+/// closing bracket.
+fn parse_key_path(src: &str, mut pos: usize) -> Fallible<(Vec<String>, usize)> {
+    let bytes = src.as_bytes();
+    let mut path = vec![];
+    loop {
+        let (key, end) = parse_key(src, skip_blank(bytes, pos))?;
+        path.push(key);
+        pos = skip_blank(bytes, end);
+        if bytes[pos] == b'.' {
+            pos += 1;
+        } else {
+            return Ok((path, pos));
+        }
+    }
+}
+
+/// Parses a bare or quoted key, returning it and the position just after it.
+fn parse_key(src: &str, pos: usize) -> Fallible<(String, usize)> {
+    let bytes = src.as_bytes();
+    Ok(match bytes[pos] {
+        b'"' => {
+            let end = skip_basic_string(bytes, pos);
+            // Basic strings escape the same characters as JSON strings.
+            let key: String = serde_json::from_str(&src[pos..end]).unknown()?;
+            (key, end)
+        }
+        b'\'' => {
+            let end = skip_literal_string(bytes, pos);
+            (src[pos + 1..end - 1].to_string(), end)
+        }
+        _ => {
+            let mut end = pos;
+            while end < bytes.len() && is_bare_key_byte(bytes[end]) {
+                end += 1;
+            }
+            (src[pos..end].to_string(), end)
+        }
+    })
+}
+
+/// Skips spaces and tabs.
+fn skip_blank(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && (bytes[pos] == b' ' || bytes[pos] == b'\t') {
+        pos += 1;
+    }
+    pos
+}
+
+/// Skips whitespace, line endings and comments.
+fn skip_trivia(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b' ' | b'\t' | b'\r' | b'\n' => pos += 1,
+            b'#' => pos = skip_comment(bytes, pos),
+            // a byte order mark
+            0xEF if bytes[pos..].starts_with(b"\xEF\xBB\xBF") => pos += 3,
+            _ => break,
+        }
+    }
+    pos
+}
+
+/// Returns the position of the line ending that terminates the comment starting at `pos`.
+fn skip_comment(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && bytes[pos] != b'\n' {
+        pos += 1;
+    }
+    pos
+}
+
+/// Returns the position just after the line ending that follows `pos`, skipping any
+/// trailing whitespace and comment.
+fn end_of_line(bytes: &[u8], pos: usize) -> usize {
+    let mut pos = skip_blank(bytes, pos);
+    if pos < bytes.len() && bytes[pos] == b'#' {
+        pos = skip_comment(bytes, pos);
+    }
+    if pos < bytes.len() && bytes[pos] == b'\r' {
+        pos += 1;
+    }
+    if pos < bytes.len() && bytes[pos] == b'\n' {
+        pos += 1;
+    }
+    pos
+}
+
+/// Returns the position of the start of the line containing `pos`.
+fn start_of_line(bytes: &[u8], mut pos: usize) -> usize {
+    while pos > 0 && bytes[pos - 1] != b'\n' {
+        pos -= 1;
+    }
+    pos
+}
+
+/// Returns the position just after the basic string starting at `pos`, which may be a
+/// multi-line string.
+fn skip_basic_string(bytes: &[u8], mut pos: usize) -> usize {
+    if bytes[pos..].starts_with(b"\"\"\"") {
+        pos += 3;
+        while !bytes[pos..].starts_with(b"\"\"\"") {
+            if bytes[pos] == b'\\' {
+                pos += 1;
+            }
+            pos += 1;
+        }
+        // A multi-line string may end with up to two quotes of its own.
+        pos += 3;
+        while pos < bytes.len() && bytes[pos] == b'"' {
+            pos += 1;
+        }
+        return pos;
+    }
+
+    // the opening quote
+    pos += 1;
+    while bytes[pos] != b'"' {
+        if bytes[pos] == b'\\' {
+            pos += 1;
+        }
+        pos += 1;
+    }
+    pos + 1
+}
+
+/// Returns the position just after the literal string starting at `pos`, which may be a
+/// multi-line string.
+fn skip_literal_string(bytes: &[u8], mut pos: usize) -> usize {
+    if bytes[pos..].starts_with(b"'''") {
+        pos += 3;
+        while !bytes[pos..].starts_with(b"'''") {
+            pos += 1;
+        }
+        pos += 3;
+        while pos < bytes.len() && bytes[pos] == b'\'' {
+            pos += 1;
+        }
+        return pos;
+    }
+
+    // the opening quote
+    pos += 1;
+    while bytes[pos] != b'\'' {
+        pos += 1;
+    }
+    pos + 1
+}
+
+/// Returns the position just after the value starting at `pos`.
+fn skip_value(bytes: &[u8], mut pos: usize) -> usize {
+    match bytes[pos] {
+        b'"' => skip_basic_string(bytes, pos),
+        b'\'' => skip_literal_string(bytes, pos),
+        b'[' | b'{' => {
+            let mut depth = 0;
+            loop {
+                match bytes[pos] {
+                    b'"' => {
+                        pos = skip_basic_string(bytes, pos);
+                        continue;
+                    }
+                    b'\'' => {
+                        pos = skip_literal_string(bytes, pos);
+                        continue;
+                    }
+                    b'#' => {
+                        pos = skip_comment(bytes, pos);
+                        continue;
+                    }
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return pos + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+        }
+        _ => {
+            // Dates may contain spaces, so a scalar runs up to its comment or line ending.
+            let start = pos;
+            while pos < bytes.len() && !(bytes[pos] == b'#' || bytes[pos] == b'\n') {
+                pos += 1;
+            }
+            while pos > start && (bytes[pos - 1] as char).is_whitespace() {
+                pos -= 1;
+            }
+            pos
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{has_table, locate, remove_entry, remove_table, render_string, set_entry};
+
+    const CONFIG: &'static str = "# Notion settings\n\
+                                  [node]\n\
+                                  # where to download Node from\n\
+                                  mirror = \"https://nodejs.org\" # the default\n\
+                                  \n\
+                                  [node.resolve]\n\
+                                  url = \"https://example.com/resolve\"\n\
+                                  \n\
+                                  [events]\n\
+                                  publish = { bin = \"/usr/bin/publish\" }\n";
+
+    #[test]
+    fn replaces_existing_value_in_place() {
+        let expected = CONFIG.replace("https://nodejs.org", "https://mirror.example.com");
+        assert_eq!(
+            set_entry(
+                CONFIG,
+                &["node"],
+                "mirror",
+                "\"https://mirror.example.com\""
+            )
+            .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn appends_missing_key_to_its_table() {
+        let expected = CONFIG.replace(
+            "publish = { bin = \"/usr/bin/publish\" }\n",
+            "publish = { bin = \"/usr/bin/publish\" }\nextra = true\n",
+        );
+        assert_eq!(
+            set_entry(CONFIG, &["events"], "extra", "true").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn appends_missing_table() {
+        let expected = format!("{}\n[project]\nversion-files = true\n", CONFIG);
+        assert_eq!(
+            set_entry(CONFIG, &["project"], "version-files", "true").unwrap(),
+            expected
+        );
+        assert_eq!(
+            set_entry("", &["project"], "version-files", "true").unwrap(),
+            "[project]\nversion-files = true\n"
+        );
+    }
+
+    #[test]
+    fn adds_key_after_header_without_line_ending() {
+        assert_eq!(
+            set_entry("[yarn]", &["yarn"], "mirror", "\"x\"").unwrap(),
+            "[yarn]\nmirror = \"x\"\n"
+        );
+    }
+
+    #[test]
+    fn preserves_crlf() {
+        assert_eq!(
+            set_entry(
+                "[node]\r\nmirror = \"a\"\r\n",
+                &["node"],
+                "ls-remote",
+                "{ url = \"b\" }"
+            )
+            .unwrap(),
+            "[node]\r\nmirror = \"a\"\r\nls-remote = { url = \"b\" }\r\n"
+        );
+    }
+
+    #[test]
+    fn skips_multiline_values() {
+        let src = "[node]\nnotes = \"\"\"\n[yarn]\nmirror = 1\n\"\"\"\nlist = [\n  \"a\", # ]\n]\n";
+        let expected = format!("{}mirror = \"x\"\n", src);
+        assert_eq!(
+            set_entry(src, &["node"], "mirror", "\"x\"").unwrap(),
+            expected
+        );
+        assert!(!has_table(src, &["yarn"]).unwrap());
+    }
+
+    #[test]
+    fn removes_entry_with_its_comment() {
+        let expected = CONFIG.replace("mirror = \"https://nodejs.org\" # the default\n", "");
+        assert_eq!(
+            remove_entry(CONFIG, &["node"], "mirror").unwrap().unwrap(),
+            expected
+        );
+        assert!(remove_entry(CONFIG, &["yarn"], "mirror").unwrap().is_none());
+    }
+
+    #[test]
+    fn removes_table() {
+        let expected = CONFIG.replace(
+            "[node.resolve]\nurl = \"https://example.com/resolve\"\n\n",
+            "",
+        );
+        assert!(has_table(CONFIG, &["node", "resolve"]).unwrap());
+        assert_eq!(
+            remove_table(CONFIG, &["node", "resolve"]).unwrap().unwrap(),
+            expected
+        );
+        assert!(remove_table(CONFIG, &["yarn"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn locates_entries() {
+        let at =
+            |path: &[&str]| locate(CONFIG, path).map(|pos| CONFIG[pos..].lines().next().unwrap());
+        assert_eq!(
+            at(&["node", "mirror"]),
+            Some("mirror = \"https://nodejs.org\" # the default")
+        );
+        assert_eq!(at(&["node", "resolve"]), Some("[node.resolve]"));
+        assert_eq!(
+            at(&["events", "publish", "bin"]),
+            Some("publish = { bin = \"/usr/bin/publish\" }")
+        );
+        assert_eq!(at(&["yarn", "mirror"]), None);
+    }
+
+    #[test]
+    fn renders_strings() {
+        assert_eq!(render_string("C:\\bin \"x\""), "\"C:\\\\bin \\\"x\\\"\"");
+    }
+
+    #[test]
+    fn rejects_invalid_toml() {
+        assert!(set_entry("[node", &["node"], "mirror", "\"x\"").is_err());
+    }
+}
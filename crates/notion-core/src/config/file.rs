@@ -0,0 +1,943 @@
+//! Provides types for reading and editing the settings of a Notion configuration file.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use cmdline_words_parser::StrExt;
+use tempfile::NamedTempFile;
+use toml;
+
+use super::edit;
+use super::serial::{self, Setting};
+use super::{Config, ConfigLevel};
+use env;
+use fs::{create_staging_file, ensure_containing_dir_exists, read_file_opt, FileParseError};
+use notion_fail::{ExitCode, Fallible, NotionError, NotionFail, ResultExt};
+use path::{system_config_file, user_config_file};
+use project::Project;
+use registry;
+use session::NotInPackageError;
+
+cfg_if! {
+    if #[cfg(windows)] {
+        const DEFAULT_EDITOR: &'static str = "notepad";
+    } else {
+        const DEFAULT_EDITOR: &'static str = "vi";
+    }
+}
+
+/// Thrown when a configuration key doesn't name a setting of the configuration file.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Unknown configuration key: `{}`", key)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct UnknownConfigKeyError {
+    pub(crate) key: String,
+}
+
+/// Thrown when a value can't be assigned to a configuration setting.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(
+    display = "Invalid value for `{}`: `{}` (expected {})",
+    key, value, expected
+)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct InvalidConfigValueError {
+    key: String,
+    value: String,
+    expected: &'static str,
+}
+
+/// Thrown when environment variables set both the `url` and the `bin` of a plugin.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(
+    display = "Both {} and {} are set\nA plugin can have either a url or a bin, but not both",
+    first, second
+)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct ConflictingEnvVarsError {
+    first: String,
+    second: String,
+}
+
+/// Thrown when a project's `.notionrc.toml` sets anything other than the URL plugins that
+/// resolve and list the versions of a tool, since the other settings are up to the user. (A
+/// `bin` plugin would run whatever command a checked-out project names.)
+#[derive(Debug, Fail, NotionFail)]
+#[fail(
+    display = "only the `resolve.url` and `ls-remote.url` plugins of a tool can be set in a project"
+)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct ProjectSettingError;
+
+/// The settings of each tool that a project's `.notionrc.toml` can set, as `url` plugins only.
+const PROJECT_SETTINGS: [&'static str; 2] = ["resolve", "ls-remote"];
+
+/// Thrown when a setting is written in a way that can't be edited in place, such as an inline
+/// table at the top level of the file.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(
+    display = "Could not update `{}` in {}\nUse `notion config edit` to change it by hand",
+    key, file
+)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct ConfigEditError {
+    key: String,
+    file: String,
+}
+
+/// Thrown when the user's editor could not be run.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Could not run editor `{}`: {}", command, error)]
+#[notion_fail(code = "ExecutionFailure")]
+pub(crate) struct EditorError {
+    command: String,
+    error: String,
+}
+
+impl EditorError {
+    fn for_command(command: &str) -> impl FnOnce(&io::Error) -> EditorError {
+        let command = command.to_string();
+        move |error| EditorError {
+            command,
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Thrown when an edited configuration file is invalid, in which case the edit is kept in a
+/// draft file instead of replacing the configuration.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(
+    display = "The edited configuration is invalid: {}\nYour changes have been kept in {}",
+    error, file
+)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct InvalidConfigEditError {
+    error: String,
+    file: String,
+}
+
+/// A dotted key naming a setting of the configuration file, e.g. `node.mirror` or
+/// `events.publish.url`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct ConfigKey {
+    table: String,
+    name: String,
+    /// The field of a plugin, `url` or `bin`, which are mutually exclusive.
+    field: Option<&'static str>,
+}
+
+impl ConfigKey {
+    /// Lists the keys of every setting of the configuration file, sorted by name.
+    pub fn all() -> Vec<ConfigKey> {
+        let mut tables = vec![
+            ("events".to_string(), vec!["publish"]),
+            (
+                "project".to_string(),
+                vec!["version-files", "resolve-engines"],
+            ),
+        ];
+        for tool in registry::tools() {
+            tables.push((
+                tool.name().to_string(),
+                vec!["resolve", "ls-remote", "mirror", "require-checksums"],
+            ));
+        }
+
+        let mut keys = vec![];
+        for (table, names) in tables {
+            for name in names {
+                match serial::setting(&table, name) {
+                    Some(Setting::Plugin) => {
+                        for field in &["url", "bin"] {
+                            keys.push(ConfigKey::new(&table, name, Some(*field)));
+                        }
+                    }
+                    Some(_) => keys.push(ConfigKey::new(&table, name, None)),
+                    None => {}
+                }
+            }
+        }
+        keys.sort();
+        keys
+    }
+
+    fn new(table: &str, name: &str, field: Option<&'static str>) -> Self {
+        ConfigKey {
+            table: table.to_string(),
+            name: name.to_string(),
+            field,
+        }
+    }
+
+    /// Looks up the value of the setting in a parsed configuration file.
+    fn lookup<'a>(&self, document: &'a toml::Value) -> Option<&'a toml::Value> {
+        let value = self.lookup_setting(document)?;
+        match self.field {
+            Some(field) => value.get(field),
+            None => Some(value),
+        }
+    }
+
+    /// Looks up the setting the key belongs to in a parsed configuration file, which for the
+    /// `url` or `bin` of a plugin is the whole plugin.
+    fn lookup_setting<'a>(&self, document: &'a toml::Value) -> Option<&'a toml::Value> {
+        document.get(&self.table)?.get(&self.name)
+    }
+
+    /// Returns the name of the environment variable that overrides the setting, e.g.
+    /// `NOTION_YARN_RESOLVE_URL` for `yarn.resolve.url`.
+    pub fn env_var(&self) -> String {
+        env::config_var(&self.to_string())
+    }
+
+    /// Parses a value given on the command line into a value of the setting's type.
+    fn parse_value(&self, value: &str) -> Fallible<toml::Value> {
+        self.parse_value_from(self.to_string(), value)
+    }
+
+    /// Parses a value given in the setting's environment variable.
+    fn parse_env_value(&self, value: &str) -> Fallible<toml::Value> {
+        self.parse_value_from(self.env_var(), value)
+    }
+
+    /// Parses a value into a value of the setting's type, naming the source of the value
+    /// (the key or an environment variable) if it's invalid.
+    fn parse_value_from(&self, source: String, value: &str) -> Fallible<toml::Value> {
+        Ok(match serial::setting(&self.table, &self.name) {
+            Some(Setting::Bool) => match value {
+                "true" => toml::Value::Boolean(true),
+                "false" => toml::Value::Boolean(false),
+                _ => {
+                    throw!(InvalidConfigValueError {
+                        key: source,
+                        value: value.to_string(),
+                        expected: "true or false",
+                    });
+                }
+            },
+            _ => toml::Value::String(value.to_string()),
+        })
+    }
+}
+
+impl FromStr for ConfigKey {
+    type Err = NotionError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let segments: Vec<&str> = src.split('.').collect();
+        let key = match (
+            segments.len(),
+            serial::setting(segments[0], segments.get(1).unwrap_or(&"")),
+        ) {
+            (2, Some(Setting::Bool)) | (2, Some(Setting::String)) => {
+                ConfigKey::new(segments[0], segments[1], None)
+            }
+            (3, Some(Setting::Plugin)) => {
+                let field = match segments[2] {
+                    "url" => "url",
+                    "bin" => "bin",
+                    _ => {
+                        throw!(UnknownConfigKeyError {
+                            key: src.to_string(),
+                        });
+                    }
+                };
+                ConfigKey::new(segments[0], segments[1], Some(field))
+            }
+            _ => {
+                throw!(UnknownConfigKeyError {
+                    key: src.to_string(),
+                });
+            }
+        };
+        Ok(key)
+    }
+}
+
+impl Display for ConfigKey {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.table, self.name)?;
+        if let Some(field) = self.field {
+            write!(f, ".{}", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a setting in effect comes from.
+pub enum ConfigOrigin<'a> {
+    File(&'a ConfigFile),
+    /// An environment variable, such as `NOTION_NODE_MIRROR`.
+    Environment(String),
+}
+
+impl<'a> Display for ConfigOrigin<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            &ConfigOrigin::File(file) => write!(f, "{}", file.path().display()),
+            &ConfigOrigin::Environment(ref var) => f.write_str(var),
+        }
+    }
+}
+
+/// The configuration files that apply in the current directory, from the lowest level to the
+/// highest: the system-wide file, the user's file and, in a project, its `.notionrc.toml`
+/// (which can only set the `resolve` and `ls-remote` plugins of tools).
+/// Environment variables named after the keys of settings, such as `NOTION_NODE_MIRROR`,
+/// override all of them.
+pub struct ConfigLayers {
+    files: Vec<ConfigFile>,
+    environment: toml::Value,
+}
+
+impl ConfigLayers {
+    /// Loads the configuration files that apply to a project, or outside of any project.
+    pub fn current(project: Option<&Project>) -> Fallible<Self> {
+        let mut files = vec![
+            ConfigFile::for_level(ConfigLevel::System, None)?,
+            ConfigFile::for_level(ConfigLevel::User, None)?,
+        ];
+        if project.is_some() {
+            files.push(ConfigFile::for_level(ConfigLevel::Project, project)?);
+        }
+        Ok(ConfigLayers {
+            files,
+            environment: environment(|var| ::std::env::var_os(var))?,
+        })
+    }
+
+    /// Merges the files and environment variables into a single configuration, in which each
+    /// setting comes from the highest level that sets it.
+    pub fn config(&self) -> Fallible<Config> {
+        let documents = self
+            .files
+            .iter()
+            .map(|file| file.document.clone())
+            .chain(Some(self.environment.clone()));
+        serial::Config::merge(documents).into_config()
+    }
+
+    /// Returns the value of a setting in effect, along with where it comes from, or `None` if
+    /// nothing sets it.
+    pub fn get<'a>(&'a self, key: &ConfigKey) -> Option<(String, ConfigOrigin<'a>)> {
+        // A plugin is overridden as a whole, so its `url` and `bin` come from the same layer.
+        if key.lookup_setting(&self.environment).is_some() {
+            return key.lookup(&self.environment).map(|value| {
+                (
+                    display_value(value),
+                    ConfigOrigin::Environment(key.env_var()),
+                )
+            });
+        }
+        self.files
+            .iter()
+            .rev()
+            .find(|file| key.lookup_setting(&file.document).is_some())
+            .and_then(|file| file.get(key).map(|value| (value, ConfigOrigin::File(file))))
+    }
+
+    /// Lists the settings in effect, sorted by key, along with where each comes from.
+    pub fn list<'a>(&'a self) -> Vec<(ConfigKey, String, ConfigOrigin<'a>)> {
+        ConfigKey::all()
+            .into_iter()
+            .filter_map(|key| self.get(&key).map(|(value, origin)| (key, value, origin)))
+            .collect()
+    }
+}
+
+/// Reads the settings overridden by environment variables, as looked up with `vars`, into a
+/// document shaped like a configuration file.
+fn environment<F>(vars: F) -> Fallible<toml::Value>
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    let mut tables: BTreeMap<String, toml::value::Table> = BTreeMap::new();
+    for key in ConfigKey::all() {
+        let mut value = match env::config_value(&key.env_var(), &vars) {
+            Some(value) => key.parse_env_value(&value)?,
+            None => continue,
+        };
+        let settings = tables
+            .entry(key.table.clone())
+            .or_insert_with(BTreeMap::new);
+        if let Some(field) = key.field {
+            // Only the other field of the same plugin can already be set.
+            if settings.contains_key(&key.name) {
+                let other = if field == "url" { "bin" } else { "url" };
+                throw!(ConflictingEnvVarsError {
+                    first: ConfigKey::new(&key.table, &key.name, Some(other)).env_var(),
+                    second: key.env_var(),
+                });
+            }
+            let mut plugin = BTreeMap::new();
+            plugin.insert(field.to_string(), value);
+            value = toml::Value::Table(plugin);
+        }
+        settings.insert(key.name.clone(), value);
+    }
+    Ok(toml::Value::Table(
+        tables
+            .into_iter()
+            .map(|(table, settings)| (table, toml::Value::Table(settings)))
+            .collect(),
+    ))
+}
+
+/// Formats the value of a setting for display, without quoting strings.
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        &toml::Value::String(ref s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// A configuration file, whose settings can be edited without disturbing its formatting.
+pub struct ConfigFile {
+    level: ConfigLevel,
+    path: PathBuf,
+    src: String,
+    document: toml::Value,
+}
+
+impl ConfigFile {
+    /// Loads the configuration file of a level, which may not exist yet. The project level is
+    /// only available in a project.
+    pub fn for_level(level: ConfigLevel, project: Option<&Project>) -> Fallible<Self> {
+        let path = match level {
+            ConfigLevel::System => system_config_file()?,
+            ConfigLevel::User => user_config_file()?,
+            ConfigLevel::Project => match project {
+                Some(project) => project.config_file(),
+                None => throw!(NotInPackageError::new()),
+            },
+        };
+        let src = read_file_opt(&path).unknown()?.unwrap_or_default();
+        ConfigFile::from_source(level, path, src)
+    }
+
+    fn from_source(level: ConfigLevel, path: PathBuf, src: String) -> Fallible<Self> {
+        let document = parse_document(level, &path, &src)?;
+        Ok(ConfigFile {
+            level,
+            path,
+            src,
+            document,
+        })
+    }
+
+    /// Returns the level of the file.
+    pub fn level(&self) -> ConfigLevel {
+        self.level
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the value of a setting as it should be displayed, or `None` if it isn't set.
+    pub fn get(&self, key: &ConfigKey) -> Option<String> {
+        key.lookup(&self.document).map(display_value)
+    }
+
+    /// Lists the settings that are set in the file, sorted by key.
+    pub fn list(&self) -> Vec<(ConfigKey, String)> {
+        ConfigKey::all()
+            .into_iter()
+            .filter_map(|key| self.get(&key).map(|value| (key, value)))
+            .collect()
+    }
+
+    /// Sets a setting to a value given on the command line. (The file isn't saved.)
+    pub fn set(&mut self, key: &ConfigKey, value: &str) -> Fallible<()> {
+        let value = key.parse_value(value)?;
+        let rendered = value.to_string();
+        let table = [&key.table[..]];
+        let plugin = [&key.table[..], &key.name[..]];
+
+        let src = match key.field {
+            None => edit::set_entry(&self.src, &table, &key.name, &rendered)?,
+            Some(field) if edit::has_table(&self.src, &plugin)? => {
+                let src = edit::set_entry(&self.src, &plugin, field, &rendered)?;
+                // A plugin has either a `url` or a `bin`, so setting one removes the other.
+                let other = if field == "url" { "bin" } else { "url" };
+                edit::remove_entry(&src, &plugin, other)?.unwrap_or(src)
+            }
+            Some(field) => {
+                let inline = format!("{{ {} = {} }}", field, rendered);
+                edit::set_entry(&self.src, &table, &key.name, &inline)?
+            }
+        };
+        self.update(key, Some(src), Some(&value))
+    }
+
+    /// Deletes a setting, returning false if it wasn't set. (The file isn't saved.) Deleting
+    /// the `url` or `bin` of a plugin deletes the whole plugin.
+    pub fn delete(&mut self, key: &ConfigKey) -> Fallible<bool> {
+        if self.get(key).is_none() {
+            return Ok(false);
+        }
+
+        let table = [&key.table[..]];
+        let plugin = [&key.table[..], &key.name[..]];
+        let src = if key.field.is_some() && edit::has_table(&self.src, &plugin)? {
+            edit::remove_table(&self.src, &plugin)?
+        } else {
+            edit::remove_entry(&self.src, &table, &key.name)?
+        };
+        self.update(key, src, None)?;
+        Ok(true)
+    }
+
+    /// Replaces the source of the file with an edit of a setting, after checking that the edit
+    /// gave the setting the expected value and left the configuration valid.
+    fn update(
+        &mut self,
+        key: &ConfigKey,
+        src: Option<String>,
+        expected: Option<&toml::Value>,
+    ) -> Fallible<()> {
+        let src = match src {
+            Some(src) => src,
+            None => throw!(self.edit_error(key)),
+        };
+        match toml::from_str::<toml::Value>(&src) {
+            Ok(ref document) if key.lookup(document) == expected => {}
+            _ => throw!(self.edit_error(key)),
+        }
+
+        *self = ConfigFile::from_source(self.level, self.path.clone(), src)?;
+        Ok(())
+    }
+
+    fn edit_error(&self, key: &ConfigKey) -> ConfigEditError {
+        ConfigEditError {
+            key: key.to_string(),
+            file: self.path.display().to_string(),
+        }
+    }
+
+    /// Opens the file in the user's editor (`VISUAL` or `EDITOR`) and saves the result, after
+    /// checking that it's valid. The file is edited as a draft next to it, which is kept if the
+    /// edited configuration is invalid so that the changes aren't lost.
+    pub fn edit(&mut self) -> Fallible<()> {
+        let draft = self.path.with_extension("edit.toml");
+        write_file(&draft, &self.src)?;
+
+        let editor = env::editor().unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+        let status = editor_command(&editor)?
+            .arg(&draft)
+            .status()
+            .with_context(EditorError::for_command(&editor))?;
+        if !status.success() {
+            fs::remove_file(&draft).unknown()?;
+            throw!(EditorError {
+                command: editor,
+                error: match status.code() {
+                    Some(code) => format!("exited with code {}", code),
+                    None => "was terminated".to_string(),
+                },
+            });
+        }
+
+        let src = fs::read_to_string(&draft).unknown()?;
+        if let Err(error) = parse_document(self.level, &draft, &src) {
+            throw!(InvalidConfigEditError {
+                error: error.to_string(),
+                file: draft.display().to_string(),
+            });
+        }
+
+        *self = ConfigFile::from_source(self.level, self.path.clone(), src)?;
+        self.save()?;
+        fs::remove_file(&draft).unknown()
+    }
+
+    /// Writes the file to disk.
+    pub fn save(&self) -> Fallible<()> {
+        write_file(&self.path, &self.src)
+    }
+}
+
+/// Parses a configuration file of a level, checking each of its settings so that an invalid
+/// one is reported along with where it is.
+fn parse_document(level: ConfigLevel, path: &Path, src: &str) -> Fallible<toml::Value> {
+    let tables = toml::from_str(src).with_context(FileParseError::for_toml(path))?;
+    edit::check_entries(path, src, &tables, |tables| {
+        if level == ConfigLevel::Project {
+            check_project_settings(&tables)?;
+        }
+        serial::Config(tables).into_config().map(|_| ())
+    })?;
+    Ok(toml::Value::Table(tables))
+}
+
+/// Checks that the settings of a project's configuration are all URL plugins that resolve or
+/// list the versions of a tool.
+fn check_project_settings(tables: &toml::value::Table) -> Fallible<()> {
+    for (table, settings) in tables {
+        let allowed = registry::lookup(table).is_some()
+            && settings.as_table().map_or(false, |settings| {
+                settings.iter().all(|(name, plugin)| {
+                    PROJECT_SETTINGS.contains(&&name[..])
+                        && plugin
+                            .as_table()
+                            .map_or(false, |plugin| plugin.keys().all(|field| field == "url"))
+                })
+            });
+        if !allowed {
+            throw!(ProjectSettingError);
+        }
+    }
+    Ok(())
+}
+
+/// Builds the command for running the user's editor, splitting its command line into the
+/// executable and its arguments.
+fn editor_command(editor: &str) -> Fallible<Command> {
+    let mut line = editor.trim().to_string();
+    let mut words = line.parse_cmdline_words();
+    let mut command = match words.next() {
+        Some(program) => Command::new(program),
+        None => throw!(EditorError {
+            command: editor.to_string(),
+            error: "no command given".to_string(),
+        }),
+    };
+    command.args(words);
+    Ok(command)
+}
+
+/// Writes a file by moving a complete staged copy into place, so that an interrupted write
+/// never leaves it truncated.
+fn write_file(path: &Path, contents: &str) -> Fallible<()> {
+    let staged: NamedTempFile = create_staging_file()?;
+
+    // Block to borrow staged for staged_file.
+    {
+        let mut staged_file: &File = staged.as_file();
+        staged_file.write_all(contents.as_bytes()).unknown()?;
+    }
+
+    ensure_containing_dir_exists(&path)?;
+    staged.persist(path).unknown()?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{environment, ConfigFile, ConfigKey, ConfigLayers, ConfigOrigin};
+    use config::ConfigLevel;
+    use plugin;
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+    use toml;
+
+    fn config_file(src: &str) -> ConfigFile {
+        layer(ConfigLevel::User, src)
+    }
+
+    fn layer(level: ConfigLevel, src: &str) -> ConfigFile {
+        ConfigFile::from_source(level, PathBuf::from("config.toml"), src.to_string())
+            .expect("could not parse config file")
+    }
+
+    fn key(key: &str) -> ConfigKey {
+        key.parse().expect("could not parse key")
+    }
+
+    fn level(origin: ConfigOrigin) -> Option<ConfigLevel> {
+        match origin {
+            ConfigOrigin::File(file) => Some(file.level()),
+            ConfigOrigin::Environment(_) => None,
+        }
+    }
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(key("node.mirror").to_string(), "node.mirror");
+        assert_eq!(key("yarn.ls-remote.bin").to_string(), "yarn.ls-remote.bin");
+        assert_eq!(key("events.publish.url").to_string(), "events.publish.url");
+        assert_eq!(
+            key("project.version-files").to_string(),
+            "project.version-files"
+        );
+
+        for invalid in &[
+            "node.default",
+            "node",
+            "node.resolve",
+            "node.mirror.url",
+            "node.resolve.path",
+            "project.version-files.url",
+            "events.mirror",
+            "ember.mirror",
+            "yarn.require-checksums",
+            "",
+        ] {
+            assert!(invalid.parse::<ConfigKey>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn lists_all_keys() {
+        let keys = ConfigKey::all();
+        assert!(keys.contains(&key("pnpm.resolve.bin")));
+        assert!(keys.contains(&key("project.resolve-engines")));
+        assert!(keys.contains(&key("node.require-checksums")));
+        assert_eq!(keys.len(), 2 + 2 + 4 * 5 + 1);
+    }
+
+    #[test]
+    fn gets_settings() {
+        let file = config_file(
+            "[node]\nmirror = \"https://mirror.example.com\"\n\n\
+             [node.resolve]\nbin = \"/bin/resolve\"\n\n\
+             [project]\nversion-files = true\n",
+        );
+        assert_eq!(
+            file.get(&key("node.mirror")),
+            Some("https://mirror.example.com".to_string())
+        );
+        assert_eq!(
+            file.get(&key("node.resolve.bin")),
+            Some("/bin/resolve".to_string())
+        );
+        assert_eq!(file.get(&key("node.resolve.url")), None);
+        assert_eq!(
+            file.get(&key("project.version-files")),
+            Some("true".to_string())
+        );
+        assert_eq!(file.list().len(), 3);
+    }
+
+    #[test]
+    fn merges_layers() {
+        let layers = ConfigLayers {
+            files: vec![
+                layer(
+                    ConfigLevel::System,
+                    "[yarn]\nmirror = \"https://system.example.com\"\n\
+                     resolve = { bin = \"resolve\" }\n",
+                ),
+                layer(
+                    ConfigLevel::User,
+                    "[yarn]\nmirror = \"https://user.example.com\"\n",
+                ),
+                layer(
+                    ConfigLevel::Project,
+                    "[yarn.resolve]\nurl = \"https://project.example.com/resolve\"\n",
+                ),
+            ],
+            environment: toml::Value::Table(toml::value::Table::new()),
+        };
+
+        let (mirror, origin) = layers.get(&key("yarn.mirror")).unwrap();
+        assert_eq!(mirror, "https://user.example.com");
+        assert_eq!(level(origin), Some(ConfigLevel::User));
+
+        let (resolve, origin) = layers.get(&key("yarn.resolve.url")).unwrap();
+        assert_eq!(resolve, "https://project.example.com/resolve");
+        assert_eq!(level(origin), Some(ConfigLevel::Project));
+
+        // The project's plugin replaces the system's as a whole.
+        assert!(layers.get(&key("yarn.resolve.bin")).is_none());
+        assert_eq!(layers.list().len(), 2);
+
+        let yarn = layers.config().unwrap().tools.remove("yarn").unwrap();
+        assert_eq!(yarn.mirror, Some("https://user.example.com".to_string()));
+        assert_eq!(
+            yarn.resolve,
+            Some(plugin::ResolvePlugin::Url(
+                "https://project.example.com/resolve".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn reads_environment() {
+        let environment_with = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars
+                .iter()
+                .map(|&(var, value)| (var.to_string(), value.to_string()))
+                .collect();
+            environment(|var| vars.get(var).map(OsString::from))
+        };
+        let layers = ConfigLayers {
+            files: vec![layer(
+                ConfigLevel::User,
+                "[pnpm]\nmirror = \"https://user.example.com\"\n\
+                 ls-remote = { url = \"https://user.example.com/ls-remote\" }\n",
+            )],
+            environment: environment_with(&[
+                ("NOTION_PNPM_MIRROR", "https://env.example.com"),
+                ("NOTION_PNPM_LS_REMOTE_BIN", "ls-remote"),
+            ]).unwrap(),
+        };
+
+        let (mirror, origin) = layers.get(&key("pnpm.mirror")).unwrap();
+        assert_eq!(mirror, "https://env.example.com");
+        assert_eq!(origin.to_string(), "NOTION_PNPM_MIRROR");
+        assert!(layers.get(&key("pnpm.ls-remote.url")).is_none());
+
+        let pnpm = layers.config().unwrap().tools.remove("pnpm").unwrap();
+        assert_eq!(pnpm.mirror, Some("https://env.example.com".to_string()));
+        assert_eq!(
+            pnpm.ls_remote,
+            Some(plugin::LsRemote::Bin("ls-remote".to_string()))
+        );
+
+        assert_eq!(
+            environment_with(&[
+                ("NOTION_PNPM_LS_REMOTE_BIN", "ls-remote"),
+                (
+                    "NOTION_PNPM_LS_REMOTE_URL",
+                    "https://env.example.com/ls-remote",
+                ),
+            ]).unwrap_err()
+                .to_string(),
+            "Both NOTION_PNPM_LS_REMOTE_BIN and NOTION_PNPM_LS_REMOTE_URL are set\n\
+             A plugin can have either a url or a bin, but not both"
+        );
+
+        assert!(environment_with(&[("NOTION_PNPM_MIRROR", "")])
+            .unwrap()
+            .get("pnpm")
+            .is_none());
+    }
+
+    #[test]
+    fn reports_invalid_settings() {
+        let parse = |src: &str| {
+            ConfigFile::from_source(
+                ConfigLevel::User,
+                PathBuf::from("config.toml"),
+                src.to_string(),
+            )
+            .err()
+            .unwrap()
+            .to_string()
+        };
+        assert_eq!(
+            parse("[node]\nmirror = \"a\"\n[yarn\n"),
+            "Could not parse config.toml:3:6: expected a right bracket, found a newline"
+        );
+        assert_eq!(
+            parse("[node]\nmirror = 1\n"),
+            "Invalid `node.mirror` in config.toml:2:1: \
+             invalid type: integer `1`, expected a string for key `mirror`"
+        );
+        assert_eq!(
+            parse("[events]\npublish = { url = \"a\", bin = \"b\" }\n"),
+            "Invalid `events.publish` in config.toml:2:1: \
+             Plugin contains both 'url' and 'bin' fields"
+        );
+    }
+
+    #[test]
+    fn restricts_project_settings() {
+        let parse = |src: &str| {
+            ConfigFile::from_source(
+                ConfigLevel::Project,
+                PathBuf::from(".notionrc.toml"),
+                src.to_string(),
+            )
+        };
+        assert!(
+            parse("[yarn]\nresolve = { url = \"a\" }\n\n[node.ls-remote]\nurl = \"a\"\n")
+                .is_ok()
+        );
+        assert_eq!(
+            parse("[yarn]\nresolve = { url = \"a\" }\nmirror = \"a\"\n")
+                .err()
+                .unwrap()
+                .to_string(),
+            "Invalid `yarn.mirror` in .notionrc.toml:3:1: \
+             only the `resolve.url` and `ls-remote.url` plugins of a tool can be set in a project"
+        );
+        assert_eq!(
+            parse("[events]\npublish = { url = \"a\" }\n")
+                .err()
+                .unwrap()
+                .to_string(),
+            "Invalid `events.publish` in .notionrc.toml:2:1: \
+             only the `resolve.url` and `ls-remote.url` plugins of a tool can be set in a project"
+        );
+    }
+
+    #[test]
+    fn rejects_project_bin_plugins() {
+        let parse = |src: &str| {
+            ConfigFile::from_source(
+                ConfigLevel::Project,
+                PathBuf::from(".notionrc.toml"),
+                src.to_string(),
+            ).err()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            parse("[yarn]\nresolve = { bin = \"./resolve\" }\n"),
+            "Invalid `yarn.resolve` in .notionrc.toml:2:1: \
+             only the `resolve.url` and `ls-remote.url` plugins of a tool can be set in a project"
+        );
+        assert_eq!(
+            parse("[node.ls-remote]\nbin = \"./ls-remote\"\n"),
+            "Invalid `node.ls-remote` in .notionrc.toml:1:1: \
+             only the `resolve.url` and `ls-remote.url` plugins of a tool can be set in a project"
+        );
+    }
+
+    #[test]
+    fn sets_settings_preserving_comments() {
+        let mut file = config_file("# my settings\n[node]\nmirror = \"a\" # company mirror\n");
+        file.set(&key("node.mirror"), "b").unwrap();
+        file.set(&key("project.resolve-engines"), "true").unwrap();
+        assert_eq!(
+            file.src,
+            "# my settings\n[node]\nmirror = \"b\" # company mirror\n\n\
+             [project]\nresolve-engines = true\n"
+        );
+        assert!(file.set(&key("project.resolve-engines"), "yes").is_err());
+    }
+
+    #[test]
+    fn sets_plugin_fields() {
+        let mut file = config_file("[yarn]\nresolve = { url = \"https://example.com\" }\n");
+        file.set(&key("yarn.resolve.bin"), "/bin/resolve").unwrap();
+        assert_eq!(file.src, "[yarn]\nresolve = { bin = \"/bin/resolve\" }\n");
+
+        let mut file = config_file("[yarn.resolve]\nurl = \"https://example.com\"\n");
+        file.set(&key("yarn.resolve.bin"), "/bin/resolve").unwrap();
+        assert_eq!(file.src, "[yarn.resolve]\nbin = \"/bin/resolve\"\n");
+    }
+
+    #[test]
+    fn deletes_settings() {
+        let mut file = config_file(
+            "[node]\nmirror = \"a\"\n\n[node.resolve]\nurl = \"b\"\n\n[events]\npublish = { bin = \"c\" }\n",
+        );
+        assert!(file.delete(&key("node.mirror")).unwrap());
+        assert!(file.delete(&key("node.resolve.url")).unwrap());
+        assert!(file.delete(&key("events.publish.bin")).unwrap());
+        assert!(!file.delete(&key("events.publish.bin")).unwrap());
+        assert_eq!(file.src, "[node]\n\n[events]\n");
+    }
+
+    #[test]
+    fn refuses_unsupported_edits() {
+        let mut file = config_file("node = { mirror = \"a\" }\n");
+        assert!(file.set(&key("node.mirror"), "b").is_err());
+        assert!(file.delete(&key("node.mirror")).is_err());
+        assert_eq!(file.src, "node = { mirror = \"a\" }\n");
+    }
+}
@@ -1,64 +1,117 @@
 //! Provides types for working with Notion configuration files.
 
-use std::marker::PhantomData;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::rc::Rc;
 use std::str::FromStr;
 
 use lazycell::LazyCell;
 use toml;
 
 use distro::Distro;
-use distro::node::NodeDistro;
-use distro::yarn::YarnDistro;
-use fs::touch;
 use notion_fail::{Fallible, NotionError, ResultExt};
-use path::user_config_file;
 use plugin;
-use readext::ReadExt;
+use project::Project;
 
+pub(crate) mod edit;
+mod file;
 pub(crate) mod serial;
 
+pub use self::file::{ConfigFile, ConfigKey, ConfigLayers, ConfigOrigin};
+
 /// Lazily loaded Notion configuration settings.
 pub struct LazyConfig {
     config: LazyCell<Config>,
+    project: Option<Rc<Project>>,
 }
 
 impl LazyConfig {
-    /// Constructs a new `LazyConfig` (but does not initialize it).
-    pub fn new() -> LazyConfig {
+    /// Constructs a new `LazyConfig` (but does not initialize it) for the settings in effect
+    /// in a project, or outside of any project.
+    pub fn new(project: Option<Rc<Project>>) -> LazyConfig {
         LazyConfig {
             config: LazyCell::new(),
+            project,
         }
     }
 
     /// Forces the loading of the configuration settings.
     pub fn get(&self) -> Fallible<&Config> {
-        self.config.try_borrow_with(|| Config::current())
+        self.config
+            .try_borrow_with(|| Config::current(self.project.as_ref().map(|project| &**project)))
+    }
+}
+
+/// The levels of configuration files. The settings of a file override those of the files at
+/// lower levels.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum ConfigLevel {
+    /// The system-wide settings, such as an organization's mirrors and plugins.
+    System,
+    /// The user's settings.
+    User,
+    /// The settings of a project, in a `.notionrc.toml` file next to its `package.json`.
+    Project,
+}
+
+impl Display for ConfigLevel {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            &ConfigLevel::System => "system",
+            &ConfigLevel::User => "user",
+            &ConfigLevel::Project => "project",
+        })
     }
 }
 
 /// Notion configuration settings.
 pub struct Config {
-    pub node: Option<ToolConfig<NodeDistro>>,
-    pub yarn: Option<ToolConfig<YarnDistro>>,
+    /// The settings of each tool that has any, keyed by the tool's name.
+    pub tools: BTreeMap<&'static str, ToolConfig>,
     pub events: Option<EventsConfig>,
+    pub project: Option<ProjectConfig>,
 }
 
-/// Notion configuration settings relating to the Node executable.
-pub struct ToolConfig<D: Distro> {
-    /// The plugin for resolving Node versions, if any.
+/// Notion configuration settings relating to a tool.
+pub struct ToolConfig {
+    /// The plugin for resolving versions of the tool, if any.
     pub resolve: Option<plugin::ResolvePlugin>,
-    /// The plugin for listing the set of Node versions available on the remote server, if any.
+    /// The plugin for listing the set of versions available on the remote server, if any.
     pub ls_remote: Option<plugin::LsRemote>,
-
-    pub phantom: PhantomData<D>,
+    /// The root URL of a mirror of the public distributor to use instead of it, if any.
+    pub mirror: Option<String>,
+    /// Whether an archive of the tool is refused if the checksums published for its version
+    /// don't include it. (Defaults to `true`, and only applies to Node.)
+    pub require_checksums: bool,
 }
 
 impl Config {
-    /// Returns the current configuration settings, loaded from the filesystem.
-    fn current() -> Fallible<Config> {
-        let path = user_config_file()?;
-        let src = touch(&path)?.read_into_string().unknown()?;
-        src.parse()
+    /// Returns the configuration settings in effect in a project, or outside of any project,
+    /// loaded from the system, user and project configuration files and overridden by
+    /// environment variables.
+    fn current(project: Option<&Project>) -> Fallible<Config> {
+        ConfigLayers::current(project)?.config()
+    }
+
+    /// Returns the settings of a tool, if it has any.
+    pub fn tool<D: Distro>(&self) -> Option<&ToolConfig> {
+        self.tools.get(D::NAME)
+    }
+
+    /// Returns true if a project's `.nvmrc` or `.node-version` file should supply its
+    /// Node version when its manifest has no toolchain.
+    pub fn version_files(&self) -> bool {
+        self.project
+            .as_ref()
+            .map_or(false, |project| project.version_files)
+    }
+
+    /// Returns true if an unpinned project whose `engines.node` range the user's Node doesn't
+    /// satisfy should run on the newest installed version of Node that does.
+    pub fn resolve_engines(&self) -> bool {
+        self.project
+            .as_ref()
+            .map_or(false, |project| project.resolve_engines)
     }
 }
 
@@ -66,7 +119,7 @@ impl FromStr for Config {
     type Err = NotionError;
 
     fn from_str(src: &str) -> Result<Self, Self::Err> {
-        let serial: serial::Config = toml::from_str(src).unknown()?;
+        let serial = serial::Config(toml::from_str(src).unknown()?);
         Ok(serial.into_config()?)
     }
 }
@@ -77,10 +130,20 @@ pub struct EventsConfig {
     pub publish: Option<plugin::Publish>,
 }
 
+/// Notion configuration settings related to projects.
+pub struct ProjectConfig {
+    /// Whether `.nvmrc` and `.node-version` files are honored.
+    pub version_files: bool,
+    /// Whether the `engines.node` range of an unpinned project selects an installed Node.
+    pub resolve_engines: bool,
+}
+
 #[cfg(test)]
 pub mod tests {
 
     use config::Config;
+    use distro::node::NodeDistro;
+    use distro::yarn::YarnDistro;
     use plugin;
     use std::fs;
     use std::path::PathBuf;
@@ -103,13 +166,14 @@ pub mod tests {
             .parse()
             .expect("Could not parse urls.toml");
         assert_eq!(
-            node_config.node.unwrap().resolve,
+            node_config.tool::<NodeDistro>().unwrap().resolve,
             Some(plugin::ResolvePlugin::Url("https://nodejs.org".to_string()))
         );
         assert_eq!(
-            node_config.yarn.unwrap().ls_remote,
+            node_config.tool::<YarnDistro>().unwrap().ls_remote,
             Some(plugin::LsRemote::Url("https://yarnpkg.com".to_string()))
         );
+        assert!(!node_config.version_files());
         assert_eq!(
             node_config.events.unwrap().publish,
             Some(plugin::Publish::Url("https://google.com".to_string()))
@@ -127,11 +191,11 @@ pub mod tests {
             .parse()
             .expect("Could not parse bins.toml");
         assert_eq!(
-            node_config.node.unwrap().resolve,
+            node_config.tool::<NodeDistro>().unwrap().resolve,
             Some(plugin::ResolvePlugin::Bin("/some/bin/for/node".to_string()))
         );
         assert_eq!(
-            node_config.yarn.unwrap().ls_remote,
+            node_config.tool::<YarnDistro>().unwrap().ls_remote,
             Some(plugin::LsRemote::Bin("/bin/to/yarn".to_string()))
         );
         assert_eq!(
@@ -139,4 +203,47 @@ pub mod tests {
             Some(plugin::Publish::Bin("/events/bin".to_string()))
         );
     }
+
+    #[test]
+    fn test_from_str_mirrors() {
+        let fixture_dir = fixture_path("config");
+        let mut mirrors_file = fixture_dir.clone();
+
+        mirrors_file.push("mirrors.toml");
+        let config: Config = fs::read_to_string(mirrors_file)
+            .expect("Could not read mirrors.toml")
+            .parse()
+            .expect("Could not parse mirrors.toml");
+        assert_eq!(
+            config.tool::<YarnDistro>().unwrap().mirror,
+            Some("https://artifactory.example.com/yarn".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_str_version_files() {
+        let config: Config = "[project]\nversion-files = true\n"
+            .parse()
+            .expect("Could not parse config");
+        assert!(config.version_files());
+        assert!(!config.resolve_engines());
+    }
+
+    #[test]
+    fn test_from_str_require_checksums() {
+        let config: Config = "[node]\nrequire-checksums = false\n[yarn]\nmirror = \"a\"\n"
+            .parse()
+            .expect("Could not parse config");
+        assert!(!config.tool::<NodeDistro>().unwrap().require_checksums);
+        assert!(config.tool::<YarnDistro>().unwrap().require_checksums);
+
+        let error = "[yarn]\nrequire-checksums = false\n"
+            .parse::<Config>()
+            .err()
+            .expect("Parsed a setting Yarn doesn't have");
+        assert_eq!(
+            error.to_string(),
+            "Unknown configuration key: `yarn.require-checksums`"
+        );
+    }
 }
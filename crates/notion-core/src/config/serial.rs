@@ -1,19 +1,18 @@
 use super::super::config;
-use std::marker::PhantomData;
+use std::collections::BTreeMap;
 
-use distro::Distro;
+use super::file::UnknownConfigKeyError;
 use distro::node::NodeDistro;
-use distro::yarn::YarnDistro;
+use distro::Distro;
 use plugin::serial::Plugin;
+use registry::{self, Visitor};
+use toml;
 
-use notion_fail::Fallible;
+use notion_fail::{Fallible, ResultExt};
 
-#[derive(Serialize, Deserialize)]
-pub struct Config {
-    pub node: Option<ToolConfig<NodeDistro>>,
-    pub yarn: Option<ToolConfig<YarnDistro>>,
-    pub events: Option<EventsConfig>,
-}
+/// The configuration file, in which every table besides `events` and `project` holds the
+/// settings of the tool it is named after.
+pub struct Config(pub BTreeMap<String, toml::Value>);
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename = "events")]
@@ -33,42 +32,142 @@ impl EventsConfig {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "project")]
+pub struct ProjectConfig {
+    #[serde(rename = "version-files", default)]
+    pub version_files: bool,
+
+    #[serde(rename = "resolve-engines", default)]
+    pub resolve_engines: bool,
+}
+
+impl ProjectConfig {
+    pub fn into_project_config(self) -> config::ProjectConfig {
+        config::ProjectConfig {
+            version_files: self.version_files,
+            resolve_engines: self.resolve_engines,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename = "tool")]
-pub struct ToolConfig<I> {
+pub struct ToolConfig {
     pub resolve: Option<Plugin>,
 
     #[serde(rename = "ls-remote")]
     pub ls_remote: Option<Plugin>,
 
-    #[serde(skip)]
-    phantom: PhantomData<I>,
+    pub mirror: Option<String>,
+
+    #[serde(rename = "require-checksums")]
+    pub require_checksums: Option<bool>,
+}
+
+/// The type of a setting in the configuration file.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Setting {
+    /// A `Plugin`, given as a table with either a `url` or a `bin` field.
+    Plugin,
+    Bool,
+    String,
+}
+
+/// Returns the type of the setting with the given name in the given table, or `None` if the
+/// configuration file has no such setting.
+pub fn setting(table: &str, name: &str) -> Option<Setting> {
+    match (table, name) {
+        ("events", "publish") => Some(Setting::Plugin),
+        ("project", "version-files") | ("project", "resolve-engines") => Some(Setting::Bool),
+        // Only Node publishes checksums for its archives.
+        (NodeDistro::NAME, "require-checksums") => Some(Setting::Bool),
+        (tool, name) if registry::lookup(tool).is_some() => match name {
+            "resolve" | "ls-remote" => Some(Setting::Plugin),
+            "mirror" => Some(Setting::String),
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 impl Config {
-    pub fn into_config(self) -> Fallible<config::Config> {
+    /// Merges parsed configuration files, each of which overrides the settings of the files
+    /// before it. Settings are overridden as a whole, so e.g. a plugin with a `bin` replaces
+    /// a plugin with a `url` rather than being combined with it.
+    pub fn merge<I: IntoIterator<Item = toml::Value>>(documents: I) -> Config {
+        let mut merged = BTreeMap::new();
+        for document in documents {
+            if let toml::Value::Table(tables) = document {
+                for (name, table) in tables {
+                    let table = match (merged.remove(&name), table) {
+                        (Some(toml::Value::Table(mut settings)), toml::Value::Table(overrides)) => {
+                            settings.extend(overrides);
+                            toml::Value::Table(settings)
+                        }
+                        (_, table) => table,
+                    };
+                    merged.insert(name, table);
+                }
+            }
+        }
+        Config(merged)
+    }
+
+    pub fn into_config(mut self) -> Fallible<config::Config> {
+        let events: Option<EventsConfig> = match self.0.remove("events") {
+            Some(events) => Some(events.try_into().unknown()?),
+            None => None,
+        };
+
+        let project: Option<ProjectConfig> = match self.0.remove("project") {
+            Some(project) => Some(project.try_into().unknown()?),
+            None => None,
+        };
+
+        let mut converter = Converter {
+            tables: self.0,
+            tools: BTreeMap::new(),
+        };
+        registry::visit(&mut converter)?;
+
         Ok(config::Config {
-            node: if let Some(n) = self.node {
-                Some(n.into_tool_config()?)
-            } else {
-                None
-            },
-            yarn: if let Some(y) = self.yarn {
-                Some(y.into_tool_config()?)
-            } else {
-                None
-            },
-            events: if let Some(e) = self.events {
+            tools: converter.tools,
+            events: if let Some(e) = events {
                 Some(e.into_events_config()?)
             } else {
                 None
             },
+            project: project.map(ProjectConfig::into_project_config),
         })
     }
 }
 
-impl<D: Distro> ToolConfig<D> {
-    pub fn into_tool_config(self) -> Fallible<config::ToolConfig<D>> {
+/// Converts the settings of each tool in the registry. (Tables for other tools are ignored.)
+struct Converter {
+    tables: BTreeMap<String, toml::Value>,
+    tools: BTreeMap<&'static str, config::ToolConfig>,
+}
+
+impl Visitor for Converter {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        if let Some(table) = self.tables.remove(D::NAME) {
+            if table.get("require-checksums").is_some()
+                && setting(D::NAME, "require-checksums").is_none()
+            {
+                throw!(UnknownConfigKeyError {
+                    key: format!("{}.require-checksums", D::NAME),
+                });
+            }
+            let tool: ToolConfig = table.try_into().unknown()?;
+            self.tools.insert(D::NAME, tool.into_tool_config()?);
+        }
+        Ok(())
+    }
+}
+
+impl ToolConfig {
+    pub fn into_tool_config(self) -> Fallible<config::ToolConfig> {
         Ok(config::ToolConfig {
             resolve: if let Some(p) = self.resolve {
                 Some(p.into_resolve()?)
@@ -80,7 +179,8 @@ impl<D: Distro> ToolConfig<D> {
             } else {
                 None
             },
-            phantom: PhantomData,
+            mirror: self.mirror,
+            require_checksums: self.require_checksums.unwrap_or(true),
         })
     }
 }
@@ -0,0 +1,261 @@
+//! Provides the `ArchiveDistro` type, which represents a provisioned distribution of a tool
+//! that is released as a plain archive, along with the provisioning and unpacking shared
+//! by every distribution.
+
+use std::fs::{remove_file, rename, File};
+use std::io::Read;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::string::ToString;
+
+use super::{Distro, Fetched, Provision};
+use catalog::{self, Collection};
+use config::ToolConfig;
+use distro::error::{DownloadError, StreamError};
+use fs::ensure_containing_dir_exists;
+use node_archive::{self, Archive};
+use path;
+use style::{progress_bar, Action};
+use version::VersionSpec;
+
+use notion_fail::{Fallible, ResultExt};
+use semver::Version;
+
+/// The archive of a provisioned distribution.
+pub(crate) struct Provisioned {
+    pub(crate) archive: Box<Archive>,
+    /// The file the archive is being downloaded to, if it isn't in the cache yet. It is
+    /// moved into the cache once it has been downloaded completely.
+    pub(crate) partial_file: Option<PathBuf>,
+}
+
+/// Check if the cached file can be loaded. It may have been corrupted or interrupted in the
+/// middle of downloading.
+fn cache_is_valid(cache_file: &PathBuf) -> bool {
+    if cache_file.is_file() {
+        if let Ok(file) = File::open(cache_file) {
+            match node_archive::load(file) {
+                Ok(_) => return true,
+                Err(_) => return false,
+            }
+        }
+    }
+    false
+}
+
+impl Provisioned {
+    /// Provisions an archive from the filesystem.
+    pub(crate) fn cached(file: File) -> Fallible<Self> {
+        Ok(Provisioned {
+            archive: node_archive::load(file).unknown()?,
+            partial_file: None,
+        })
+    }
+
+    /// Provisions the archive of a version of a tool from a remote distributor, unless the
+    /// archive is already in the cache.
+    pub(crate) fn remote<D: Distro>(version: &Version, url: &str) -> Fallible<Self> {
+        let archive_file = D::archive_file(&version.to_string());
+        let cache_file = D::cache_dir()?.join(&archive_file);
+
+        if cache_is_valid(&cache_file) {
+            return Provisioned::cached(File::open(cache_file).unknown()?);
+        }
+
+        let partial_file = D::cache_dir()?.join(path::partial_download_file(&archive_file));
+        ensure_containing_dir_exists(&partial_file)?;
+        Ok(Provisioned {
+            archive: node_archive::fetch(url, &partial_file)
+                .with_context(DownloadError::for_version(version.to_string()))?,
+            partial_file: Some(partial_file),
+        })
+    }
+
+    /// Provisions the archive of a version of a tool from a stream of archive data, saving it
+    /// in the filesystem.
+    pub(crate) fn stream<D: Distro, R: Read>(version: &Version, source: R) -> Fallible<Self> {
+        let archive_file = D::archive_file(&version.to_string());
+        let partial_file = D::cache_dir()?.join(path::partial_download_file(&archive_file));
+
+        ensure_containing_dir_exists(&partial_file)?;
+        Ok(Provisioned {
+            archive: node_archive::stream(source, &partial_file)
+                .with_context(StreamError::for_version(version.to_string()))?,
+            partial_file: Some(partial_file),
+        })
+    }
+
+    /// Unpacks the archive into the version directory of the tool, from the directory at the
+    /// root of the archive, and moves a downloaded archive into the cache.
+    pub(crate) fn unpack<D: Distro>(self, version: &Version, root_dir: &str) -> Fallible<()> {
+        let archive = self.archive;
+        let dest = path::tool_versions_dir(D::NAME)?;
+        let bar = progress_bar(
+            Action::Fetching,
+            &format!("v{}", version),
+            archive
+                .uncompressed_size()
+                .unwrap_or(archive.compressed_size()),
+        );
+
+        archive
+            .unpack(&dest, &mut |_, read| {
+                bar.inc(read as u64);
+            })
+            .unknown()?;
+
+        let version_string = version.to_string();
+
+        if let Some(partial_file) = self.partial_file {
+            let cache_file = D::cache_dir()?.join(D::archive_file(&version_string));
+            rename(partial_file, cache_file).unknown()?;
+        }
+
+        rename(dest.join(root_dir), D::version_dir(&version_string)?).unknown()?;
+
+        bar.finish_and_clear();
+        Ok(())
+    }
+
+    /// Deletes any archive data that has been saved but isn't in the cache yet.
+    pub(crate) fn discard(self) {
+        if let Some(partial_file) = self.partial_file {
+            let _ = remove_file(partial_file);
+        }
+    }
+}
+
+/// A tool released as a plain archive, which is unpacked into the tool's version directory.
+/// By default, the tool is published to the npm registry as the package of the same name.
+/// Implementing this trait and adding `ArchiveDistro<Self>` to the registry (see
+/// `registry::visit`) is all it takes to support such a tool.
+pub trait ArchiveTool: 'static {
+    /// The name of the tool, as used on the command line, in the catalog and in the
+    /// project toolchain (e.g. `npm`).
+    const NAME: &'static str;
+
+    /// The name of the tool as displayed in messages (e.g. `npm`).
+    const DISPLAY_NAME: &'static str;
+
+    /// Returns the URL of the archive of a version on the public distributor, or on the given
+    /// mirror of it.
+    fn public_url(version: &Version, mirror: Option<&str>) -> String {
+        format!(
+            "{}/{}/-/{}",
+            catalog::npm_registry(mirror),
+            Self::NAME,
+            Self::archive_file(&version.to_string())
+        )
+    }
+
+    /// Resolves the specified semantic versioning requirements from the public distributor,
+    /// or from the given mirror of it.
+    fn resolve_public(matching: &VersionSpec, mirror: Option<&str>) -> Fallible<Version> {
+        catalog::resolve_registry_package(Self::NAME, matching, mirror)
+    }
+
+    /// Lists the versions available from the public distributor, or from the given mirror
+    /// of it.
+    fn ls_public(mirror: Option<&str>) -> Fallible<Vec<Version>> {
+        catalog::ls_registry_package(Self::NAME, mirror)
+    }
+
+    /// Returns the file name of the archive of a version of the tool.
+    fn archive_file(version: &str) -> String {
+        path::registry_archive_file(Self::NAME, version)
+    }
+
+    /// Extracts the version from the name of an archive file of the tool, if it is one.
+    fn archive_version(file_name: &str) -> Option<&str> {
+        path::registry_archive_version(Self::NAME, file_name)
+    }
+
+    /// Returns the directory at the root of the archive of a version of the tool.
+    fn archive_root_dir(_version: &str) -> String {
+        path::registry_archive_root_dir()
+    }
+
+    /// Completes the installation of a version of the tool once it has been unpacked.
+    fn install(_version: &str) -> Fallible<()> {
+        Ok(())
+    }
+}
+
+/// A provisioned distribution of a tool released as a plain archive.
+pub struct ArchiveDistro<T: ArchiveTool> {
+    provisioned: Provisioned,
+    version: Version,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: ArchiveTool> ArchiveDistro<T> {
+    fn new(provisioned: Provisioned, version: Version) -> Self {
+        ArchiveDistro {
+            provisioned,
+            version,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ArchiveTool> Distro for ArchiveDistro<T> {
+    const NAME: &'static str = T::NAME;
+    const DISPLAY_NAME: &'static str = T::DISPLAY_NAME;
+
+    fn public(version: Version, mirror: Option<&str>) -> Fallible<Self> {
+        let url = T::public_url(&version, mirror);
+        Self::remote(version, &url)
+    }
+
+    fn cached(version: Version, file: File) -> Fallible<Self> {
+        Ok(Self::new(Provisioned::cached(file)?, version))
+    }
+
+    fn fetch(self, collection: &Collection<Self>, _config: Option<&ToolConfig>) -> Fallible<Fetched> {
+        if collection.contains(&self.version) {
+            return Ok(Fetched::Already(self.version));
+        }
+
+        let version_string = self.version.to_string();
+        self.provisioned
+            .unpack::<Self>(&self.version, &T::archive_root_dir(&version_string))?;
+        T::install(&version_string)?;
+
+        Ok(Fetched::Now(self.version))
+    }
+
+    fn resolve_public(matching: &VersionSpec, mirror: Option<&str>) -> Fallible<Self> {
+        let version = T::resolve_public(matching, mirror)?;
+        Self::public(version, mirror)
+    }
+
+    fn ls_public(mirror: Option<&str>) -> Fallible<Vec<Version>> {
+        T::ls_public(mirror)
+    }
+
+    fn archive_file(version: &str) -> String {
+        T::archive_file(version)
+    }
+
+    fn archive_version(file_name: &str) -> Option<&str> {
+        T::archive_version(file_name)
+    }
+}
+
+impl<T: ArchiveTool> Provision for ArchiveDistro<T> {
+    fn remote(version: Version, url: &str) -> Fallible<Self> {
+        Ok(Self::new(Provisioned::remote::<Self>(&version, url)?, version))
+    }
+
+    fn stream<R: Read>(version: Version, source: R) -> Fallible<Self> {
+        Ok(Self::new(Provisioned::stream::<Self, R>(&version, source)?, version))
+    }
+
+    fn version(&self) -> &Version {
+        &self.version
+    }
+
+    fn discard(self) {
+        self.provisioned.discard();
+    }
+}
@@ -3,6 +3,25 @@
 use notion_fail::{ExitCode, NotionFail};
 
 use failure;
+use reqwest;
+use semver::VersionReq;
+use version::VersionSpec;
+
+/// Thrown when there is no Node version matching a requested semver specifier.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No Node version found for {}", matching)]
+#[notion_fail(code = "NoVersionMatch")]
+pub(crate) struct NoNodeVersionFoundError {
+    pub(crate) matching: VersionSpec,
+}
+
+/// Thrown when there is no Yarn version matching a requested semver specifier.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No Yarn version found for {}", matching)]
+#[notion_fail(code = "NoVersionMatch")]
+pub(crate) struct NoYarnVersionFoundError {
+    pub(crate) matching: VersionReq,
+}
 
 #[derive(Debug, Fail, NotionFail)]
 #[fail(display = "Failed to download version {}\n{}", version, error)]
@@ -20,3 +39,60 @@ impl DownloadError {
         }
     }
 }
+
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Failed to receive version {} from plugin stream\n{}", version, error)]
+#[notion_fail(code = "ExecutionFailure")]
+pub(crate) struct StreamError {
+    version: String,
+    error: String,
+}
+
+impl StreamError {
+    pub(crate) fn for_version(version: String) -> impl FnOnce(&failure::Error) -> StreamError {
+        move |error| StreamError {
+            version: version,
+            error: error.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Failed to fetch checksums from {}\n{}", url, error)]
+#[notion_fail(code = "NetworkError")]
+pub(crate) struct ChecksumFetchError {
+    url: String,
+    error: String,
+}
+
+impl ChecksumFetchError {
+    pub(crate) fn for_url(url: &str) -> impl FnOnce(&reqwest::Error) -> ChecksumFetchError {
+        let url = url.to_string();
+        move |error| ChecksumFetchError {
+            url,
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Thrown when the checksums published for a version don't include an archive that is
+/// required to have one.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No checksum found for {}\n\
+                  To install it without verifying it, run `notion config set node.require-checksums false`",
+       file)]
+#[notion_fail(code = "NetworkError")]
+pub(crate) struct MissingChecksumError {
+    pub(crate) file: String,
+}
+
+/// Thrown when a downloaded archive doesn't match the checksum published for it.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Checksum mismatch for {} (the download may be corrupted)\nexpected: {}\n  actual: {}",
+       file, expected, actual)]
+#[notion_fail(code = "NetworkError")]
+pub(crate) struct ChecksumMismatchError {
+    pub(crate) file: String,
+    pub(crate) expected: String,
+    pub(crate) actual: String,
+}
@@ -1,13 +1,22 @@
 //! Provides types for fetching tool distributions into the Notion catalog.
 
+mod archive;
 mod error;
 pub mod node;
+pub mod npm;
+pub mod pnpm;
 pub mod yarn;
 
-use catalog::Collection;
+use catalog::{self, Collection};
+use config::ToolConfig;
 use notion_fail::Fallible;
+use path;
 use semver::Version;
+use std::collections::BTreeMap;
 use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use version::VersionSpec;
 
 /// The result of a requested installation.
 pub enum Fetched {
@@ -33,20 +42,84 @@ impl Fetched {
     }
 }
 
-pub trait Distro: Sized {
-    /// Provision a distribution from the public distributor (e.g. `https://nodejs.org`).
-    fn public(version: Version) -> Fallible<Self>;
-
+/// A distribution of a version of a tool, as provisioned by a resolve plugin (see
+/// `plugin::ResolvePlugin::resolve`).
+pub trait Provision: Sized {
     /// Provision a distribution from a remote distributor.
     fn remote(version: Version, url: &str) -> Fallible<Self>;
 
-    /// Provision a distribution from the filesystem.
-    fn cached(version: Version, file: File) -> Fallible<Self>;
+    /// Provision a distribution from a stream of archive data, caching it in the filesystem.
+    fn stream<R: Read>(version: Version, source: R) -> Fallible<Self>;
 
     /// Produces a reference to this distro's Tool version.
     fn version(&self) -> &Version;
 
-    /// Fetches this version of the Tool. (It is left to the responsibility of the `Collection`
-    /// to update its state after fetching succeeds.)
-    fn fetch(self, catalog: &Collection<Self>) -> Fallible<Fetched>;
+    /// Discards this distribution without fetching it, such as when the plugin that
+    /// provisioned it fails.
+    fn discard(self);
+}
+
+/// A tool whose versions Notion manages. Implementing this trait and adding the tool to
+/// the registry (see `registry::visit`) is all it takes for the catalog, the configuration,
+/// the project toolchain, the shims and the command-line interface to support a new tool.
+pub trait Distro: Provision + 'static {
+    /// The name of the tool, as used on the command line, in the catalog and in the
+    /// project toolchain (e.g. `node`).
+    const NAME: &'static str;
+
+    /// The name of the tool as displayed in messages (e.g. `Node`).
+    const DISPLAY_NAME: &'static str;
+
+    /// Provision a distribution from the public distributor (e.g. `https://nodejs.org`),
+    /// or from the given mirror of it.
+    fn public(version: Version, mirror: Option<&str>) -> Fallible<Self>;
+
+    /// Provision a distribution from the filesystem.
+    fn cached(version: Version, file: File) -> Fallible<Self>;
+
+    /// Fetches this version of the Tool, following the tool's configuration settings, if it
+    /// has any. (It is left to the responsibility of the `Collection` to update its state
+    /// after fetching succeeds.)
+    fn fetch(self, catalog: &Collection<Self>, config: Option<&ToolConfig>) -> Fallible<Fetched>;
+
+    /// Resolves the specified semantic versioning requirements from the public distributor,
+    /// or from the given mirror of it. By default, this is the npm registry, where the
+    /// tool is published as the package of the same name.
+    fn resolve_public(matching: &VersionSpec, mirror: Option<&str>) -> Fallible<Self> {
+        let version = catalog::resolve_registry_package(Self::NAME, matching, mirror)?;
+        Self::public(version, mirror)
+    }
+
+    /// Lists the versions available from the public distributor, or from the given mirror
+    /// of it. By default, this is the npm registry.
+    fn ls_public(mirror: Option<&str>) -> Fallible<Vec<Version>> {
+        catalog::ls_registry_package(Self::NAME, mirror)
+    }
+
+    /// Lists the versions in the locally cached index of the public distributor, even if
+    /// the index has expired, with the long-term support line each belongs to.
+    fn cached_index() -> Fallible<BTreeMap<Version, Option<String>>> {
+        Ok(BTreeMap::new())
+    }
+
+    /// Returns the directory a version of the tool is installed in.
+    fn version_dir(version: &str) -> Fallible<PathBuf> {
+        path::tool_version_dir(Self::NAME, version)
+    }
+
+    /// Returns the directory containing the executables of a version of the tool.
+    fn bin_dir(version: &str) -> Fallible<PathBuf> {
+        path::tool_version_bin_dir(Self::NAME, version)
+    }
+
+    /// Returns the directory downloaded archives of the tool are cached in.
+    fn cache_dir() -> Fallible<PathBuf> {
+        path::tool_cache_dir(Self::NAME)
+    }
+
+    /// Returns the file name of the archive of a version of the tool.
+    fn archive_file(version: &str) -> String;
+
+    /// Extracts the version from the name of an archive file of the tool, if it is one.
+    fn archive_version(file_name: &str) -> Option<&str>;
 }
@@ -1,18 +1,25 @@
 //! Provides the `Installer` type, which represents a provisioned Node installer.
 
-use std::fs::{rename, File};
+use std::collections::BTreeMap;
+use std::fs::{remove_file, write, File};
+use std::io::Read;
 use std::path::PathBuf;
 use std::string::ToString;
 
-use super::{Distro, Fetched};
-use catalog::NodeCollection;
-use distro::error::DownloadError;
-use fs::ensure_containing_dir_exists;
-use node_archive::{self, Archive};
+use super::archive::Provisioned;
+use super::{Distro, Fetched, Provision};
+use catalog::{self, NodeCollection};
+use config::ToolConfig;
+use distro::error::{ChecksumFetchError, ChecksumMismatchError, MissingChecksumError,
+                    NoNodeVersionFoundError};
+use fs::{ensure_containing_dir_exists, read_file_opt};
+use node_archive;
 use path;
-use style::{progress_bar, Action};
+use style::{display_warning, progress_bar, Action};
+use version::VersionSpec;
 
 use notion_fail::{Fallible, ResultExt};
+use reqwest::{self, StatusCode};
 use semver::Version;
 
 #[cfg(feature = "mock-network")]
@@ -32,60 +39,183 @@ cfg_if! {
 
 /// A provisioned Node distribution.
 pub struct NodeDistro {
-    archive: Box<Archive>,
+    /// The archive, which is moved into the cache once it has been downloaded completely
+    /// and verified.
+    provisioned: Provisioned,
     version: Version,
-}
-
-/// Check if the cached file is valid. It may have been corrupted or interrupted in the middle of
-/// downloading.
-// ISSUE(#134) - verify checksum
-fn cache_is_valid(cache_file: &PathBuf) -> bool {
-    if cache_file.is_file() {
-        if let Ok(file) = File::open(cache_file) {
-            match node_archive::load(file) {
-                Ok(_) => return true,
-                Err(_) => return false,
-            }
-        }
-    }
-    false
+    /// The URL of the `SHASUMS256.txt` file published alongside the archive, if known.
+    shasums_url: Option<String>,
 }
 
 impl Distro for NodeDistro {
-    /// Provision a Node distribution from the public Node distributor (`https://nodejs.org`).
-    fn public(version: Version) -> Fallible<Self> {
+    const NAME: &'static str = "node";
+    const DISPLAY_NAME: &'static str = "Node";
+
+    /// Provision a Node distribution from the public Node distributor (`https://nodejs.org`),
+    /// or from a mirror with the same layout.
+    fn public(version: Version, mirror: Option<&str>) -> Fallible<Self> {
         let archive_file = path::node_archive_file(&version.to_string());
-        let url = format!(
-            "{}/v{}/{}",
-            public_node_server_root(),
-            version,
-            &archive_file
-        );
+        let root = match mirror {
+            Some(mirror) => mirror.trim_right_matches('/').to_string(),
+            None => public_node_server_root(),
+        };
+        let url = format!("{}/v{}/{}", root, version, &archive_file);
         NodeDistro::remote(version, &url)
     }
 
-    /// Provision a Node distribution from a remote distributor.
-    fn remote(version: Version, url: &str) -> Fallible<Self> {
-        let archive_file = path::node_archive_file(&version.to_string());
-        let cache_file = path::node_cache_dir()?.join(&archive_file);
+    /// Provision a Node distribution from the filesystem.
+    fn cached(version: Version, file: File) -> Fallible<Self> {
+        Ok(NodeDistro {
+            provisioned: Provisioned::cached(file)?,
+            version: version,
+            shasums_url: None,
+        })
+    }
 
-        if cache_is_valid(&cache_file) {
-            return NodeDistro::cached(version, File::open(cache_file).unknown()?);
+    /// Fetches this version of Node. If a checksum is published for the archive, the archive is
+    /// verified against it before anything is unpacked. (It is left to the responsibility of
+    /// the `NodeCollection` to update its state after fetching succeeds.)
+    fn fetch(self, collection: &NodeCollection, config: Option<&ToolConfig>) -> Fallible<Fetched> {
+        if collection.contains(&self.version) {
+            return Ok(Fetched::Already(self.version));
         }
 
-        ensure_containing_dir_exists(&cache_file)?;
+        let version_string = self.version.to_string();
+        let archive_file = path::node_archive_file(&version_string);
+        let Provisioned {
+            archive,
+            partial_file,
+        } = self.provisioned;
+        let archive_path = match partial_file {
+            Some(ref partial_file) => partial_file.clone(),
+            None => NodeDistro::cache_dir()?.join(&archive_file),
+        };
+
+        let shasums_file =
+            NodeDistro::cache_dir()?.join(path::node_shasums_file(&version_string));
+        let checksum =
+            expected_checksum(&shasums_file, &archive_file, self.shasums_url.as_ref())?;
+        let require = config.map_or(true, |config| config.require_checksums);
+
+        let archive = match verified_checksum(checksum, require, &archive_file)? {
+            Some(expected) => {
+                let bar = progress_bar(
+                    Action::Verifying,
+                    &format!("v{}", self.version),
+                    archive.compressed_size(),
+                );
+
+                // Reading the archive through completes its download, if it is remote.
+                let actual = archive
+                    .digest(&mut |_, read| {
+                        bar.inc(read as u64);
+                    })
+                    .unknown()?;
+                bar.finish_and_clear();
+
+                if actual != expected {
+                    // Don't leave the bad archive around to be picked up again next time.
+                    let _ = remove_file(&archive_path);
+
+                    throw!(ChecksumMismatchError {
+                        file: archive_file,
+                        expected,
+                        actual,
+                    });
+                }
+
+                node_archive::load(File::open(&archive_path).unknown()?).unknown()?
+            }
+            None => {
+                // An archive in the cache was already verified when it was downloaded.
+                if partial_file.is_some() {
+                    display_warning(&format!(
+                        "No checksum is published for {}, so it can't be verified",
+                        archive_file
+                    ));
+                }
+                archive
+            }
+        };
+
+        Provisioned {
+            archive,
+            partial_file,
+        }.unpack::<NodeDistro>(
+            &self.version,
+            &path::node_archive_root_dir(&version_string),
+        )?;
+
+        Ok(Fetched::Now(self.version))
+    }
+
+    fn resolve_public(matching: &VersionSpec, mirror: Option<&str>) -> Fallible<Self> {
+        let index = catalog::fetch_node_index(mirror)?;
+
+        // NOTE: This assumes the registry always produces a list in sorted order
+        //       from newest to oldest. This should be specified as a requirement
+        //       when we document the plugin API.
+        // ISSUE #34: also make sure this OS is available for this version
+        let version = index
+            .entries
+            .into_iter()
+            .find(|&(ref k, ref data)| catalog::spec_matches(matching, k, data.lts.as_ref()))
+            .map(|(k, _)| k);
+
+        match version {
+            Some(version) => NodeDistro::public(version, mirror),
+            None => throw!(NoNodeVersionFoundError {
+                matching: matching.clone()
+            }),
+        }
+    }
+
+    fn ls_public(mirror: Option<&str>) -> Fallible<Vec<Version>> {
+        let index = catalog::fetch_node_index(mirror)?;
+        Ok(index.entries.into_iter().map(|(version, _)| version).collect())
+    }
+
+    fn cached_index() -> Fallible<BTreeMap<Version, Option<String>>> {
+        match catalog::read_node_index()? {
+            Some(index) => Ok(index
+                .entries
+                .into_iter()
+                .map(|(version, data)| (version, data.lts))
+                .collect()),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    fn bin_dir(version: &str) -> Fallible<PathBuf> {
+        path::node_version_bin_dir(version)
+    }
+
+    fn archive_file(version: &str) -> String {
+        path::node_archive_file(version)
+    }
+
+    fn archive_version(file_name: &str) -> Option<&str> {
+        path::node_archive_version(file_name)
+    }
+}
+
+impl Provision for NodeDistro {
+    /// Provision a Node distribution from a remote distributor.
+    fn remote(version: Version, url: &str) -> Fallible<Self> {
         Ok(NodeDistro {
-            archive: node_archive::fetch(url, &cache_file)
-                .with_context(DownloadError::for_version(version.to_string()))?,
+            provisioned: Provisioned::remote::<NodeDistro>(&version, url)?,
             version: version,
+            shasums_url: Some(shasums_url(url)),
         })
     }
 
-    /// Provision a Node distribution from the filesystem.
-    fn cached(version: Version, file: File) -> Fallible<Self> {
+    /// Provision a Node distribution from a stream of archive data, saving it in the filesystem.
+    /// It is moved into the cache once it has been fetched.
+    fn stream<R: Read>(version: Version, source: R) -> Fallible<Self> {
         Ok(NodeDistro {
-            archive: node_archive::load(file).unknown()?,
+            provisioned: Provisioned::stream::<NodeDistro, R>(&version, source)?,
             version: version,
+            shasums_url: None,
         })
     }
 
@@ -94,35 +224,183 @@ impl Distro for NodeDistro {
         &self.version
     }
 
-    /// Fetches this version of Node. (It is left to the responsibility of the `NodeCollection`
-    /// to update its state after fetching succeeds.)
-    fn fetch(self, collection: &NodeCollection) -> Fallible<Fetched> {
-        if collection.contains(&self.version) {
-            return Ok(Fetched::Already(self.version));
+    /// Discards this distribution, deleting any archive data it has saved that isn't in
+    /// the cache yet.
+    fn discard(self) {
+        self.provisioned.discard();
+    }
+}
+
+/// Determines the URL of the `SHASUMS256.txt` file published in the same directory as
+/// the archive at the given URL.
+fn shasums_url(archive_url: &str) -> String {
+    match archive_url.rfind('/') {
+        Some(index) => format!("{}/SHASUMS256.txt", &archive_url[..index]),
+        None => "SHASUMS256.txt".to_string(),
+    }
+}
+
+/// What is known about the checksum of a Node archive.
+#[derive(Debug, PartialEq)]
+enum Checksum {
+    /// The checksum published for the archive.
+    Published(String),
+    /// Checksums are published for the version, but not for the archive.
+    Unlisted,
+    /// No checksums are published for the version, or it isn't known where they would be
+    /// published (e.g. for an archive streamed from a plugin).
+    Unknown,
+}
+
+/// Looks up the expected SHA-256 checksum of a Node archive in the version's `SHASUMS256.txt`
+/// file. The file is cached the first time it is fetched from the given URL, so the checksum
+/// remains available when the cached archive is reused.
+fn expected_checksum(
+    shasums_file: &PathBuf,
+    archive_file: &str,
+    url: Option<&String>,
+) -> Fallible<Checksum> {
+    let shasums = match read_file_opt(shasums_file).unknown()? {
+        Some(shasums) => shasums,
+        None => {
+            let url = match url {
+                Some(url) => url,
+                None => {
+                    return Ok(Checksum::Unknown);
+                }
+            };
+
+            let response =
+                reqwest::get(url.as_str()).with_context(ChecksumFetchError::for_url(url))?;
+
+            // Not every distributor publishes checksums.
+            if response.status() == StatusCode::NotFound {
+                return Ok(Checksum::Unknown);
+            }
+
+            let shasums = response
+                .error_for_status()
+                .and_then(|mut response| response.text())
+                .with_context(ChecksumFetchError::for_url(url))?;
+
+            ensure_containing_dir_exists(shasums_file)?;
+            write(shasums_file, &shasums).unknown()?;
+            shasums
         }
+    };
 
-        let dest = path::node_versions_dir()?;
-        let bar = progress_bar(
-            Action::Fetching,
-            &format!("v{}", self.version),
-            self.archive
-                .uncompressed_size()
-                .unwrap_or(self.archive.compressed_size()),
+    Ok(match find_checksum(&shasums, archive_file) {
+        Some(checksum) => Checksum::Published(checksum),
+        None => Checksum::Unlisted,
+    })
+}
+
+/// Returns the checksum an archive must be verified against, or `None` if it can't be
+/// verified. An archive missing from the published checksums is refused if checksums are
+/// required, since the checksums file should list every archive of the version.
+fn verified_checksum(
+    checksum: Checksum,
+    require: bool,
+    archive_file: &str,
+) -> Fallible<Option<String>> {
+    match checksum {
+        Checksum::Published(checksum) => Ok(Some(checksum)),
+        Checksum::Unlisted if require => throw!(MissingChecksumError {
+            file: archive_file.to_string(),
+        }),
+        Checksum::Unlisted | Checksum::Unknown => Ok(None),
+    }
+}
+
+/// Finds the checksum of the given file in the contents of a `SHASUMS256.txt` file.
+fn find_checksum(shasums: &str, file: &str) -> Option<String> {
+    for line in shasums.lines() {
+        let mut words = line.split_whitespace();
+        if let (Some(checksum), Some(name)) = (words.next(), words.next()) {
+            if name == file {
+                return Some(checksum.to_lowercase());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{expected_checksum, find_checksum, shasums_url, verified_checksum, Checksum};
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    const ARCHIVE: &'static str = "node-v8.9.4-linux-x64.tar.gz";
+
+    #[test]
+    fn test_shasums_url() {
+        assert_eq!(
+            shasums_url("https://nodejs.org/dist/v8.9.4/node-v8.9.4-linux-x64.tar.gz"),
+            "https://nodejs.org/dist/v8.9.4/SHASUMS256.txt"
         );
+    }
 
-        self.archive
-            .unpack(&dest, &mut |_, read| {
-                bar.inc(read as u64);
-            })
-            .unknown()?;
+    #[test]
+    fn test_find_checksum() {
+        let shasums = "\
+            0A1B2C  node-v8.9.4-darwin-x64.tar.gz\n\
+            3d4e5f  node-v8.9.4-linux-x64.tar.gz\n";
+        assert_eq!(
+            find_checksum(shasums, "node-v8.9.4-darwin-x64.tar.gz"),
+            Some("0a1b2c".to_string())
+        );
+        assert_eq!(
+            find_checksum(shasums, "node-v8.9.4-linux-x64.tar.gz"),
+            Some("3d4e5f".to_string())
+        );
+        assert_eq!(find_checksum(shasums, "node-v8.9.4-win-x64.zip"), None);
+    }
 
-        let version_string = self.version.to_string();
-        rename(
-            dest.join(path::node_archive_root_dir(&version_string)),
-            path::node_version_dir(&version_string)?,
-        ).unknown()?;
+    #[test]
+    fn test_streamed_archive_is_not_verified() {
+        // A streamed archive has no URL to fetch its checksums from.
+        let dir = tempdir().unwrap();
+        let shasums_file = dir.path().join("node-v8.9.4-SHASUMS256.txt");
+        let checksum = expected_checksum(&shasums_file, ARCHIVE, None).unwrap();
+        assert_eq!(checksum, Checksum::Unknown);
+        assert_eq!(verified_checksum(checksum, true, ARCHIVE).unwrap(), None);
+    }
 
-        bar.finish_and_clear();
-        Ok(Fetched::Now(self.version))
+    #[test]
+    fn test_cached_archive_uses_cached_checksums() {
+        // Offline, a cached archive is checked against the cached checksums, if any.
+        let dir = tempdir().unwrap();
+        let shasums_file = dir.path().join("node-v8.9.4-SHASUMS256.txt");
+        write(&shasums_file, format!("3d4e5f  {}\n", ARCHIVE)).unwrap();
+        let checksum = expected_checksum(&shasums_file, ARCHIVE, None).unwrap();
+        assert_eq!(checksum, Checksum::Published("3d4e5f".to_string()));
+        assert_eq!(
+            verified_checksum(checksum, true, ARCHIVE).unwrap(),
+            Some("3d4e5f".to_string())
+        );
+
+        write(&shasums_file, "0a1b2c  node-v8.9.4-darwin-x64.tar.gz\n").unwrap();
+        let checksum = expected_checksum(&shasums_file, ARCHIVE, None).unwrap();
+        assert_eq!(checksum, Checksum::Unlisted);
+    }
+
+    #[test]
+    fn test_unlisted_archive_requires_opt_out() {
+        assert_eq!(
+            verified_checksum(Checksum::Unlisted, true, ARCHIVE)
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "No checksum found for {}\n\
+                 To install it without verifying it, run `notion config set node.require-checksums false`",
+                ARCHIVE
+            )
+        );
+        assert_eq!(
+            verified_checksum(Checksum::Unlisted, false, ARCHIVE).unwrap(),
+            None
+        );
     }
 }
@@ -0,0 +1,17 @@
+//! Provides the `NpmDistro` type, which represents a provisioned npm distribution.
+
+use super::archive::{ArchiveDistro, ArchiveTool};
+
+// NOTE: The npm registry only publishes tarballs, which `node_archive` doesn't
+//       unpack on Windows yet.
+
+/// npm, which is released to the npm registry as the `npm` package.
+pub enum Npm {}
+
+impl ArchiveTool for Npm {
+    const NAME: &'static str = "npm";
+    const DISPLAY_NAME: &'static str = "npm";
+}
+
+/// A provisioned npm distribution.
+pub type NpmDistro = ArchiveDistro<Npm>;
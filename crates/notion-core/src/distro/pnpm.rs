@@ -0,0 +1,55 @@
+//! Provides the `PnpmDistro` type, which represents a provisioned pnpm distribution.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use super::archive::{ArchiveDistro, ArchiveTool};
+use super::Distro;
+
+use notion_fail::{Fallible, ResultExt};
+
+#[cfg(unix)]
+use std::fs::{set_permissions, Permissions};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+// NOTE: pnpm is released to the npm registry, which only publishes tarballs,
+//       which `node_archive` doesn't unpack on Windows yet.
+
+cfg_if! {
+    if #[cfg(windows)] {
+        /// Writes a `pnpm.cmd` launcher next to the `pnpm.js` script the package provides.
+        fn write_launcher(bin_dir: &Path) -> Fallible<()> {
+            let mut file = File::create(bin_dir.join("pnpm.cmd")).unknown()?;
+            file.write_all(b"@node \"%~dp0\\pnpm.js\" %*\r\n").unknown()?;
+            Ok(())
+        }
+    } else {
+        /// Writes a `pnpm` launcher next to the `pnpm.js` script the package provides, since
+        /// the registry tarball doesn't include one.
+        fn write_launcher(bin_dir: &Path) -> Fallible<()> {
+            let launcher = bin_dir.join("pnpm");
+            let mut file = File::create(&launcher).unknown()?;
+            file.write_all(b"#!/bin/sh\nexec node \"$(dirname \"$0\")/pnpm.js\" \"$@\"\n")
+                .unknown()?;
+            set_permissions(&launcher, Permissions::from_mode(0o755)).unknown()?;
+            Ok(())
+        }
+    }
+}
+
+/// pnpm, which is released to the npm registry as the `pnpm` package.
+pub enum Pnpm {}
+
+impl ArchiveTool for Pnpm {
+    const NAME: &'static str = "pnpm";
+    const DISPLAY_NAME: &'static str = "pnpm";
+
+    fn install(version: &str) -> Fallible<()> {
+        write_launcher(&PnpmDistro::bin_dir(version)?)
+    }
+}
+
+/// A provisioned pnpm distribution.
+pub type PnpmDistro = ArchiveDistro<Pnpm>;
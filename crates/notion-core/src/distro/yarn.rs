@@ -1,19 +1,17 @@
-//! Provides the `Installer` type, which represents a provisioned Node installer.
+//! Provides the `YarnDistro` type, which represents a provisioned Yarn distribution.
 
-use std::fs::{rename, File};
-use std::path::PathBuf;
 use std::string::ToString;
 
-use super::{Distro, Fetched};
-use catalog::YarnCollection;
-use distro::error::DownloadError;
-use fs::ensure_containing_dir_exists;
-use node_archive::{self, Archive};
+use super::archive::{ArchiveDistro, ArchiveTool};
+use catalog::{NoLtsReleasesError, RegistryFetchError};
+use distro::error::NoYarnVersionFoundError;
 use path;
-use style::{progress_bar, Action};
+use style::progress_spinner;
+use version::VersionSpec;
 
 use notion_fail::{Fallible, ResultExt};
-use semver::Version;
+use reqwest;
+use semver::{Version, VersionReq};
 
 #[cfg(feature = "mock-network")]
 use mockito;
@@ -23,101 +21,128 @@ cfg_if! {
         fn public_yarn_server_root() -> String {
             mockito::SERVER_URL.to_string()
         }
+        fn public_yarn_version_index() -> String {
+            format!("{}/yarn-releases/index.json", mockito::SERVER_URL)
+        }
+        fn public_yarn_latest_version() -> String {
+            format!("{}/yarn-latest", mockito::SERVER_URL)
+        }
     } else {
         fn public_yarn_server_root() -> String {
             "https://github.com/notion-cli/yarn-releases/raw/master/dist".to_string()
         }
+        /// Return the URL of the index of available Yarn versions on the public git repository.
+        fn public_yarn_version_index() -> String {
+            "https://github.com/notion-cli/yarn-releases/raw/master/index.json".to_string()
+        }
+        /// URL of the latest Yarn version on the public yarnpkg.com
+        fn public_yarn_latest_version() -> String {
+            "https://yarnpkg.com/latest-version".to_string()
+        }
     }
 }
 
-/// A provisioned Yarn distribution.
-pub struct YarnDistro {
-    archive: Box<Archive>,
-    version: Version,
+/// Returns the URL of the index of available Yarn versions, on the given mirror if any.
+fn yarn_version_index(mirror: Option<&str>) -> String {
+    match mirror {
+        Some(mirror) => format!("{}/index.json", mirror.trim_right_matches('/')),
+        None => public_yarn_version_index(),
+    }
 }
 
-/// Check if the cached file is valid. It may have been corrupted or interrupted in the middle of
-/// downloading.
-// ISSUE(#134) - verify checksum
-fn cache_is_valid(cache_file: &PathBuf) -> bool {
-    if cache_file.is_file() {
-        if let Ok(file) = File::open(cache_file) {
-            match node_archive::load(file) {
-                Ok(_) => return true,
-                Err(_) => return false,
-            }
-        }
-    }
-    false
+/// Fetches the list of released Yarn versions from the public Yarn index, or from the
+/// index on the given mirror.
+fn resolve_yarn_versions(mirror: Option<&str>) -> Fallible<Vec<String>> {
+    let url = yarn_version_index(mirror);
+    let spinner = progress_spinner(&format!("Fetching public registry: {}", url));
+    let releases: Vec<String> = reqwest::get(url.as_str())
+        .with_context(RegistryFetchError::from_error)?
+        .json()
+        .unknown()?;
+    spinner.finish_and_clear();
+    Ok(releases)
 }
 
-impl Distro for YarnDistro {
-    /// Provision a distribution from the public Yarn distributor (`https://yarnpkg.com`).
-    fn public(version: Version) -> Fallible<Self> {
-        let archive_file = path::yarn_archive_file(&version.to_string());
-        let url = format!("{}/{}", public_yarn_server_root(), archive_file);
-        YarnDistro::remote(version, &url)
+/// Yarn, which is released on the Yarn releases repository.
+pub enum Yarn {}
+
+impl ArchiveTool for Yarn {
+    const NAME: &'static str = "yarn";
+    const DISPLAY_NAME: &'static str = "Yarn";
+
+    /// Returns the URL of the archive on the public Yarn distributor, or on a mirror of the
+    /// Yarn releases repository, which serves archives under `dist/`.
+    fn public_url(version: &Version, mirror: Option<&str>) -> String {
+        let root = match mirror {
+            Some(mirror) => format!("{}/dist", mirror.trim_right_matches('/')),
+            None => public_yarn_server_root(),
+        };
+        format!("{}/{}", root, path::yarn_archive_file(&version.to_string()))
     }
 
-    /// Provision a distribution from a remote distributor.
-    fn remote(version: Version, url: &str) -> Fallible<Self> {
-        let archive_file = path::yarn_archive_file(&version.to_string());
-        let cache_file = path::yarn_cache_dir()?.join(&archive_file);
-
-        if cache_is_valid(&cache_file) {
-            return YarnDistro::cached(version, File::open(cache_file).unknown()?);
-        }
-
-        ensure_containing_dir_exists(&cache_file)?;
-        Ok(YarnDistro {
-            archive: node_archive::fetch(url, &cache_file)
-                .with_context(DownloadError::for_version(version.to_string()))?,
-            version: version,
-        })
+    fn resolve_public(matching: &VersionSpec, mirror: Option<&str>) -> Fallible<Version> {
+        let version = match (matching, mirror) {
+            (&VersionSpec::Latest, None) => {
+                let mut response: reqwest::Response =
+                    reqwest::get(public_yarn_latest_version().as_str())
+                        .with_context(RegistryFetchError::from_error)?;
+                response.text().unknown()?
+            }
+            // Mirrors only provide the index, so the latest version is found there.
+            (&VersionSpec::Latest, Some(_)) => {
+                let latest = Yarn::ls_public(mirror)?.into_iter().max();
+
+                if let Some(version) = latest {
+                    version.to_string()
+                } else {
+                    throw!(NoYarnVersionFoundError {
+                        matching: VersionReq::any(),
+                    });
+                }
+            }
+            (&VersionSpec::Semver(ref matching), _) => {
+                let releases = resolve_yarn_versions(mirror)?;
+                let version = releases.into_iter().find(|v| {
+                    let v = Version::parse(v).unwrap();
+                    matching.matches(&v)
+                });
+
+                if let Some(version) = version {
+                    version
+                } else {
+                    throw!(NoYarnVersionFoundError {
+                        matching: matching.clone(),
+                    });
+                }
+            }
+            (&VersionSpec::Lts, _) | (&VersionSpec::LtsLine(_), _) => {
+                throw!(NoLtsReleasesError {
+                    tool: Yarn::DISPLAY_NAME.to_string(),
+                });
+            }
+        };
+        Version::parse(&version).unknown()
     }
 
-    /// Provision a distribution from the filesystem.
-    fn cached(version: Version, file: File) -> Fallible<Self> {
-        Ok(YarnDistro {
-            archive: node_archive::load(file).unknown()?,
-            version: version,
-        })
+    fn ls_public(mirror: Option<&str>) -> Fallible<Vec<Version>> {
+        resolve_yarn_versions(mirror)?
+            .into_iter()
+            .map(|v| Version::parse(&v).unknown())
+            .collect()
     }
 
-    /// Produces a reference to this distro's Yarn version.
-    fn version(&self) -> &Version {
-        &self.version
+    fn archive_file(version: &str) -> String {
+        path::yarn_archive_file(version)
     }
 
-    /// Fetches this version of Yarn. (It is left to the responsibility of the `YarnCollection`
-    /// to update its state after fetching succeeds.)
-    fn fetch(self, collection: &YarnCollection) -> Fallible<Fetched> {
-        if collection.contains(&self.version) {
-            return Ok(Fetched::Already(self.version));
-        }
+    fn archive_version(file_name: &str) -> Option<&str> {
+        path::yarn_archive_version(file_name)
+    }
 
-        let dest = path::yarn_versions_dir()?;
-        let bar = progress_bar(
-            Action::Fetching,
-            &format!("v{}", self.version),
-            self.archive
-                .uncompressed_size()
-                .unwrap_or(self.archive.compressed_size()),
-        );
-
-        self.archive
-            .unpack(&dest, &mut |_, read| {
-                bar.inc(read as u64);
-            })
-            .unknown()?;
-
-        let version_string = self.version.to_string();
-        rename(
-            dest.join(path::yarn_archive_root_dir(&version_string)),
-            path::yarn_version_dir(&version_string)?,
-        ).unknown()?;
-
-        bar.finish_and_clear();
-        Ok(Fetched::Now(self.version))
+    fn archive_root_dir(version: &str) -> String {
+        path::yarn_archive_root_dir(version)
     }
 }
+
+/// A provisioned Yarn distribution.
+pub type YarnDistro = ArchiveDistro<Yarn>;
@@ -1,12 +1,69 @@
 //! Provides utilities for extracting standard Notion environment variables.
 
 use std::env;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
 pub(crate) fn shell_name() -> Option<String> {
     env::var_os("NOTION_SHELL").map(|s| s.to_string_lossy().into_owned())
 }
 
+/// Returns the name of the environment variable holding a setting of a tool,
+/// e.g. `NOTION_NODE_VERSION` for the `version` setting of `node`.
+fn tool_var(tool: &str, setting: &str) -> String {
+    format!("NOTION_{}_{}", tool.to_uppercase(), setting.to_uppercase())
+}
+
+/// Returns the name of the environment variable that overrides a configuration setting,
+/// e.g. `NOTION_NODE_MIRROR` for `node.mirror` or `NOTION_YARN_LS_REMOTE_URL` for
+/// `yarn.ls-remote.url`.
+pub(crate) fn config_var(key: &str) -> String {
+    format!(
+        "NOTION_{}",
+        key.replace('.', "_").replace('-', "_").to_uppercase()
+    )
+}
+
+/// Returns the value of an environment variable that overrides a configuration setting,
+/// if it is set in the environment `vars` looks variables up in. (An empty value leaves the
+/// setting alone.)
+pub(crate) fn config_value<F>(var: &str, vars: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    vars(var)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string_lossy().into_owned())
+}
+
+/// Returns the name of the environment variable that overrides the user's default
+/// version of a tool, e.g. `NOTION_NODE_VERSION`.
+pub(crate) fn version_var(tool: &str) -> String {
+    tool_var(tool, "version")
+}
+
+/// Returns whether Notion should avoid accessing the network, as requested with
+/// `NOTION_OFFLINE` (which the `--offline` flag sets).
+pub fn offline() -> bool {
+    match env::var_os("NOTION_OFFLINE") {
+        Some(value) => {
+            let value = value.to_string_lossy();
+            !(value.is_empty() || value == "0" || value == "false")
+        }
+        None => false,
+    }
+}
+
+/// Returns the command line of the user's preferred text editor, set with `VISUAL` or
+/// `EDITOR`, if any.
+pub(crate) fn editor() -> Option<String> {
+    env::var_os("VISUAL")
+        .into_iter()
+        .chain(env::var_os("EDITOR"))
+        .find(|editor| !editor.is_empty())
+        .map(|editor| editor.to_string_lossy().into_owned())
+}
+
 pub fn postscript_path() -> Option<PathBuf> {
     env::var_os("NOTION_POSTSCRIPT")
         .as_ref()
@@ -26,10 +83,37 @@ pub mod tests {
         assert_eq!(shell_name().unwrap(), "bash".to_string());
     }
 
+    #[test]
+    fn test_config_var() {
+        assert_eq!(config_var("node.mirror"), "NOTION_NODE_MIRROR".to_string());
+        assert_eq!(
+            config_var("yarn.ls-remote.url"),
+            "NOTION_YARN_LS_REMOTE_URL".to_string()
+        );
+    }
+
+    #[test]
+    fn test_config_value() {
+        let mirror = |_: &str| Some(OsString::from("https://mirror.example.com/node"));
+        assert_eq!(
+            config_value("NOTION_NODE_MIRROR", mirror).unwrap(),
+            "https://mirror.example.com/node".to_string()
+        );
+        assert_eq!(
+            config_value("NOTION_NODE_MIRROR", |_: &str| Some(OsString::new())),
+            None
+        );
+        assert_eq!(config_value("NOTION_NODE_MIRROR", |_: &str| None), None);
+    }
+
+    #[test]
+    fn test_version_var() {
+        assert_eq!(version_var("node"), "NOTION_NODE_VERSION".to_string());
+    }
+
     #[test]
     fn test_postscript_path() {
         env::set_var("NOTION_POSTSCRIPT", "/some/path");
         assert_eq!(postscript_path().unwrap(), PathBuf::from("/some/path"));
     }
-
 }
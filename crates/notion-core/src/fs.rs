@@ -1,10 +1,15 @@
 //! Provides utilities for operating on the filesystem.
 
+use std::fmt::{self, Display, Formatter};
 use std::fs::{self, create_dir_all, File};
 use std::io::{self, ErrorKind};
 use std::path::{Path, PathBuf};
 
-use notion_fail::{ExitCode, FailExt, Fallible, NotionFail, ResultExt};
+use notion_fail::{ExitCode, FailExt, Fallible, NotionError, NotionFail, ResultExt};
+use path;
+use serde_json;
+use tempfile::NamedTempFile;
+use toml;
 
 pub fn touch(path: &Path) -> Fallible<File> {
     if !path.is_file() {
@@ -37,6 +42,134 @@ impl CreateDirError {
 #[notion_fail(code = "UnknownError")]
 pub(crate) struct PathInternalError;
 
+/// A position in a file, shown in error messages as `path:line:column`, or just the path if
+/// the position isn't known.
+#[derive(Debug)]
+pub(crate) struct FileLocation {
+    file: PathBuf,
+    /// The line and column, counting from 1.
+    line_col: Option<(usize, usize)>,
+}
+
+impl FileLocation {
+    pub(crate) fn new(file: &Path, line_col: Option<(usize, usize)>) -> Self {
+        FileLocation {
+            file: file.to_path_buf(),
+            line_col,
+        }
+    }
+
+    /// Locates a byte offset in the source of a file, if it's known.
+    pub(crate) fn at_offset(file: &Path, src: &str, offset: Option<usize>) -> Self {
+        let line_col = offset.map(|offset| {
+            let before = &src[..offset];
+            let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+            (
+                before.matches('\n').count() + 1,
+                before[line_start..].chars().count() + 1,
+            )
+        });
+        FileLocation::new(file, line_col)
+    }
+}
+
+impl Display for FileLocation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some((line, column)) = self.line_col {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        Ok(())
+    }
+}
+
+/// Thrown when a file such as `config.toml` or `package.json` isn't valid TOML or JSON, or
+/// doesn't have the expected structure.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Could not parse {}: {}", location, error)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct FileParseError {
+    location: FileLocation,
+    error: String,
+}
+
+impl FileParseError {
+    pub(crate) fn for_toml(file: &Path) -> impl FnOnce(&toml::de::Error) -> FileParseError {
+        let file = file.to_path_buf();
+        move |error| {
+            // The message ends with the line, which the location shows instead.
+            let line_col = error.line_col().map(|(line, col)| (line + 1, col + 1));
+            let suffix = line_col.map(|(line, _)| format!(" at line {}", line));
+            FileParseError {
+                location: FileLocation::new(&file, line_col),
+                error: without_suffix(error.to_string(), suffix),
+            }
+        }
+    }
+
+    pub(crate) fn for_json(file: &Path) -> impl FnOnce(&serde_json::Error) -> FileParseError {
+        let file = file.to_path_buf();
+        move |error| {
+            // The message ends with the line and column, which the location shows instead.
+            let (line, column) = (error.line(), error.column());
+            let (line_col, suffix) = if line > 0 {
+                // The column is 0 at the start of a line, e.g. at an unexpected end of file.
+                (
+                    Some((line, column.max(1))),
+                    Some(format!(" at line {} column {}", line, column)),
+                )
+            } else {
+                (None, None)
+            };
+            FileParseError {
+                location: FileLocation::new(&file, line_col),
+                error: without_suffix(error.to_string(), suffix),
+            }
+        }
+    }
+}
+
+fn without_suffix(mut message: String, suffix: Option<String>) -> String {
+    if let Some(suffix) = suffix {
+        if message.ends_with(&suffix) {
+            let len = message.len() - suffix.len();
+            message.truncate(len);
+        }
+    }
+    message
+}
+
+/// Thrown when a value in a file such as `config.toml` or `catalog.toml` is invalid, e.g. a
+/// version that can't be parsed or a plugin with both a `url` and a `bin`.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Invalid `{}` in {}: {}", key, location, error)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct InvalidFileValueError {
+    key: String,
+    location: FileLocation,
+    error: String,
+}
+
+impl InvalidFileValueError {
+    pub(crate) fn new(key: String, location: FileLocation, error: &NotionError) -> Self {
+        // Report the underlying problem, e.g. a type mismatch, rather than hiding it behind a
+        // generic message.
+        let error = if error.is_user_friendly() {
+            error.to_string()
+        } else {
+            error
+                .as_fail()
+                .cause()
+                .map_or_else(|| error.to_string(), |cause| cause.to_string())
+        };
+        InvalidFileValueError {
+            key,
+            location,
+            error,
+        }
+    }
+}
+
 /// This creates the parent directory of the input path, assuming the input path is a file.
 pub fn ensure_containing_dir_exists<P: AsRef<Path>>(path: &P) -> Fallible<()> {
     if let Some(dir) = path.as_ref().parent() {
@@ -48,6 +181,16 @@ pub fn ensure_containing_dir_exists<P: AsRef<Path>>(path: &P) -> Fallible<()> {
     }
 }
 
+/// Creates a temporary file in Notion's `tmp` directory. Since that is in the same place as the
+/// rest of Notion's files, the temporary file can always be persisted into its final location.
+pub fn create_staging_file() -> Fallible<NamedTempFile> {
+    let tmp_dir = path::tmp_dir()?;
+    fs::create_dir_all(&tmp_dir).with_context(CreateDirError::for_dir(
+        tmp_dir.to_string_lossy().to_string(),
+    ))?;
+    NamedTempFile::new_in(&tmp_dir).unknown()
+}
+
 /// Reads a file, if it exists.
 pub fn read_file_opt(path: &PathBuf) -> io::Result<Option<String>> {
     let result: io::Result<String> = fs::read_to_string(path);
@@ -60,3 +203,23 @@ pub fn read_file_opt(path: &PathBuf) -> io::Result<Option<String>> {
         },
     }
 }
+
+/// Computes the total size in bytes of the files in a directory tree, without following
+/// symlinks. Returns 0 if the directory doesn't exist.
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(ref error) if error.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(error),
+    };
+
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += dir_size(&entry?.path())?;
+    }
+    Ok(size)
+}
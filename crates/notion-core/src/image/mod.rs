@@ -1,35 +1,75 @@
+use std::collections::{btree_map, BTreeMap};
 use std::ffi::OsString;
 use std::path::PathBuf;
 
 use envoy;
 use semver::Version;
 
+use distro::node::NodeDistro;
+use distro::npm::NpmDistro;
+use distro::Distro;
 use notion_fail::{Fallible, ResultExt};
 use path;
+use registry::{self, Visitor};
 
 /// A platform image.
+#[derive(Clone)]
 pub struct Image {
     /// The pinned version of Node, under the `toolchain.node` key.
-    pub node: Version,
-    /// The pinned version of Node as a string.
-    pub node_str: String,
-    /// The pinned version of Yarn, under the `toolchain.yarn` key.
-    pub yarn: Option<Version>,
-    /// The pinned version of Yarn as a string.
-    pub yarn_str: Option<String>,
+    node: Version,
+    /// The pinned versions of the other tools, keyed by their names in the `toolchain`
+    /// section (e.g. `yarn`).
+    tools: BTreeMap<&'static str, Version>,
 }
 
 impl Image {
-    pub fn bins(&self) -> Fallible<Vec<PathBuf>> {
-        let mut bins = vec![path::node_version_bin_dir(&self.node_str)?];
-        if let Some(ref yarn_str) = self.yarn_str {
-            bins.push(path::yarn_version_bin_dir(yarn_str)?);
+    /// Produces an image with only the specified version of Node.
+    pub fn for_node(node: Version) -> Self {
+        Image {
+            node,
+            tools: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the pinned version of Node.
+    pub fn node(&self) -> &Version {
+        &self.node
+    }
+
+    /// Returns the pinned version of a tool, if any.
+    pub fn pinned<D: Distro>(&self) -> Option<&Version> {
+        if D::NAME == NodeDistro::NAME {
+            Some(&self.node)
+        } else {
+            self.tools.get(D::NAME)
         }
-        Ok(bins)
+    }
+
+    /// Pins a version of a tool in this image, replacing any version already pinned.
+    pub fn pin<D: Distro>(&mut self, version: Version) {
+        if D::NAME == NodeDistro::NAME {
+            self.node = version;
+        } else {
+            self.tools.insert(D::NAME, version);
+        }
+    }
+
+    /// Iterates over the pinned versions of the tools besides Node, by name.
+    pub fn tools(&self) -> btree_map::Iter<&'static str, Version> {
+        self.tools.iter()
+    }
+
+    pub fn bins(&self) -> Fallible<Vec<PathBuf>> {
+        let mut collector = BinCollector {
+            image: self,
+            bins: vec![],
+        };
+        registry::visit(&mut collector)?;
+        Ok(collector.bins)
     }
 
     /// Produces a modified version of the current `PATH` environment variable that
-    /// will find toolchain executables (Node, Yarn) in the installation directories
+    /// will find toolchain executables (Node, Yarn, npm, pnpm) in the installation directories
     /// for the given versions instead of in the Notion shim directory.
     pub fn path(&self) -> Fallible<OsString> {
         let old_path = envoy::path().unwrap_or(envoy::Var::from(""));
@@ -45,6 +85,27 @@ impl Image {
     }
 }
 
+/// Collects the executable directories of the tool versions pinned by an image.
+struct BinCollector<'a> {
+    image: &'a Image,
+    bins: Vec<PathBuf>,
+}
+
+impl<'a> Visitor for BinCollector<'a> {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        if let Some(version) = self.image.pinned::<D>() {
+            let bin_dir = D::bin_dir(&version.to_string())?;
+            // A pinned npm must shadow the npm bundled with Node, so it goes ahead of Node.
+            if D::NAME == NpmDistro::NAME {
+                self.bins.insert(0, bin_dir);
+            } else {
+                self.bins.push(bin_dir);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// A lightweight namespace type representing the system environment, i.e. the environment
 /// with Notion removed.
 pub struct System;
@@ -72,6 +133,7 @@ impl System {
 mod test {
 
     use super::*;
+    use distro::yarn::YarnDistro;
     use std;
     use std::path::PathBuf;
     use semver::Version;
@@ -80,6 +142,12 @@ mod test {
     use winfolder;
 
     fn notion_base() -> PathBuf {
+        if let Some(notion_home) = std::env::var_os("NOTION_HOME") {
+            if !notion_home.is_empty() {
+                return PathBuf::from(notion_home);
+            }
+        }
+
         #[cfg(unix)]
         return PathBuf::from(std::env::home_dir().expect("Could not get home directory")).join(".notion");
 
@@ -127,24 +195,15 @@ mod test {
         let v123 = Version::parse("1.2.3").unwrap();
         let v457 = Version::parse("4.5.7").unwrap();
 
-        let no_yarn_image = Image {
-            node: v123.clone(),
-            node_str: v123.to_string(),
-            yarn: None,
-            yarn_str: None
-        };
+        let no_yarn_image = Image::for_node(v123.clone());
 
         assert_eq!(
             no_yarn_image.path().unwrap().into_string().unwrap(),
             format!("{}:/usr/bin:/blah:/doesnt/matter/bin", expected_node_bin),
         );
 
-        let with_yarn_image = Image {
-            node: v123.clone(),
-            node_str: v123.to_string(),
-            yarn: Some(v457.clone()),
-            yarn_str: Some(v457.to_string())
-        };
+        let mut with_yarn_image = Image::for_node(v123.clone());
+        with_yarn_image.pin::<YarnDistro>(v457.clone());
 
         assert_eq!(
             with_yarn_image.path().unwrap().into_string().unwrap(),
@@ -152,6 +211,38 @@ mod test {
         );
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_image_path_with_npm() {
+        std::env::set_var("PATH", format!("/usr/bin:{}", shim_dir().to_string_lossy()));
+
+        let node_bin = notion_base()
+            .join("versions")
+            .join("node")
+            .join("1.2.3")
+            .join("bin");
+        let expected_node_bin = node_bin.as_path().to_str().unwrap();
+
+        let npm_bin = notion_base()
+            .join("versions")
+            .join("npm")
+            .join("6.1.0")
+            .join("bin");
+        let expected_npm_bin = npm_bin.as_path().to_str().unwrap();
+
+        let v123 = Version::parse("1.2.3").unwrap();
+        let v610 = Version::parse("6.1.0").unwrap();
+
+        let mut with_npm_image = Image::for_node(v123.clone());
+        with_npm_image.pin::<NpmDistro>(v610.clone());
+
+        // the pinned npm must shadow the one bundled with Node
+        assert_eq!(
+            with_npm_image.path().unwrap().into_string().unwrap(),
+            format!("{}:{}:/usr/bin", expected_npm_bin, expected_node_bin),
+        );
+    }
+
     #[test]
     #[cfg(windows)]
     fn test_image_path() {
@@ -183,24 +274,15 @@ mod test {
         let v123 = Version::parse("1.2.3").unwrap();
         let v457 = Version::parse("4.5.7").unwrap();
 
-        let no_yarn_image = Image {
-            node: v123.clone(),
-            node_str: v123.to_string(),
-            yarn: None,
-            yarn_str: None
-        };
+        let no_yarn_image = Image::for_node(v123.clone());
 
         assert_eq!(
             no_yarn_image.path().unwrap().into_string().unwrap(),
             format!("{};C:\\\\somebin;D:\\\\ProbramFlies", expected_node_bin),
         );
 
-        let with_yarn_image = Image {
-            node: v123.clone(),
-            node_str: v123.to_string(),
-            yarn: Some(v457.clone()),
-            yarn_str: Some(v457.to_string())
-        };
+        let mut with_yarn_image = Image::for_node(v123.clone());
+        with_yarn_image.pin::<YarnDistro>(v457.clone());
 
         assert_eq!(
             with_yarn_image.path().unwrap().into_string().unwrap(),
@@ -34,9 +34,11 @@ pub(crate) mod fs;
 pub mod image;
 pub mod manifest;
 pub mod monitor;
+mod package;
 pub mod path;
 mod plugin;
 pub mod project;
+pub mod registry;
 pub mod session;
 pub mod shell;
 pub mod shim;
@@ -0,0 +1,337 @@
+//! Provides format-preserving edits of the top-level keys of a JSON document, so that
+//! updating `package.json` leaves everything besides the edited key exactly as it was.
+
+use std::ops::Range;
+
+use notion_fail::{ExitCode, Fallible, NotionFail, ResultExt};
+use serde::Serialize;
+use serde_json::{self, Value};
+
+/// Thrown when a manifest can't be edited because it isn't a JSON object.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Could not update package.json: the manifest is not a JSON object")]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct ManifestNotObjectError;
+
+/// A member of the top-level object of a JSON document.
+struct Member {
+    /// The position of the opening quote of the key.
+    start: usize,
+    key: String,
+    /// The span of the member's value.
+    value: Range<usize>,
+}
+
+/// The top-level object of a JSON document.
+struct Object {
+    /// The position just after the opening brace.
+    start: usize,
+    members: Vec<Member>,
+}
+
+/// Sets a top-level key of a JSON object to a value, replacing the key's current value in
+/// place or, if it's missing, adding it after the last member. The rest of the source,
+/// including its key order, whitespace and line endings, is left unchanged.
+pub(crate) fn set_key<T: Serialize>(
+    src: &str,
+    key: &str,
+    value: &T,
+    indent: &str,
+) -> Fallible<String> {
+    let object = parse_object(src)?;
+    let eol = line_ending(src);
+    let rendered = render(value, indent, eol)?;
+
+    let mut result = String::with_capacity(src.len() + rendered.len());
+    match object.members.iter().find(|member| member.key == key) {
+        Some(member) => {
+            result.push_str(&src[..member.value.start]);
+            result.push_str(&rendered);
+            result.push_str(&src[member.value.end..]);
+        }
+        None => {
+            let entry = format!(
+                "{}{}{}: {}",
+                eol,
+                indent,
+                serde_json::to_string(key).unknown()?,
+                rendered
+            );
+            match object.members.last() {
+                Some(last) => {
+                    result.push_str(&src[..last.value.end]);
+                    result.push(',');
+                    result.push_str(&entry);
+                    result.push_str(&src[last.value.end..]);
+                }
+                None => {
+                    result.push_str(&src[..object.start]);
+                    result.push_str(&entry);
+                    result.push_str(eol);
+                    result.push_str(&src[skip_whitespace(src.as_bytes(), object.start)..]);
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Removes a top-level key from a JSON object, along with the comma that separated it from
+/// its neighbor, leaving the rest of the source unchanged. Returns `None` if the key is missing.
+pub(crate) fn remove_key(src: &str, key: &str) -> Fallible<Option<String>> {
+    let object = parse_object(src)?;
+    let index = match object.members.iter().position(|member| member.key == key) {
+        Some(index) => index,
+        None => {
+            return Ok(None);
+        }
+    };
+
+    let member = &object.members[index];
+    let removed = if index > 0 {
+        // from the end of the previous member, which takes the preceding comma with it
+        object.members[index - 1].value.end..member.value.end
+    } else if let Some(next) = object.members.get(index + 1) {
+        // up to the start of the next member, which takes the following comma with it
+        member.start..next.start
+    } else {
+        // the only member
+        object.start..member.value.end
+    };
+
+    let mut result = String::with_capacity(src.len());
+    result.push_str(&src[..removed.start]);
+    result.push_str(&src[removed.end..]);
+    Ok(Some(result))
+}
+
+/// Returns the position of a top-level key of a JSON object, if it's there.
+pub(crate) fn locate_key(src: &str, key: &str) -> Option<usize> {
+    let object = parse_object(src).ok()?;
+    let found = object
+        .members
+        .iter()
+        .find(|member| member.key == key)
+        .map(|member| member.start);
+    found
+}
+
+/// Renders a value as pretty-printed JSON nested one level deep in the top-level object.
+fn render<T: Serialize>(value: &T, indent: &str, eol: &str) -> Fallible<String> {
+    let mut buf = Vec::new();
+    {
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        value.serialize(&mut ser).unknown()?;
+    }
+    let rendered = String::from_utf8(buf).unknown()?;
+    Ok(rendered.replace('\n', &format!("{}{}", eol, indent)))
+}
+
+/// Returns the line ending used by a source file.
+fn line_ending(src: &str) -> &'static str {
+    if src.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// Locates the members of the top-level object of a JSON document.
+fn parse_object(src: &str) -> Fallible<Object> {
+    // Validating the document up front means the scan below only sees well-formed JSON.
+    let document: Value = serde_json::from_str(src).unknown()?;
+    if !document.is_object() {
+        throw!(ManifestNotObjectError);
+    }
+
+    let bytes = src.as_bytes();
+    let mut pos = skip_whitespace(bytes, 0);
+    // the opening brace
+    pos += 1;
+    let start = pos;
+
+    let mut members = vec![];
+    loop {
+        pos = skip_whitespace(bytes, pos);
+        match bytes[pos] {
+            b'}' => break,
+            b',' => {
+                pos += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let key_start = pos;
+        let key_end = skip_string(bytes, key_start);
+        let key: String = serde_json::from_str(&src[key_start..key_end]).unknown()?;
+
+        // the colon
+        pos = skip_whitespace(bytes, key_end) + 1;
+        let value_start = skip_whitespace(bytes, pos);
+        let value_end = skip_value(bytes, value_start);
+        members.push(Member {
+            start: key_start,
+            key,
+            value: value_start..value_end,
+        });
+        pos = value_end;
+    }
+
+    Ok(Object { start, members })
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b' ' | b'\t' | b'\r' | b'\n' => pos += 1,
+            // a byte order mark
+            0xEF if bytes[pos..].starts_with(b"\xEF\xBB\xBF") => pos += 3,
+            _ => break,
+        }
+    }
+    pos
+}
+
+/// Returns the position just after the string starting at `pos`.
+fn skip_string(bytes: &[u8], mut pos: usize) -> usize {
+    // the opening quote
+    pos += 1;
+    while bytes[pos] != b'"' {
+        if bytes[pos] == b'\\' {
+            pos += 1;
+        }
+        pos += 1;
+    }
+    pos + 1
+}
+
+/// Returns the position just after the value starting at `pos`.
+fn skip_value(bytes: &[u8], mut pos: usize) -> usize {
+    match bytes[pos] {
+        b'"' => skip_string(bytes, pos),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            loop {
+                match bytes[pos] {
+                    b'"' => {
+                        pos = skip_string(bytes, pos);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return pos + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+        }
+        _ => {
+            while pos < bytes.len() {
+                match bytes[pos] {
+                    b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n' => break,
+                    _ => pos += 1,
+                }
+            }
+            pos
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::{remove_key, set_key};
+    use serde_json::{self, Value};
+
+    fn toolchain(node: &str) -> Value {
+        serde_json::from_str(&format!(r#"{{"node": "{}"}}"#, node)).unwrap()
+    }
+
+    #[test]
+    fn replaces_existing_key_in_place() {
+        let src = "{\n  \"name\": \"test\",\n  \"toolchain\": {\n    \"node\": \"8.9.4\"\n  },\n  \"files\": [\"a\", \"b\"]\n}\n";
+        let expected = "{\n  \"name\": \"test\",\n  \"toolchain\": {\n    \"node\": \"10.13.0\"\n  },\n  \"files\": [\"a\", \"b\"]\n}\n";
+        assert_eq!(
+            set_key(src, "toolchain", &toolchain("10.13.0"), "  ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn appends_missing_key() {
+        let src = "{\n    \"name\": \"test\",\n    \"version\": \"1.0.0\"\n}\n";
+        let expected = "{\n    \"name\": \"test\",\n    \"version\": \"1.0.0\",\n    \"toolchain\": {\n        \"node\": \"10.13.0\"\n    }\n}\n";
+        assert_eq!(
+            set_key(src, "toolchain", &toolchain("10.13.0"), "    ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn preserves_crlf_and_missing_final_newline() {
+        let src = "{\r\n  \"name\": \"test\"\r\n}";
+        let expected = "{\r\n  \"name\": \"test\",\r\n  \"toolchain\": {\r\n    \"node\": \"10.13.0\"\r\n  }\r\n}";
+        assert_eq!(
+            set_key(src, "toolchain", &toolchain("10.13.0"), "  ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn adds_key_to_empty_object() {
+        assert_eq!(
+            set_key("{}\n", "toolchain", &toolchain("10.13.0"), "  ").unwrap(),
+            "{\n  \"toolchain\": {\n    \"node\": \"10.13.0\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn skips_nested_strings_and_keys() {
+        let src = "{\"scripts\": {\"toolchain\": \"} ] \\\" {\"}, \"toolchain\": {\"node\": \"8.9.4\"}}";
+        let expected = "{\"scripts\": {\"toolchain\": \"} ] \\\" {\"}, \"toolchain\": {\n\"node\": \"10.13.0\"\n}}";
+        assert_eq!(
+            set_key(src, "toolchain", &toolchain("10.13.0"), "").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn removes_last_key() {
+        let src = "{\n  \"name\": \"test\",\n  \"toolchain\": {\n    \"node\": \"8.9.4\"\n  }\n}\n";
+        assert_eq!(
+            remove_key(src, "toolchain").unwrap().unwrap(),
+            "{\n  \"name\": \"test\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn removes_first_key() {
+        let src = "{\n  \"toolchain\": {\n    \"node\": \"8.9.4\"\n  },\n  \"name\": \"test\"\n}\n";
+        assert_eq!(
+            remove_key(src, "toolchain").unwrap().unwrap(),
+            "{\n  \"name\": \"test\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn removes_only_key() {
+        let src = "{\n  \"toolchain\": {\n    \"node\": \"8.9.4\"\n  }\n}\n";
+        assert_eq!(remove_key(src, "toolchain").unwrap().unwrap(), "{\n}\n");
+    }
+
+    #[test]
+    fn removes_missing_key() {
+        assert!(remove_key("{\"name\": \"test\"}", "toolchain").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_non_objects() {
+        assert!(set_key("[]", "toolchain", &toolchain("10.13.0"), "  ").is_err());
+    }
+}
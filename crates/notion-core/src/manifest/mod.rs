@@ -1,18 +1,22 @@
 //! Provides the `Manifest` type, which represents a Node manifest file (`package.json`).
 
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::str::FromStr;
 
 use detect_indent;
+use distro::Distro;
+use fs::{FileLocation, FileParseError, InvalidFileValueError};
 use notion_fail::{ExitCode, Fallible, NotionFail, ResultExt};
 use image::Image;
-use semver::Version;
-use serde::Serialize;
+use semver::{ReqParseError, Version, VersionReq};
 use serde_json;
 
+mod edit;
 pub(crate) mod serial;
 
 #[derive(Debug, Fail, NotionFail)]
@@ -30,10 +34,111 @@ impl PackageReadError {
     }
 }
 
+/// A version range from the `engines` section of a manifest, such as `>=8.9 || 10`.
+#[derive(Debug, Clone)]
+pub struct EngineRange {
+    src: String,
+    alternatives: Vec<VersionReq>,
+}
+
+impl EngineRange {
+    /// Tests whether a version satisfies any of the alternatives in this range.
+    pub fn matches(&self, version: &Version) -> bool {
+        self.alternatives.iter().any(|req| req.matches(version))
+    }
+}
+
+impl FromStr for EngineRange {
+    type Err = ReqParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let alternatives = src
+            .split("||")
+            .map(parse_npm_range)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(EngineRange {
+            src: src.trim().to_string(),
+            alternatives,
+        })
+    }
+}
+
+/// Translates one alternative of an npm version range into semantic versioning requirements.
+/// Besides single comparators, npm ranges can have several comparators separated by spaces
+/// (`>=8.9 <11`), hyphen ranges (`8.9 - 10`) and x-ranges (`8.x`, `10.*`, `*`).
+fn parse_npm_range(src: &str) -> Result<VersionReq, ReqParseError> {
+    // An operator can be separated from its version by spaces, as in `>= 8.9`.
+    let mut comparators = vec![];
+    let mut pending = String::new();
+    for word in src.split_whitespace() {
+        pending.push_str(word);
+        if !word.chars().all(|c| "<>=~^".contains(c)) {
+            comparators.push(pending);
+            pending = String::new();
+        }
+    }
+    if !pending.is_empty() {
+        comparators.push(pending);
+    }
+
+    let mut requirements = vec![];
+    let mut rest = &comparators[..];
+    while !rest.is_empty() {
+        if rest.len() >= 3 && rest[1] == "-" {
+            let (lower, upper) = (partial_version(&rest[0]), partial_version(&rest[2]));
+            if !lower.is_empty() {
+                requirements.push(format!(">={}", lower));
+            }
+            if !upper.is_empty() {
+                requirements.push(format!("<={}", upper));
+            }
+            rest = &rest[3..];
+        } else {
+            let split = rest[0]
+                .find(|c| !"<>=~^".contains(c))
+                .unwrap_or(rest[0].len());
+            let (op, version) = rest[0].split_at(split);
+            let version = partial_version(version);
+            if !version.is_empty() {
+                let op = if op.is_empty() { "=" } else { op };
+                requirements.push(format!("{}{}", op, version));
+            }
+            rest = &rest[1..];
+        }
+    }
+
+    if requirements.is_empty() {
+        return Ok(VersionReq::any());
+    }
+    VersionReq::parse(&requirements.join(", "))
+}
+
+/// Strips the `v` prefix and any wildcard components from a version in an npm range, leaving
+/// a (possibly partial or empty) version: `v8.x` becomes `8`, and `*` becomes empty.
+fn partial_version(src: &str) -> String {
+    src.trim_left_matches('v')
+        .split('.')
+        .take_while(|part| !["x", "X", "*"].contains(part))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+impl fmt::Display for EngineRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.src)
+    }
+}
+
 /// A Node manifest file.
 pub struct Manifest {
     /// The platform image specified by the `toolchain` section.
     pub platform_image: Option<Rc<Image>>,
+    /// The `engines` section, containing a map of tool names to the version ranges the
+    /// project requires.
+    pub engines: HashMap<String, EngineRange>,
+    /// The globs of the workspace packages in the `workspaces` section, if this is the root
+    /// of a Yarn workspace.
+    pub workspaces: Vec<String>,
     /// The `dependencies` section.
     pub dependencies: HashMap<String, String>,
     /// The `devDependencies` section.
@@ -45,10 +150,19 @@ pub struct Manifest {
 impl Manifest {
     /// Loads and parses a Node manifest for the project rooted at the specified path.
     pub fn for_dir(project_root: &Path) -> Fallible<Manifest> {
-        let file = File::open(project_root.join("package.json"))
+        let path = project_root.join("package.json");
+        let mut src = String::new();
+        File::open(&path)
+            .and_then(|mut file| file.read_to_string(&mut src))
             .with_context(PackageReadError::from_io_error)?;
-        let serial: serial::Manifest = serde_json::de::from_reader(file).unknown()?;
-        serial.into_manifest()
+        let serial: serial::Manifest =
+            serde_json::from_str(&src).with_context(FileParseError::for_json(&path))?;
+        // Only the versions in the `toolchain` section can be invalid.
+        serial.into_manifest().map_err(|error| {
+            let toolchain = edit::locate_key(&src, "toolchain");
+            let location = FileLocation::at_offset(&path, &src, toolchain);
+            InvalidFileValueError::new("toolchain".to_string(), location, &error).into()
+        })
     }
 
     /// Returns a reference to the platform image specified by manifest, if any.
@@ -56,62 +170,64 @@ impl Manifest {
         self.platform_image.as_ref().map(|p| p.clone())
     }
 
-    /// Returns the pinned version of Node as a Version, if any.
-    pub fn node(&self) -> Option<Version> {
-        self.platform().map(|t| t.node.clone())
-    }
-
-    /// Returns the pinned verison of Node as a String, if any.
-    pub fn node_str(&self) -> Option<String> {
-        self.platform().map(|t| t.node_str.clone())
+    /// Returns the version range of a tool required by the `engines` section, if any.
+    pub fn engine(&self, tool: &str) -> Option<&EngineRange> {
+        self.engines.get(tool)
     }
 
-    /// Returns the pinned verison of Yarn as a Version, if any.
-    pub fn yarn(&self) -> Option<Version> {
-        self.platform()
-            .map(|t| t.yarn.clone())
-            .unwrap_or(None)
+    /// Returns the pinned version of Node, if any.
+    pub fn node(&self) -> Option<Version> {
+        self.platform().map(|t| t.node().clone())
     }
 
-    /// Returns the pinned verison of Yarn as a String, if any.
-    pub fn yarn_str(&self) -> Option<String> {
-        self.platform()
-            .map(|t| t.yarn_str.clone())
-            .unwrap_or(None)
+    /// Returns the pinned version of a tool, if any.
+    pub fn pinned<D: Distro>(&self) -> Option<Version> {
+        self.platform().and_then(|t| t.pinned::<D>().cloned())
     }
 
     /// Writes the input ToolchainManifest to package.json, adding the "toolchain" key if
-    /// necessary.
+    /// necessary. Only the "toolchain" value is rewritten; the rest of the file keeps its
+    /// key order and formatting.
     pub fn update_toolchain(
         toolchain: serial::Image,
         package_file: PathBuf,
     ) -> Fallible<()> {
-        // parse the entire package.json file into a Value
-        let file = File::open(&package_file).unknown()?;
-        let mut v: serde_json::Value = serde_json::from_reader(file).unknown()?;
+        let contents = read_file(&package_file)?;
 
         // detect indentation in package.json
-        let mut contents = String::new();
-        let mut indent_file = File::open(&package_file).unknown()?;
-        indent_file.read_to_string(&mut contents).unknown()?;
         let indent = detect_indent::detect_indent(&contents);
 
-        if let Some(map) = v.as_object_mut() {
-            // update the "toolchain" key
-            let toolchain_value = serde_json::to_value(toolchain).unknown()?;
-            map.insert("toolchain".to_string(), toolchain_value);
-
-            // serialize the updated contents back to package.json
-            let file = File::create(package_file).unknown()?;
-            let formatter =
-                serde_json::ser::PrettyFormatter::with_indent(indent.indent().as_bytes());
-            let mut ser = serde_json::Serializer::with_formatter(file, formatter);
-            map.serialize(&mut ser).unknown()?;
+        let updated = edit::set_key(&contents, "toolchain", &toolchain, indent.indent())?;
+        write_file(&package_file, &updated)
+    }
+
+    /// Removes the "toolchain" key from package.json, if it's there, leaving the rest of
+    /// the file unchanged.
+    pub fn remove_toolchain(package_file: PathBuf) -> Fallible<()> {
+        let contents = read_file(&package_file)?;
+        if let Some(updated) = edit::remove_key(&contents, "toolchain")? {
+            write_file(&package_file, &updated)?;
         }
         Ok(())
     }
 }
 
+fn read_file(path: &Path) -> Fallible<String> {
+    let mut contents = String::new();
+    File::open(path)
+        .unknown()?
+        .read_to_string(&mut contents)
+        .unknown()?;
+    Ok(contents)
+}
+
+fn write_file(path: &Path, contents: &str) -> Fallible<()> {
+    File::create(path)
+        .unknown()?
+        .write_all(contents.as_bytes())
+        .unknown()
+}
+
 // unit tests
 
 #[cfg(test)]
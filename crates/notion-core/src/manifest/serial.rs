@@ -1,11 +1,15 @@
 use super::super::{image, manifest};
+use distro::Distro;
+use registry;
 use version::VersionSpec;
 
 use notion_fail::Fallible;
 
 use serde::de::{Deserialize, Deserializer, Error, MapAccess, Visitor};
 
-use std::collections::HashMap;
+use serde_json;
+
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::hash::Hash;
 use std::marker::PhantomData;
@@ -53,17 +57,26 @@ pub struct Manifest {
 
     pub toolchain: Option<Image>,
 
+    // older packages may use an array here, which npm ignores, so this is read loosely
+    #[serde(default)]
+    pub engines: Option<serde_json::Value>,
+
+    // either an array of globs or an object with a `packages` array of globs
+    #[serde(default)]
+    pub workspaces: Option<serde_json::Value>,
+
     // the "bin" field can be a map or a string
     // (see https://docs.npmjs.com/files/package.json#bin)
     #[serde(default)] // handles Option
     pub bin: Option<BinMap<String, String>>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Image {
     pub node: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub yarn: Option<String>,
+    // the versions of the other tools, keyed by their names in the registry
+    #[serde(flatten)]
+    pub tools: BTreeMap<String, String>,
 }
 
 impl Manifest {
@@ -82,34 +95,95 @@ impl Manifest {
         }
         Ok(manifest::Manifest {
             platform_image: self.into_image()?.map(Rc::new),
+            engines: self.into_engines(),
+            workspaces: self.into_workspaces(),
             dependencies: self.dependencies,
             dev_dependencies: self.dev_dependencies,
             bin: map,
         })
     }
 
+    /// Collects the package globs in the `workspaces` section.
+    pub fn into_workspaces(&self) -> Vec<String> {
+        let globs = match self.workspaces {
+            Some(serde_json::Value::Array(ref globs)) => globs,
+            Some(serde_json::Value::Object(ref map)) => match map.get("packages") {
+                Some(serde_json::Value::Array(ref globs)) => globs,
+                _ => {
+                    return vec![];
+                }
+            },
+            _ => {
+                return vec![];
+            }
+        };
+        globs
+            .iter()
+            .filter_map(|glob| glob.as_str().map(|glob| glob.to_string()))
+            .collect()
+    }
+
+    /// Collects the version ranges in the `engines` section, skipping any that can't be
+    /// parsed (npm treats `engines` as advisory, and so does Notion).
+    pub fn into_engines(&self) -> HashMap<String, manifest::EngineRange> {
+        let mut engines = HashMap::new();
+        if let Some(serde_json::Value::Object(ref map)) = self.engines {
+            for (name, range) in map.iter() {
+                if let Some(range) = range.as_str() {
+                    if let Ok(range) = range.parse() {
+                        engines.insert(name.clone(), range);
+                    }
+                }
+            }
+        }
+        engines
+    }
+
     pub fn into_image(&self) -> Fallible<Option<image::Image>> {
         if let Some(toolchain) = &self.toolchain {
-            return Ok(Some(image::Image {
-                node: VersionSpec::parse_version(&toolchain.node)?,
-                node_str: toolchain.node.clone(),
-                yarn: if let Some(yarn) = &toolchain.yarn {
-                    Some(VersionSpec::parse_version(&yarn)?)
-                } else {
-                    None
-                },
-                yarn_str: toolchain.yarn.clone(),
-            }));
+            let mut pinner = Pinner {
+                tools: &toolchain.tools,
+                image: image::Image::for_node(VersionSpec::parse_version(&toolchain.node)?),
+            };
+            registry::visit(&mut pinner)?;
+            return Ok(Some(pinner.image));
         }
         Ok(None)
     }
 }
 
+/// Pins the version of each tool in the registry that is listed in a `toolchain` section.
+struct Pinner<'a> {
+    tools: &'a BTreeMap<String, String>,
+    image: image::Image,
+}
+
+impl<'a> registry::Visitor for Pinner<'a> {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        if let Some(version) = self.tools.get(D::NAME) {
+            self.image.pin::<D>(VersionSpec::parse_version(version)?);
+        }
+        Ok(())
+    }
+}
+
 impl Image {
-    pub fn new(node_version: String, yarn_version: Option<String>) -> Self {
+    pub fn new(node_version: String) -> Self {
         Image {
             node: node_version,
-            yarn: yarn_version,
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'a> From<&'a image::Image> for Image {
+    fn from(image: &'a image::Image) -> Self {
+        Image {
+            node: image.node().to_string(),
+            tools: image
+                .tools()
+                .map(|(name, version)| (name.to_string(), version.to_string()))
+                .collect(),
         }
     }
 }
@@ -185,7 +259,10 @@ where
 #[cfg(test)]
 pub mod tests {
 
-    use super::{BinMap, Manifest};
+    use super::{BinMap, Image, Manifest};
+    use distro::pnpm::PnpmDistro;
+    use distro::yarn::YarnDistro;
+    use semver::Version;
     use serde_json;
     use std::collections::HashMap;
 
@@ -313,7 +390,34 @@ pub mod tests {
             .toolchain
             .expect("Did not parse toolchain correctly");
         assert_eq!(toolchain.node, "0.10.5");
-        assert_eq!(toolchain.yarn.unwrap(), "1.2.1");
+        assert_eq!(toolchain.tools["yarn"], "1.2.1");
+    }
+
+    #[test]
+    fn test_package_toolchain_image() {
+        let package_pnpm = r#"{
+            "toolchain": {
+                "node": "10.13.0",
+                "pnpm": "2.25.5"
+            }
+        }"#;
+        let manifest_pnpm: Manifest =
+            serde_json::de::from_str(package_pnpm).expect("Could not deserialize string");
+        let image = manifest_pnpm
+            .into_image()
+            .expect("Could not parse toolchain")
+            .expect("Did not parse toolchain correctly");
+        assert_eq!(image.node(), &Version::parse("10.13.0").unwrap());
+        assert_eq!(
+            image.pinned::<PnpmDistro>(),
+            Some(&Version::parse("2.25.5").unwrap())
+        );
+        assert_eq!(image.pinned::<YarnDistro>(), None);
+
+        let toolchain = Image::from(&image);
+        assert_eq!(toolchain.node, "10.13.0");
+        assert_eq!(toolchain.tools.len(), 1);
+        assert_eq!(toolchain.tools["pnpm"], "2.25.5");
     }
 
     #[test]
@@ -1,4 +1,5 @@
-use manifest::Manifest;
+use distro::yarn::YarnDistro;
+use manifest::{EngineRange, Manifest};
 use semver::Version;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -25,7 +26,7 @@ fn gets_yarn_version() {
     let project_path = fixture_path("basic");
     let version = Manifest::for_dir(&project_path)
         .expect("Could not get manifest")
-        .yarn();
+        .pinned::<YarnDistro>();
     assert_eq!(version.unwrap(), Version::parse("1.2.0").unwrap());
 }
 
@@ -67,7 +68,76 @@ fn node_for_no_toolchain() {
 fn yarn_for_no_toolchain() {
     let project_path = fixture_path("no_toolchain");
     let manifest = Manifest::for_dir(&project_path).expect("Could not get manifest");
-    assert_eq!(manifest.yarn(), None);
+    assert_eq!(manifest.pinned::<YarnDistro>(), None);
+}
+
+#[test]
+fn gets_engines() {
+    let project_path = fixture_path("no_toolchain");
+    let manifest = Manifest::for_dir(&project_path).expect("Could not get manifest");
+
+    let node = manifest.engine("node").expect("Could not get engines.node");
+    assert_eq!(node.to_string(), ">=8.9 || 6.11");
+    assert!(node.matches(&Version::parse("10.13.0").unwrap()));
+    assert!(node.matches(&Version::parse("6.11.5").unwrap()));
+    assert!(!node.matches(&Version::parse("7.10.1").unwrap()));
+
+    let yarn = manifest.engine("yarn").expect("Could not get engines.yarn");
+    assert!(yarn.matches(&Version::parse("1.12.3").unwrap()));
+    assert!(!yarn.matches(&Version::parse("1.7.0").unwrap()));
+
+    // unparseable ranges are ignored
+    assert!(manifest.engine("npm").is_none());
+}
+
+fn range(src: &str) -> EngineRange {
+    src.parse().expect("Could not parse range")
+}
+
+fn matches(range: &EngineRange, version: &str) -> bool {
+    range.matches(&Version::parse(version).unwrap())
+}
+
+#[test]
+fn parses_npm_ranges() {
+    let spaced = range(">=8.9.0 <11");
+    assert!(matches(&spaced, "8.9.0"));
+    assert!(matches(&spaced, "10.13.0"));
+    assert!(!matches(&spaced, "8.8.1"));
+    assert!(!matches(&spaced, "11.0.0"));
+
+    let spaced_op = range(">= 8.9 < 10 || ^6.11");
+    assert!(matches(&spaced_op, "9.11.2"));
+    assert!(matches(&spaced_op, "6.14.4"));
+    assert!(!matches(&spaced_op, "10.0.0"));
+
+    let hyphen = range("6.11 - 8");
+    assert!(matches(&hyphen, "6.11.0"));
+    assert!(matches(&hyphen, "8.12.0"));
+    assert!(!matches(&hyphen, "6.10.3"));
+    assert!(!matches(&hyphen, "9.0.0"));
+
+    let x_range = range("8.x || 10.*");
+    assert!(matches(&x_range, "8.0.0"));
+    assert!(matches(&x_range, "10.13.0"));
+    assert!(!matches(&x_range, "9.11.2"));
+
+    let minor_x = range("v8.9.x");
+    assert!(matches(&minor_x, "8.9.4"));
+    assert!(!matches(&minor_x, "8.10.0"));
+
+    assert!(matches(&range("*"), "11.1.0"));
+    assert!(matches(&range(">=8.x"), "11.1.0"));
+    assert!(matches(&range("8.9.4"), "8.9.4"));
+    assert!(!matches(&range("8.9.4"), "8.9.5"));
+    assert_eq!(range(" 6.11 - 8 ").to_string(), "6.11 - 8");
+}
+
+#[test]
+fn engines_for_toolchain() {
+    let project_path = fixture_path("basic");
+    let manifest = Manifest::for_dir(&project_path).expect("Could not get manifest");
+    assert!(manifest.engine("node").is_none());
 }
 
 #[test]
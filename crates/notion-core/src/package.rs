@@ -0,0 +1,80 @@
+//! Provides functions for installing packages from the npm registry, such as command-line
+//! tools, each into its own directory.
+
+use std::fs::remove_dir_all;
+use std::process::Command;
+
+use image::Image;
+use manifest::Manifest;
+use notion_fail::{ExitCode, Fallible, NotionFail, ResultExt};
+use path;
+use semver::Version;
+
+cfg_if! {
+    if #[cfg(windows)] {
+        fn npm_command() -> Command {
+            Command::new("npm.cmd")
+        }
+    } else {
+        fn npm_command() -> Command {
+            Command::new("npm")
+        }
+    }
+}
+
+/// Thrown when npm fails to install a package.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Could not install {}@{}: npm {}", name, version, status)]
+#[notion_fail(code = "ExecutionFailure")]
+pub(crate) struct PackageInstallError {
+    name: String,
+    version: String,
+    status: String,
+}
+
+/// Installs a version of a package into its own directory, using the npm of the given
+/// platform image, and returns the names of the executables the package provides.
+pub(crate) fn install(name: &str, version: &Version, image: &Image) -> Fallible<Vec<String>> {
+    let version_str = version.to_string();
+    let dir = path::package_dir(name, &version_str)?;
+
+    // Start from scratch, in case an earlier installation was interrupted.
+    if dir.is_dir() {
+        remove_dir_all(&dir).unknown()?;
+    }
+
+    let status = npm_command()
+        .arg("install")
+        .arg("--global")
+        .arg("--prefix")
+        .arg(&dir)
+        .arg(format!("{}@{}", name, version_str))
+        .env("PATH", image.path()?)
+        .status()
+        .unknown()?;
+
+    if !status.success() {
+        throw!(PackageInstallError {
+            name: name.to_string(),
+            version: version_str,
+            status: match status.code() {
+                Some(code) => format!("exited with code {}", code),
+                None => "was terminated".to_string(),
+            },
+        });
+    }
+
+    let manifest = Manifest::for_dir(&path::package_module_dir(name, &version_str)?)?;
+    let mut bins: Vec<String> = manifest.bin.keys().cloned().collect();
+    bins.sort();
+    Ok(bins)
+}
+
+/// Removes the directory of an installed version of a package, if it exists.
+pub(crate) fn remove(name: &str, version: &Version) -> Fallible<()> {
+    let dir = path::package_dir(name, &version.to_string())?;
+    if dir.is_dir() {
+        remove_dir_all(&dir).unknown()?;
+    }
+    Ok(())
+}
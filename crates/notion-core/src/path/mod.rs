@@ -27,6 +27,42 @@ pub fn node_archive_root_dir(version: &str) -> String {
     format!("node-v{}-{}-{}", version, OS, ARCH)
 }
 
+/// Extracts the version from the name of a Node archive file, if it is one.
+pub fn node_archive_version(file_name: &str) -> Option<&str> {
+    strip_affixes(
+        file_name,
+        "node-v",
+        &format!("-{}-{}.{}", OS, ARCH, archive_extension()),
+    )
+}
+
+/// Extracts the version from the name of a Yarn archive file, if it is one.
+pub fn yarn_archive_version(file_name: &str) -> Option<&str> {
+    strip_affixes(file_name, "yarn-v", &format!(".{}", archive_extension()))
+}
+
+/// Extracts the version from the name of the archive file of a package from the npm
+/// registry, if it is one.
+pub fn registry_archive_version<'a>(name: &str, file_name: &'a str) -> Option<&'a str> {
+    strip_affixes(file_name, &format!("{}-", name), ".tgz")
+}
+
+fn strip_affixes<'a>(s: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    if s.len() > prefix.len() + suffix.len() && s.starts_with(prefix) && s.ends_with(suffix) {
+        Some(&s[prefix.len()..s.len() - suffix.len()])
+    } else {
+        None
+    }
+}
+
+pub fn partial_download_file(file: &str) -> String {
+    format!("{}.partial", file)
+}
+
+pub fn node_shasums_file(version: &str) -> String {
+    format!("node-v{}-SHASUMS256.txt", version)
+}
+
 pub fn yarn_archive_file(version: &str) -> String {
     format!("{}.{}", yarn_archive_root_dir(version), archive_extension())
 }
@@ -35,6 +71,16 @@ pub fn yarn_archive_root_dir(version: &str) -> String {
     format!("yarn-v{}", version)
 }
 
+/// The npm registry serves packages as gzipped tarballs on every platform.
+pub fn registry_archive_file(name: &str, version: &str) -> String {
+    format!("{}-{}.tgz", name, version)
+}
+
+/// Packages from the npm registry unpack into a `package` directory, whatever their version.
+pub fn registry_archive_root_dir() -> String {
+    String::from("package")
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -56,6 +102,60 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_node_archive_version() {
+        assert_eq!(
+            node_archive_version(&node_archive_file("1.2.3")),
+            Some("1.2.3")
+        );
+        assert_eq!(
+            node_archive_version(&partial_download_file(&node_archive_file("1.2.3"))),
+            None
+        );
+        assert_eq!(node_archive_version("index.json"), None);
+    }
+
+    #[test]
+    fn test_yarn_archive_version() {
+        assert_eq!(
+            yarn_archive_version(&yarn_archive_file("1.2.3")),
+            Some("1.2.3")
+        );
+        assert_eq!(yarn_archive_version("yarn-v.tar.gz"), None);
+    }
+
+    #[test]
+    fn test_registry_archive_version() {
+        assert_eq!(
+            registry_archive_version("npm", &registry_archive_file("npm", "6.1.0")),
+            Some("6.1.0")
+        );
+        assert_eq!(registry_archive_version("npm", "npm-.tgz"), None);
+        assert_eq!(
+            registry_archive_version(
+                "npm",
+                &partial_download_file(&registry_archive_file("npm", "6.1.0"))
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_partial_download_file() {
+        assert_eq!(
+            partial_download_file("yarn-v1.2.3.tar.gz"),
+            "yarn-v1.2.3.tar.gz.partial".to_string()
+        );
+    }
+
+    #[test]
+    fn test_node_shasums_file() {
+        assert_eq!(
+            node_shasums_file("1.2.3"),
+            "node-v1.2.3-SHASUMS256.txt".to_string()
+        );
+    }
+
     #[test]
     fn yarn_node_archive_file() {
         assert_eq!(
@@ -68,4 +168,24 @@ pub mod tests {
     fn yarn_node_archive_root_dir() {
         assert_eq!(yarn_archive_root_dir("1.2.3"), "yarn-v1.2.3".to_string());
     }
+
+    #[test]
+    fn test_registry_archive_version_of_other_package() {
+        assert_eq!(
+            registry_archive_version("pnpm", &registry_archive_file("pnpm", "2.9.0")),
+            Some("2.9.0")
+        );
+        assert_eq!(
+            registry_archive_version("pnpm", &registry_archive_file("npm", "6.1.0")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_registry_archive_file() {
+        assert_eq!(
+            registry_archive_file("npm", "6.1.0"),
+            "npm-6.1.0.tgz".to_string()
+        );
+    }
 }
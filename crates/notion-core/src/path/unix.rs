@@ -41,20 +41,25 @@ cfg_if! {
 }
 
 // ~/
-//     .notion/
+//     .notion/                                            (or $NOTION_HOME, if set)
 //         cache/                                          cache_dir
-//             node/                                       node_cache_dir
+//             node/                                       tool_cache_dir("node")
 //                 node-dist-v4.8.4-linux-x64.tar.gz       archive_file("4.8.4")
 //                 node-dist-v6.11.3-linux-x64.tar.gz
 //                 node-dist-v8.6.0-linux-x64.tar.gz
 //                 ...
 //         versions/                                       versions_dir
-//             node/                                       node_versions_dir
-//                 4.8.4/                                  node_version_dir("4.8.4")
-//                   bin/                                  node_version_bin_dir("4.8.4")
+//             node/                                       tool_versions_dir("node")
+//                 4.8.4/                                  tool_version_dir("node", "4.8.4")
+//                   bin/                                  tool_version_bin_dir("node", "4.8.4")
 //                 6.11.3/
 //                 8.6.0/
 //                 ...
+//         packages/                                       packages_dir
+//             ember-cli/
+//                 3.1.0/                                  package_dir("ember-cli", "3.1.0")
+//                     bin/                                package_bin_dir("ember-cli", "3.1.0")
+//                     lib/node_modules/ember-cli/         package_module_dir("ember-cli", "3.1.0")
 //         bin/                                            shim_dir
 //             node                                        shim_file("node")
 //             npm
@@ -65,8 +70,19 @@ cfg_if! {
 //         launchscript                                    launchscript_file
 //         config.toml                                     user_config_file
 //         catalog.toml                                    user_catalog_file
+//         tmp/                                            tmp_dir
+//
+// /etc/
+//     notion/
+//         config.toml                                     system_config_file
 
 fn notion_home() -> Fallible<PathBuf> {
+    // this must agree with the default in shell/unix/load.sh
+    if let Some(notion_home) = env::var_os("NOTION_HOME") {
+        if !notion_home.is_empty() {
+            return Ok(PathBuf::from(notion_home));
+        }
+    }
     let home = env::home_dir().ok_or(NoHomeEnvVar)?;
     Ok(home.join(".notion"))
 }
@@ -75,19 +91,20 @@ pub fn cache_dir() -> Fallible<PathBuf> {
     Ok(notion_home()?.join("cache"))
 }
 
-pub fn node_cache_dir() -> Fallible<PathBuf> {
-    Ok(cache_dir()?.join("node"))
-}
-pub fn yarn_cache_dir() -> Fallible<PathBuf> {
-    Ok(cache_dir()?.join("yarn"))
+pub fn tool_cache_dir(tool: &str) -> Fallible<PathBuf> {
+    Ok(cache_dir()?.join(tool))
 }
 
 pub fn node_index_file() -> Fallible<PathBuf> {
-    Ok(node_cache_dir()?.join("index.json"))
+    Ok(tool_cache_dir("node")?.join("index.json"))
 }
 
 pub fn node_index_expiry_file() -> Fallible<PathBuf> {
-    Ok(node_cache_dir()?.join("index.json.expires"))
+    Ok(tool_cache_dir("node")?.join("index.json.expires"))
+}
+
+pub fn node_index_source_file() -> Fallible<PathBuf> {
+    Ok(tool_cache_dir("node")?.join("index.json.source"))
 }
 
 pub fn archive_extension() -> String {
@@ -98,33 +115,44 @@ pub fn versions_dir() -> Fallible<PathBuf> {
     Ok(notion_home()?.join("versions"))
 }
 
-pub fn node_versions_dir() -> Fallible<PathBuf> {
-    Ok(versions_dir()?.join("node"))
+pub fn tool_versions_dir(tool: &str) -> Fallible<PathBuf> {
+    Ok(versions_dir()?.join(tool))
 }
 
-pub fn yarn_versions_dir() -> Fallible<PathBuf> {
-    Ok(versions_dir()?.join("yarn"))
+pub fn tool_version_dir(tool: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(tool_versions_dir(tool)?.join(version))
 }
 
-pub fn node_version_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(node_versions_dir()?.join(version))
+pub fn tool_version_bin_dir(tool: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(tool_version_dir(tool, version)?.join("bin"))
 }
 
-pub fn yarn_version_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(yarn_versions_dir()?.join(version))
+pub fn node_version_bin_dir(version: &str) -> Fallible<PathBuf> {
+    tool_version_bin_dir("node", version)
 }
 
-pub fn node_version_bin_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(node_version_dir(version)?.join("bin"))
+// 3rd-party binaries installed globally for this node version
+pub fn node_version_3p_bin_dir(version: &str) -> Fallible<PathBuf> {
+    Ok(tool_version_dir("node", version)?.join("lib/node_modules/.bin"))
 }
 
-pub fn yarn_version_bin_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(yarn_version_dir(version)?.join("bin"))
+pub fn packages_dir() -> Fallible<PathBuf> {
+    Ok(notion_home()?.join("packages"))
 }
 
-// 3rd-party binaries installed globally for this node version
-pub fn node_version_3p_bin_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(node_version_dir(version)?.join("lib/node_modules/.bin"))
+pub fn package_dir(name: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(packages_dir()?.join(name).join(version))
+}
+
+pub fn package_bin_dir(name: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(package_dir(name, version)?.join("bin"))
+}
+
+pub fn package_module_dir(name: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(package_dir(name, version)?
+        .join("lib")
+        .join("node_modules")
+        .join(name))
 }
 
 pub fn notion_file() -> Fallible<PathBuf> {
@@ -155,6 +183,21 @@ pub fn user_catalog_file() -> Fallible<PathBuf> {
     Ok(notion_home()?.join("catalog.toml"))
 }
 
+pub fn tmp_dir() -> Fallible<PathBuf> {
+    Ok(notion_home()?.join("tmp"))
+}
+
+pub fn system_config_file() -> Fallible<PathBuf> {
+    // if this is sandboxed in CI, use the sandboxed /etc directory
+    let etc = if env::var("NOTION_SANDBOX").is_ok() {
+        let notion_data = env::var("NOTION_DATA_ROOT").unwrap();
+        PathBuf::from(notion_data).join("etc")
+    } else {
+        PathBuf::from("/etc")
+    };
+    Ok(etc.join("notion").join("config.toml"))
+}
+
 pub fn create_file_symlink(src: PathBuf, dst: PathBuf) -> Result<(), io::Error> {
     unix::fs::symlink(src, dst)
 }
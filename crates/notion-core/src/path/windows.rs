@@ -27,26 +27,50 @@ cfg_if! {
     }
 }
 
+// If NOTION_HOME is set, it replaces all three of the Notion\ directories below, except that
+// the system configuration stays in ProgramData\Notion\ since it's shared by every user.
+
 // C:\
 //     ProgramData\
 //         Notion\
 //             cache\                                  cache_dir
-//                 node\                               node_cache_dir
+//                 node\                               tool_cache_dir("node")
 //                     node-v4.8.4-win-x64.zip         archive_file("4.8.4")
 //                     node-v6.11.3-win-x64.zip
 //                     node-v8.6.0-win-x64.zip
 //                     ...
 //             versions\                               versions_dir
-//                 node\                               node_versions_dir
-//                     4.8.4\                          node_version_dir("4.8.4")
+//                 node\                               tool_versions_dir("node")
+//                     4.8.4\                          tool_version_dir("node", "4.8.4")
 //                                                     node_version_bin_dir("4.8.4")
 //                     6.11.3\
 //                     8.6.0\
 //                     ...
+//             packages\                               packages_dir
+//                 ember-cli\
+//                     3.1.0\                          package_dir("ember-cli", "3.1.0")
+//                                                     package_bin_dir("ember-cli", "3.1.0")
+//                         node_modules\ember-cli\     package_module_dir("ember-cli", "3.1.0")
 //             launchbin.exe                           launchbin_file
 //             launchscript.exe                        launchscript_file
+//             tmp\                                    tmp_dir
+//             config.toml                             system_config_file
+
+fn notion_home() -> Option<PathBuf> {
+    match env::var_os("NOTION_HOME") {
+        Some(ref notion_home) if !notion_home.is_empty() => Some(PathBuf::from(notion_home)),
+        _ => None,
+    }
+}
 
 fn program_data_root() -> Fallible<PathBuf> {
+    if let Some(notion_home) = notion_home() {
+        return Ok(notion_home);
+    }
+    shared_data_root()
+}
+
+fn shared_data_root() -> Fallible<PathBuf> {
     // if this is sandboxed in CI, use the sandboxed ProgramData directory
     if env::var("NOTION_SANDBOX").is_ok() {
         let notion_data = env::var("NOTION_DATA_ROOT").unwrap();
@@ -59,27 +83,26 @@ fn program_data_root() -> Fallible<PathBuf> {
         #[cfg(feature = "universal-docs")]
         unimplemented!()
     }
-
 }
 
 pub fn cache_dir() -> Fallible<PathBuf> {
     Ok(program_data_root()?.join("cache"))
 }
 
-pub fn node_cache_dir() -> Fallible<PathBuf> {
-    Ok(cache_dir()?.join("node"))
-}
-
-pub fn yarn_cache_dir() -> Fallible<PathBuf> {
-    Ok(cache_dir()?.join("yarn"))
+pub fn tool_cache_dir(tool: &str) -> Fallible<PathBuf> {
+    Ok(cache_dir()?.join(tool))
 }
 
 pub fn node_index_file() -> Fallible<PathBuf> {
-    Ok(node_cache_dir()?.join("index.json"))
+    Ok(tool_cache_dir("node")?.join("index.json"))
 }
 
 pub fn node_index_expiry_file() -> Fallible<PathBuf> {
-    Ok(node_cache_dir()?.join("index.json.expires"))
+    Ok(tool_cache_dir("node")?.join("index.json.expires"))
+}
+
+pub fn node_index_source_file() -> Fallible<PathBuf> {
+    Ok(tool_cache_dir("node")?.join("index.json.source"))
 }
 
 pub fn archive_extension() -> String {
@@ -90,28 +113,20 @@ pub fn versions_dir() -> Fallible<PathBuf> {
     Ok(program_data_root()?.join("versions"))
 }
 
-pub fn node_versions_dir() -> Fallible<PathBuf> {
-    Ok(versions_dir()?.join("node"))
-}
-
-pub fn yarn_versions_dir() -> Fallible<PathBuf> {
-    Ok(versions_dir()?.join("yarn"))
+pub fn tool_versions_dir(tool: &str) -> Fallible<PathBuf> {
+    Ok(versions_dir()?.join(tool))
 }
 
-pub fn node_version_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(node_versions_dir()?.join(version))
+pub fn tool_version_dir(tool: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(tool_versions_dir(tool)?.join(version))
 }
 
-pub fn yarn_version_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(yarn_versions_dir()?.join(version))
+pub fn tool_version_bin_dir(tool: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(tool_version_dir(tool, version)?.join("bin"))
 }
 
 pub fn node_version_bin_dir(version: &str) -> Fallible<PathBuf> {
-    node_version_dir(version)
-}
-
-pub fn yarn_version_bin_dir(version: &str) -> Fallible<PathBuf> {
-    Ok(yarn_version_dir(version)?.join("bin"))
+    tool_version_dir("node", version)
 }
 
 // 3rd-party binaries installed globally for this node version
@@ -120,6 +135,22 @@ pub fn node_version_3p_bin_dir(_version: &str) -> Fallible<PathBuf> {
     unimplemented!("global 3rd party executables not yet implemented for Windows")
 }
 
+pub fn packages_dir() -> Fallible<PathBuf> {
+    Ok(program_data_root()?.join("packages"))
+}
+
+pub fn package_dir(name: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(packages_dir()?.join(name).join(version))
+}
+
+pub fn package_bin_dir(name: &str, version: &str) -> Fallible<PathBuf> {
+    package_dir(name, version)
+}
+
+pub fn package_module_dir(name: &str, version: &str) -> Fallible<PathBuf> {
+    Ok(package_dir(name, version)?.join("node_modules").join(name))
+}
+
 pub fn launchbin_file() -> Fallible<PathBuf> {
     Ok(program_data_root()?.join("launchbin.exe"))
 }
@@ -128,6 +159,14 @@ pub fn launchscript_file() -> Fallible<PathBuf> {
     Ok(program_data_root()?.join("launchscript.exe"))
 }
 
+pub fn tmp_dir() -> Fallible<PathBuf> {
+    Ok(program_data_root()?.join("tmp"))
+}
+
+pub fn system_config_file() -> Fallible<PathBuf> {
+    Ok(shared_data_root()?.join("config.toml"))
+}
+
 // C:\
 //     Program Files\
 //         Notion\
@@ -139,6 +178,10 @@ pub fn launchscript_file() -> Fallible<PathBuf> {
 //                 ...
 
 fn program_files_root() -> Fallible<PathBuf> {
+    if let Some(notion_home) = notion_home() {
+        return Ok(notion_home);
+    }
+
     #[cfg(all(windows, target_arch = "x86"))]
     return Ok(winfolder::Folder::ProgramFiles.path().join("Notion"));
 
@@ -172,18 +215,16 @@ pub fn shim_file(toolname: &str) -> Fallible<PathBuf> {
 //                         catalog.toml                user_catalog_file
 
 fn local_data_root() -> Fallible<PathBuf> {
-    // if this is sandboxed in CI, use the sandboxed AppData directory
-    if env::var("NOTION_SANDBOX").is_ok() {
-        let home_dir = env::home_dir().unwrap();
-        return Ok(home_dir.join("AppData").join("Local").join("Notion"));
-    } else {
-        #[cfg(windows)]
-        return Ok(winfolder::Folder::LocalAppData.path().join("Notion"));
-
-        // "universal-docs" is built on a Unix machine, so we can't include Windows-specific libs
-        #[cfg(feature = "universal-docs")]
-        unimplemented!()
+    if let Some(notion_home) = notion_home() {
+        return Ok(notion_home);
     }
+
+    #[cfg(windows)]
+    return Ok(winfolder::Folder::LocalAppData.path().join("Notion"));
+
+    // "universal-docs" is built on a Unix machine, so we can't include Windows-specific libs
+    #[cfg(feature = "universal-docs")]
+    unimplemented!()
 }
 
 pub fn user_config_file() -> Fallible<PathBuf> {
@@ -2,12 +2,13 @@
 
 use std::ffi::OsString;
 use std::io::Read;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
 
-use distro::Distro;
+use distro::Provision;
 
 use cmdline_words_parser::StrExt;
-use notion_fail::{FailExt, Fallible, ResultExt};
+use notion_fail::{ExitCode, FailExt, Fallible, NotionError, NotionFail, ResultExt};
+use reqwest;
 use semver::Version;
 use serde_json;
 use version::VersionSpec;
@@ -18,7 +19,8 @@ pub(crate) mod serial;
 #[derive(PartialEq, Debug)]
 pub enum ResolvePlugin {
     /// Resolves a Tool version by sending it to a URL and receiving the
-    /// resolution in the response.
+    /// resolution in the response. The requested version is sent as the
+    /// JSON body `{ "version": "<spec>" }` of a POST request.
     Url(String),
 
     /// Resolves a Tool version by passing it to an executable and
@@ -32,44 +34,151 @@ pub struct InvalidCommandError {
     command: String,
 }
 
+/// Thrown when a request to a plugin URL could not be completed.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Could not reach plugin at {}\n{}", url, error)]
+#[notion_fail(code = "NetworkError")]
+pub(crate) struct PluginRequestError {
+    url: String,
+    error: String,
+}
+
+impl PluginRequestError {
+    pub(crate) fn for_url(url: &str) -> impl FnOnce(&reqwest::Error) -> PluginRequestError {
+        let url = url.to_string();
+        move |error| PluginRequestError {
+            url,
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Thrown when a plugin URL responds with an unsuccessful HTTP status.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Plugin at {} responded with HTTP status {}", url, status)]
+#[notion_fail(code = "NetworkError")]
+pub(crate) struct PluginStatusError {
+    url: String,
+    status: String,
+}
+
+/// Thrown when a plugin produces a response that can't be parsed.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Invalid response from plugin {}\n{}", plugin, error)]
+#[notion_fail(code = "NetworkError")]
+pub(crate) struct InvalidResponseError {
+    plugin: String,
+    error: String,
+}
+
+impl InvalidResponseError {
+    pub(crate) fn for_plugin(plugin: &str) -> impl FnOnce(&NotionError) -> InvalidResponseError {
+        let plugin = plugin.to_string();
+        move |error| InvalidResponseError {
+            plugin,
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Thrown when a bin plugin exits unsuccessfully.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Plugin '{}' failed ({})", plugin, status)]
+#[notion_fail(code = "ExecutionFailure")]
+pub(crate) struct PluginExitError {
+    plugin: String,
+    status: String,
+}
+
+#[derive(Fail, Debug)]
+#[fail(display = "Plugin did not produce a response")]
+struct EmptyResponseError;
+
+/// Thrown when a URL plugin responds with a stream, which only bin plugins can produce.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Plugin at {} responded with a stream, which is only supported for 'bin' plugins",
+       url)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct UrlStreamError {
+    url: String,
+}
+
 impl ResolvePlugin {
     /// Performs resolution of a Tool version based on the given semantic
     /// versioning requirements.
-    pub fn resolve<D: Distro>(&self, _matching: &VersionSpec) -> Fallible<D> {
+    pub fn resolve<D: Provision>(&self, matching: &VersionSpec) -> Fallible<D> {
         match self {
-            &ResolvePlugin::Url(_) => unimplemented!(),
+            &ResolvePlugin::Url(ref url) => {
+                let request = serial::ResolveRequest::new(matching);
+                let mut response = reqwest::Client::new()
+                    .post(url.as_str())
+                    .json(&request)
+                    .send()
+                    .with_context(PluginRequestError::for_url(url))?;
+
+                if !response.status().is_success() {
+                    throw!(PluginStatusError {
+                        url: url.clone(),
+                        status: response.status().to_string(),
+                    });
+                }
+
+                let response = ResolveResponse::from_reader(&mut response)
+                    .with_context(InvalidResponseError::for_plugin(url))?;
+                match response {
+                    ResolveResponse::Url { version, url } => D::remote(version, &url),
+                    ResolveResponse::Stream { .. } => throw!(UrlStreamError { url: url.clone() }),
+                }
+            }
 
             &ResolvePlugin::Bin(ref bin) => {
-                let mut trimmed = bin.trim().to_string();
-                let mut words = trimmed.parse_cmdline_words();
-                let cmd = if let Some(word) = words.next() {
-                    word
-                } else {
-                    throw!(
-                        InvalidCommandError {
-                            command: String::from(bin.trim()),
-                        }.unknown()
-                    );
-                };
-                let args: Vec<OsString> = words
-                    .map(|s| {
-                        let mut os = OsString::new();
-                        os.push(s);
-                        os
-                    })
-                    .collect();
-                let child = Command::new(cmd)
-                    .args(&args)
+                let mut child = plugin_command(bin)?
                     .stdin(Stdio::null())
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
                     .spawn()
                     .unknown()?;
-                let response = ResolveResponse::from_reader(child.stdout.unwrap())?;
+                let response = match ResolveResponse::from_reader(child.stdout.take().unwrap())
+                    .with_context(InvalidResponseError::for_plugin(bin))
+                {
+                    Ok(response) => response,
+                    Err(error) => {
+                        stop_plugin(&mut child);
+                        return Err(error);
+                    }
+                };
+
                 match response {
-                    ResolveResponse::Url { version, url } => D::remote(version, &url),
-                    ResolveResponse::Stream { version: _version } => {
-                        unimplemented!("bin plugin produced a stream")
+                    ResolveResponse::Url { version, url } => {
+                        // The plugin could block writing to a full stderr pipe, so its
+                        // diagnostics are read through before waiting for it to exit.
+                        let mut diagnostics = Vec::new();
+                        let read = child.stderr.take().unwrap().read_to_end(&mut diagnostics);
+                        eprint!("{}", String::from_utf8_lossy(&diagnostics));
+                        if let Err(error) = read {
+                            stop_plugin(&mut child);
+                            return Err(error).unknown();
+                        }
+
+                        wait_for_plugin(bin, &mut child)?;
+                        D::remote(version, &url)
+                    }
+                    ResolveResponse::Stream { version } => {
+                        let distro = match D::stream(version, child.stderr.take().unwrap()) {
+                            Ok(distro) => distro,
+                            Err(error) => {
+                                stop_plugin(&mut child);
+                                return Err(error);
+                            }
+                        };
+
+                        // A streamed archive is only complete if the plugin succeeded.
+                        if let Err(error) = wait_for_plugin(bin, &mut child) {
+                            distro.discard();
+                            return Err(error);
+                        }
+
+                        Ok(distro)
                     }
                 }
             }
@@ -77,7 +186,26 @@ impl ResolvePlugin {
     }
 }
 
-/// A response from the Node version resolution plugin.
+/// Waits for a bin plugin to exit, failing if it exited unsuccessfully.
+fn wait_for_plugin(bin: &str, child: &mut Child) -> Fallible<()> {
+    let status = child.wait().unknown()?;
+    if !status.success() {
+        throw!(PluginExitError {
+            plugin: bin.to_string(),
+            status: status.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Stops a bin plugin whose response is abandoned, reaping its process.
+fn stop_plugin(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// A response from the Node version resolution plugin, which is read from the
+/// stdout stream of a bin plugin or from the body of a URL plugin's HTTP response.
 #[derive(Debug)]
 pub enum ResolveResponse {
     /// A plugin response indicating that the Node installer for the resolved version
@@ -85,14 +213,23 @@ pub enum ResolveResponse {
     Url { version: Version, url: String },
 
     /// A plugin response indicating that the Node installer for the resolved version
-    /// is being delivered via the stderr stream of the plugin process.
+    /// is being delivered via the stderr stream of the plugin process. The plugin
+    /// must write this response to stdout before it starts writing the installer.
     Stream { version: Version },
 }
 
 impl ResolveResponse {
     /// Reads and parses a response from a Node version resolution plugin.
     pub fn from_reader<R: Read>(reader: R) -> Fallible<Self> {
-        let serial: serial::ResolveResponse = serde_json::from_reader(reader).unknown()?;
+        // Only the first JSON value is read, so a streaming plugin doesn't have to
+        // close its stdout before it delivers the installer over stderr.
+        let serial: serial::ResolveResponse = match serde_json::Deserializer::from_reader(reader)
+            .into_iter()
+            .next()
+        {
+            Some(result) => result.unknown()?,
+            None => throw!(EmptyResponseError.unknown()),
+        };
         Ok(serial.into_resolve_response()?)
     }
 }
@@ -100,10 +237,92 @@ impl ResolveResponse {
 /// A plugin listing the available versions of Node.
 #[derive(PartialEq, Debug)]
 pub enum LsRemote {
+    /// Lists the available versions by sending a GET request to a URL, which
+    /// responds with a JSON array of version strings.
     Url(String),
+
+    /// Lists the available versions by running an executable, which writes a
+    /// JSON array of version strings to its stdout stream.
     Bin(String),
 }
 
+impl LsRemote {
+    /// Produces the list of versions available from the plugin.
+    pub fn list(&self) -> Fallible<Vec<Version>> {
+        let response = match self {
+            &LsRemote::Url(ref url) => {
+                let mut response =
+                    reqwest::get(url.as_str()).with_context(PluginRequestError::for_url(url))?;
+
+                if !response.status().is_success() {
+                    throw!(PluginStatusError {
+                        url: url.clone(),
+                        status: response.status().to_string(),
+                    });
+                }
+
+                serial::LsRemoteResponse::from_reader(&mut response)
+                    .with_context(InvalidResponseError::for_plugin(url))?
+            }
+
+            &LsRemote::Bin(ref bin) => {
+                let output = plugin_command(bin)?
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::inherit())
+                    .output()
+                    .unknown()?;
+
+                if !output.status.success() {
+                    throw!(PluginExitError {
+                        plugin: bin.clone(),
+                        status: output.status.to_string(),
+                    });
+                }
+
+                serial::LsRemoteResponse::from_reader(&output.stdout[..])
+                    .with_context(InvalidResponseError::for_plugin(bin))?
+            }
+        };
+
+        response
+            .into_versions()
+            .with_context(InvalidResponseError::for_plugin(self.source()))
+    }
+
+    fn source(&self) -> &str {
+        match self {
+            &LsRemote::Url(ref url) => url,
+            &LsRemote::Bin(ref bin) => bin,
+        }
+    }
+}
+
+/// Builds the command for invoking a bin plugin, splitting its configured
+/// command line into the executable and its arguments.
+fn plugin_command(bin: &str) -> Fallible<Command> {
+    let mut trimmed = bin.trim().to_string();
+    let mut words = trimmed.parse_cmdline_words();
+    let cmd = if let Some(word) = words.next() {
+        word
+    } else {
+        throw!(
+            InvalidCommandError {
+                command: String::from(bin.trim()),
+            }.unknown()
+        );
+    };
+    let args: Vec<OsString> = words
+        .map(|s| {
+            let mut os = OsString::new();
+            os.push(s);
+            os
+        })
+        .collect();
+    let mut command = Command::new(cmd);
+    command.args(&args);
+    Ok(command)
+}
+
 /// A plugin for publishing Notion events.
 #[derive(PartialEq, Debug)]
 pub enum Publish {
@@ -113,3 +332,193 @@ pub enum Publish {
     /// Reports an event by forking a process and sending the event by IPC.
     Bin(String),
 }
+
+#[cfg(all(test, feature = "mock-network"))]
+pub mod tests {
+
+    use distro::Provision;
+    use mockito::{self, mock};
+    use notion_fail::{ExitCode, Fallible, ResultExt};
+    use plugin::{LsRemote, ResolvePlugin};
+    use semver::Version;
+    use std::cell::RefCell;
+    use std::io::Read;
+    #[cfg(unix)]
+    use std::path::PathBuf;
+    use version::VersionSpec;
+
+    /// A distro that records how it was provisioned instead of downloading anything.
+    struct RecordedDistro {
+        version: Version,
+        url: String,
+        data: String,
+    }
+
+    thread_local! {
+        /// The data of the streamed distros that have been discarded on this thread.
+        static DISCARDED: RefCell<Vec<String>> = RefCell::new(vec![]);
+    }
+
+    impl Provision for RecordedDistro {
+        fn remote(version: Version, url: &str) -> Fallible<Self> {
+            Ok(RecordedDistro {
+                version,
+                url: url.to_string(),
+                data: String::new(),
+            })
+        }
+
+        fn stream<R: Read>(version: Version, mut source: R) -> Fallible<Self> {
+            let mut data = String::new();
+            source.read_to_string(&mut data).unknown()?;
+            Ok(RecordedDistro {
+                version,
+                url: String::new(),
+                data,
+            })
+        }
+
+        fn version(&self) -> &Version {
+            &self.version
+        }
+
+        fn discard(self) {
+            DISCARDED.with(|discarded| discarded.borrow_mut().push(self.data));
+        }
+    }
+
+    fn plugin_url(path: &str) -> ResolvePlugin {
+        ResolvePlugin::Url(format!("{}{}", mockito::SERVER_URL, path))
+    }
+
+    #[cfg(unix)]
+    fn stream_plugin(status: i32) -> ResolvePlugin {
+        let mut script = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        script.push("fixtures");
+        script.push("plugins");
+        script.push("stream.sh");
+        ResolvePlugin::Bin(format!("sh {} {}", script.display(), status))
+    }
+
+    #[cfg(unix)]
+    fn url_plugin(status: i32) -> ResolvePlugin {
+        let mut script = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        script.push("fixtures");
+        script.push("plugins");
+        script.push("url.sh");
+        ResolvePlugin::Bin(format!("sh {} {}", script.display(), status))
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let _mock = mock("POST", "/resolve-url")
+            .match_header("content-type", "application/json")
+            .with_status(200)
+            .with_body(r#"{ "version": "8.9.4", "url": "https://example.com/node-v8.9.4.tar.gz" }"#)
+            .create();
+
+        let distro: RecordedDistro = plugin_url("/resolve-url")
+            .resolve(&VersionSpec::parse("8").unwrap())
+            .expect("Could not resolve version from URL plugin");
+
+        assert_eq!(distro.version, Version::parse("8.9.4").unwrap());
+        assert_eq!(distro.url, "https://example.com/node-v8.9.4.tar.gz");
+    }
+
+    #[test]
+    fn test_resolve_url_http_error() {
+        let _mock = mock("POST", "/resolve-http-error")
+            .with_status(500)
+            .create();
+
+        let error = plugin_url("/resolve-http-error")
+            .resolve::<RecordedDistro>(&VersionSpec::Latest)
+            .err()
+            .expect("Expected an HTTP error");
+
+        assert!(error.is_user_friendly());
+        assert_eq!(error.exit_code() as i32, ExitCode::NetworkError as i32);
+    }
+
+    #[test]
+    fn test_resolve_url_invalid_response() {
+        let _mock = mock("POST", "/resolve-invalid")
+            .with_status(200)
+            .with_body(r#"{ "version": "8.9.4" }"#)
+            .create();
+
+        let error = plugin_url("/resolve-invalid")
+            .resolve::<RecordedDistro>(&VersionSpec::Latest)
+            .err()
+            .expect("Expected an invalid response error");
+
+        assert_eq!(error.exit_code() as i32, ExitCode::NetworkError as i32);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_stream() {
+        let distro: RecordedDistro = stream_plugin(0)
+            .resolve(&VersionSpec::Latest)
+            .expect("Could not resolve version from streaming plugin");
+
+        assert_eq!(distro.version, Version::parse("8.9.4").unwrap());
+        assert_eq!(distro.data, "archive data");
+        DISCARDED.with(|discarded| assert!(discarded.borrow().is_empty()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_stream_plugin_failure() {
+        let error = stream_plugin(1)
+            .resolve::<RecordedDistro>(&VersionSpec::Latest)
+            .err()
+            .expect("Expected a plugin failure");
+
+        assert_eq!(error.exit_code() as i32, ExitCode::ExecutionFailure as i32);
+        DISCARDED.with(|discarded| assert_eq!(*discarded.borrow(), vec!["archive data"]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_bin_url_with_diagnostics() {
+        let distro: RecordedDistro = url_plugin(0)
+            .resolve(&VersionSpec::Latest)
+            .expect("Could not resolve version from bin plugin");
+
+        assert_eq!(distro.version, Version::parse("8.9.4").unwrap());
+        assert_eq!(distro.url, "https://example.com/node-v8.9.4.tar.gz");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_bin_url_plugin_failure() {
+        let error = url_plugin(1)
+            .resolve::<RecordedDistro>(&VersionSpec::Latest)
+            .err()
+            .expect("Expected a plugin failure");
+
+        assert_eq!(error.exit_code() as i32, ExitCode::ExecutionFailure as i32);
+    }
+
+    #[test]
+    fn test_ls_remote_url() {
+        let _mock = mock("GET", "/ls-remote")
+            .with_status(200)
+            .with_body(r#"["v8.9.4", "10.1.0", "9.11.2"]"#)
+            .create();
+
+        let versions = LsRemote::Url(format!("{}/ls-remote", mockito::SERVER_URL))
+            .list()
+            .expect("Could not list versions from URL plugin");
+
+        assert_eq!(
+            versions,
+            vec![
+                Version::parse("8.9.4").unwrap(),
+                Version::parse("10.1.0").unwrap(),
+                Version::parse("9.11.2").unwrap(),
+            ]
+        );
+    }
+}
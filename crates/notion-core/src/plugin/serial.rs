@@ -1,7 +1,11 @@
 use super::super::plugin;
 
-use notion_fail::{FailExt, Fallible, ResultExt};
+use std::io::Read;
+
+use notion_fail::{ExitCode, FailExt, Fallible, NotionFail, ResultExt};
 use semver::Version;
+use serde_json;
+use version::VersionSpec;
 
 #[derive(Serialize, Deserialize)]
 pub struct Plugin {
@@ -9,12 +13,14 @@ pub struct Plugin {
     bin: Option<String>,
 }
 
-#[derive(Fail, Debug)]
+#[derive(Debug, Fail, NotionFail)]
 #[fail(display = "Plugin contains both 'url' and 'bin' fields")]
+#[notion_fail(code = "ConfigurationError")]
 struct BothUrlAndBin;
 
-#[derive(Fail, Debug)]
+#[derive(Debug, Fail, NotionFail)]
 #[fail(display = "Plugin must contain either a 'url' or 'bin' field")]
+#[notion_fail(code = "ConfigurationError")]
 struct NeitherUrlNorBin;
 
 impl Plugin {
@@ -27,7 +33,7 @@ impl Plugin {
             Plugin {
                 url: Some(_),
                 bin: Some(_),
-            } => Err(BothUrlAndBin.unknown()),
+            } => Err(BothUrlAndBin.into()),
             Plugin {
                 url: Some(url),
                 bin: None,
@@ -39,7 +45,7 @@ impl Plugin {
             Plugin {
                 url: None,
                 bin: None,
-            } => Err(NeitherUrlNorBin.unknown()),
+            } => Err(NeitherUrlNorBin.into()),
         }
     }
 
@@ -56,6 +62,19 @@ impl Plugin {
     }
 }
 
+#[derive(Serialize)]
+pub struct ResolveRequest {
+    version: String,
+}
+
+impl ResolveRequest {
+    pub fn new(matching: &VersionSpec) -> Self {
+        ResolveRequest {
+            version: matching.to_string(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ResolveResponse {
     version: String,
@@ -111,3 +130,27 @@ impl ResolveResponse {
         }
     }
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct LsRemoteResponse(Vec<String>);
+
+impl LsRemoteResponse {
+    pub fn from_reader<R: Read>(reader: R) -> Fallible<Self> {
+        serde_json::from_reader(reader).unknown()
+    }
+
+    pub fn into_versions(self) -> Fallible<Vec<Version>> {
+        self.0
+            .into_iter()
+            .map(|version| {
+                let version = version.trim();
+                let version = if version.starts_with('v') {
+                    &version[1..]
+                } else {
+                    version
+                };
+                Version::parse(version).unknown()
+            })
+            .collect()
+    }
+}
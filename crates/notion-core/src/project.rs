@@ -4,16 +4,28 @@
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::OsStr;
+use std::fs::read_to_string;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use lazycell::LazyCell;
 
+use distro::node::NodeDistro;
+use distro::Distro;
 use image::Image;
 use manifest::Manifest;
 use manifest::serial;
 use notion_fail::{ExitCode, Fallible, NotionError, NotionFail, ResultExt};
+use registry;
 use semver::Version;
+use version::VersionSpec;
+
+/// The files, besides `package.json`, that can specify a project's Node version, in order
+/// of precedence.
+const VERSION_FILES: [&'static str; 2] = [".nvmrc", ".node-version"];
+
+/// The file holding a project's Notion settings.
+const CONFIG_FILE: &'static str = ".notionrc.toml";
 
 fn is_node_root(dir: &Path) -> bool {
     dir.join("package.json").is_file()
@@ -31,6 +43,49 @@ fn is_project_root(dir: &Path) -> bool {
     is_node_root(dir) && !is_dependency(dir)
 }
 
+/// Tests whether a workspace glob from a `workspaces` section, such as `packages/*`, matches
+/// the path of a package relative to the workspace root.
+fn matches_workspace(glob: &str, package: &Path) -> bool {
+    let glob: Vec<&str> = glob
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+    let package: Vec<String> = package
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    matches_segments(&glob, &package)
+}
+
+fn matches_segments(glob: &[&str], path: &[String]) -> bool {
+    match glob.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..path.len() + 1).any(|i| matches_segments(rest, &path[i..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                matches_wildcard(segment, name) && matches_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Tests whether a path segment matches a glob segment, in which `*` matches any characters.
+fn matches_wildcard(glob: &str, name: &str) -> bool {
+    match glob.find('*') {
+        None => glob == name,
+        Some(index) => {
+            let (prefix, rest) = (&glob[..index], &glob[index + 1..]);
+            if !name.starts_with(prefix) {
+                return false;
+            }
+            (prefix.len()..name.len() + 1)
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| matches_wildcard(rest, &name[i..]))
+        }
+    }
+}
+
 pub struct LazyDependentBins {
     bins: LazyCell<HashMap<String, String>>,
 }
@@ -65,7 +120,7 @@ impl DepPackageReadError {
     }
 }
 
-/// Thrown when a user tries to pin a Yarn version before pinning a Node version.
+/// Thrown when a user tries to pin a package manager version before pinning a Node version.
 #[derive(Debug, Fail, NotionFail)]
 #[fail(display = "There is no pinned node version for this project")]
 #[notion_fail(code = "ConfigurationError")]
@@ -77,10 +132,144 @@ impl NoPinnedNodeVersion {
     }
 }
 
+/// Thrown when a user tries to unpin a tool that isn't pinned.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "{} is not pinned in this project's toolchain", tool)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct NotPinnedError {
+    tool: String,
+}
+
+/// Thrown when a user tries to unpin Node while other tools are still pinned.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Cannot unpin node while {} pinned in this project's toolchain
+Unpin them first with `notion unpin <tool>`", tools)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct OtherToolsPinnedError {
+    tools: String,
+}
+
+/// Thrown when a user tries to unpin Node in a workspace package that inherits its toolchain
+/// from the workspace root.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Cannot unpin node in a package that inherits the toolchain of {}
+Unpin it in the workspace root instead", file)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct InheritedToolchainError {
+    file: String,
+}
+
+/// Thrown when a version file doesn't contain a valid Node version.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Invalid Node version in {}: {}", file, error)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct VersionFileParseError {
+    file: String,
+    error: String,
+}
+
+/// A file in the project root that specifies the project's Node version, such as `.nvmrc`.
+pub struct VersionFile {
+    path: PathBuf,
+    contents: String,
+}
+
+impl VersionFile {
+    /// Reads the first version file found in the specified directory, if any.
+    fn for_dir(dir: &Path) -> Fallible<Option<VersionFile>> {
+        for name in VERSION_FILES.iter() {
+            let path = dir.join(name);
+            if path.is_file() {
+                let contents = read_to_string(&path).unknown()?;
+                return Ok(Some(VersionFile { path, contents }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the path to this file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the name of this file, such as `.nvmrc`.
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Parses the version specified by this file. Besides the usual version specs, this
+    /// accepts nvm's `node` and `stable` aliases for the latest release.
+    pub fn spec(&self) -> Fallible<VersionSpec> {
+        let src = self.contents.lines().next().unwrap_or("").trim();
+        match src {
+            "node" | "stable" => Ok(VersionSpec::Latest),
+            src => match VersionSpec::parse(src) {
+                Ok(spec) => Ok(spec),
+                Err(error) => throw!(VersionFileParseError {
+                    file: self.name(),
+                    error: error.to_string(),
+                }),
+            },
+        }
+    }
+}
+
+/// The root of a Yarn workspace that contains a project.
+struct Workspace {
+    manifest: Manifest,
+    root: PathBuf,
+}
+
+impl Workspace {
+    /// Returns the workspace containing the project rooted at the specified directory, if
+    /// any. This is the nearest ancestor project that declares `workspaces`, provided one of
+    /// its globs matches the project.
+    ///
+    /// Like Yarn, this looks past ancestor projects that don't declare `workspaces`, since a
+    /// workspace package may well be nested in a directory that has a `package.json` of its
+    /// own (e.g. for tooling). An ancestor `package.json` that can't be read or parsed is
+    /// taken to be such a project rather than failing every command run in its subtree.
+    fn for_project(project_root: &Path) -> Fallible<Option<Workspace>> {
+        let mut ancestor = project_root.parent();
+        while let Some(dir) = ancestor {
+            if is_project_root(dir) {
+                let manifest = match Manifest::for_dir(dir) {
+                    Ok(manifest) => manifest,
+                    Err(_) => {
+                        ancestor = dir.parent();
+                        continue;
+                    }
+                };
+                if !manifest.workspaces.is_empty() {
+                    let package = project_root.strip_prefix(dir).unknown()?;
+                    if manifest
+                        .workspaces
+                        .iter()
+                        .any(|glob| matches_workspace(glob, package))
+                    {
+                        return Ok(Some(Workspace {
+                            manifest,
+                            root: dir.to_path_buf(),
+                        }));
+                    }
+                    return Ok(None);
+                }
+            }
+            ancestor = dir.parent();
+        }
+        Ok(None)
+    }
+}
+
 /// A Node project tree in the filesystem.
 pub struct Project {
     manifest: Manifest,
     project_root: PathBuf,
+    workspace: Option<Workspace>,
+    version_file: LazyCell<Option<VersionFile>>,
     dependent_bins: LazyDependentBins,
 }
 
@@ -92,7 +281,8 @@ impl Project {
         Self::for_dir(&current_dir)
     }
 
-    /// Returns the Node project for the input directory, if any.
+    /// Returns the Node project for the input directory, if any. The manifest of the Yarn
+    /// workspace containing the project, if any, is read along with the project's own.
     pub fn for_dir(base_dir: &Path) -> Fallible<Option<Project>> {
         let mut dir = base_dir.clone();
         while !is_project_root(dir) {
@@ -107,18 +297,41 @@ impl Project {
         Ok(Some(Project {
             manifest: Manifest::for_dir(&dir)?,
             project_root: PathBuf::from(dir),
+            workspace: Workspace::for_project(&dir)?,
+            version_file: LazyCell::new(),
             dependent_bins: LazyDependentBins::new(),
         }))
     }
 
-    /// Returns the pinned platform image, if any.
+    /// Returns the pinned platform image, if any. A project in a Yarn workspace that has no
+    /// toolchain of its own inherits the toolchain of the workspace root.
     pub fn platform(&self) -> Option<Rc<Image>> {
-        self.manifest.platform()
+        self.manifest.platform().or_else(|| {
+            self.workspace
+                .as_ref()
+                .and_then(|workspace| workspace.manifest.platform())
+        })
     }
 
-    /// Returns true if the project manifest contains a toolchain.
+    /// Returns true if the project manifest, or that of its workspace root, contains a
+    /// toolchain.
     pub fn is_pinned(&self) -> bool {
-        self.manifest.platform().is_some()
+        self.platform().is_some()
+    }
+
+    /// Returns the root directory of the Yarn workspace containing this project, if any.
+    pub fn workspace_root(&self) -> Option<&Path> {
+        self.workspace
+            .as_ref()
+            .map(|workspace| workspace.root.as_path())
+    }
+
+    /// Returns the project's `.nvmrc` or `.node-version` file, if any. It is only read the
+    /// first time it is needed.
+    pub fn version_file(&self) -> Fallible<Option<&VersionFile>> {
+        self.version_file
+            .try_borrow_with(|| VersionFile::for_dir(&self.project_root))
+            .map(|version_file| version_file.as_ref())
     }
 
     /// Returns the project manifest (`package.json`) for this project.
@@ -131,12 +344,56 @@ impl Project {
         self.project_root.join("package.json")
     }
 
+    /// Returns the path to the `.notionrc.toml` file holding this project's Notion settings.
+    /// This is the project's own unless it's in a Yarn workspace and has none of its own,
+    /// while the workspace root does.
+    pub fn config_file(&self) -> PathBuf {
+        let file = self.project_root.join(CONFIG_FILE);
+        match self.workspace {
+            Some(ref workspace) if !file.is_file() => {
+                let root_file = workspace.root.join(CONFIG_FILE);
+                if root_file.is_file() {
+                    root_file
+                } else {
+                    file
+                }
+            }
+            _ => file,
+        }
+    }
+
     /// Returns the path to the local binary directory for this project.
     pub fn local_bin_dir(&self) -> PathBuf {
         let sub_dir: PathBuf = ["node_modules", ".bin"].iter().collect();
         self.project_root.join(sub_dir)
     }
 
+    /// Returns the path to a binary of a direct dependency. In a Yarn workspace, this may be
+    /// in the `node_modules/.bin` directory at the workspace root, where dependencies are
+    /// hoisted.
+    pub fn local_bin_file(&self, bin_name: &OsStr) -> PathBuf {
+        let local = self.local_bin_dir().join(bin_name);
+        match self.workspace {
+            Some(ref workspace) if !local.exists() => {
+                let sub_dir: PathBuf = ["node_modules", ".bin"].iter().collect();
+                workspace.root.join(sub_dir).join(bin_name)
+            }
+            _ => local,
+        }
+    }
+
+    /// Returns the directory of a direct dependency. In a Yarn workspace, this may be in the
+    /// `node_modules` directory at the workspace root, where dependencies are hoisted.
+    fn dependency_dir(&self, name: &str) -> PathBuf {
+        let local = self.project_root.join("node_modules").join(name);
+        match self.workspace {
+            Some(ref workspace) if !local.is_dir() => {
+                workspace.root.join("node_modules").join(name)
+            }
+            _ => local,
+        }
+    }
+
     /// Returns true if the input binary name is a direct dependency of the input project
     pub fn has_direct_bin(&self, bin_name: &OsStr) -> Fallible<bool> {
         let dep_bins = self.dependent_bins.get(&self)?;
@@ -169,12 +426,7 @@ impl Project {
         // convert dependency names to the path to each project
         let all_dep_paths = all_deps
             .iter()
-            .map(|dep_name| {
-                let mut path_to_pkg = PathBuf::from(&self.project_root);
-                path_to_pkg.push("node_modules");
-                path_to_pkg.push(dep_name);
-                path_to_pkg
-            })
+            .map(|dep_name| self.dependency_dir(dep_name))
             .collect::<HashSet<PathBuf>>();
 
         // use those project paths to get the "bin" info for each project
@@ -189,27 +441,75 @@ impl Project {
         Ok(dependent_bins)
     }
 
-    /// Writes the specified version of Node to the `toolchain.node` key in package.json.
-    pub fn pin_node_in_toolchain(&self, node_version: Version) -> Fallible<()> {
-        // update the toolchain node version
-        let toolchain =
-            serial::Image::new(node_version.to_string(), self.manifest().yarn_str().clone());
+    /// Returns the serialized form of the current toolchain, if any.
+    fn toolchain(&self) -> Option<serial::Image> {
+        self.platform().map(|image| serial::Image::from(&*image))
+    }
+
+    /// Writes the specified version of a tool to its key in the `toolchain` section of
+    /// package.json. Tools other than Node can only be pinned once Node is, since they run on
+    /// the pinned Node. A package in a Yarn workspace that inherits the toolchain of the
+    /// workspace root gets a toolchain of its own, starting from the inherited one.
+    pub fn pin<D: Distro>(&self, version: Version) -> Fallible<()> {
+        let toolchain = match self.toolchain() {
+            Some(mut toolchain) => {
+                if D::NAME == NodeDistro::NAME {
+                    toolchain.node = version.to_string();
+                } else {
+                    toolchain.tools.insert(D::NAME.to_string(), version.to_string());
+                }
+                toolchain
+            }
+            None if D::NAME == NodeDistro::NAME => serial::Image::new(version.to_string()),
+            None => throw!(NoPinnedNodeVersion::new()),
+        };
         Manifest::update_toolchain(toolchain, self.package_file())?;
-        println!("Pinned node to version {} in package.json", node_version);
+        println!("Pinned {} to version {} in package.json", D::NAME, version);
         Ok(())
     }
 
-    /// Writes the specified version of Yarn to the `toolchain.yarn` key in package.json.
-    pub fn pin_yarn_in_toolchain(&self, yarn_version: Version) -> Fallible<()> {
-        // update the toolchain yarn version
-        if let Some(node_str) = self.manifest().node_str() {
-            let toolchain =
-                serial::Image::new(node_str.clone(), Some(yarn_version.to_string()));
-            Manifest::update_toolchain(toolchain, self.package_file())?;
-            println!("Pinned yarn to version {} in package.json", yarn_version);
+    /// Removes a tool from the `toolchain` section of package.json. Unpinning Node removes the
+    /// whole section, which this refuses to do while any other tools are pinned. Like pinning,
+    /// unpinning another tool in a package that inherits the toolchain of its workspace root
+    /// gives the package a toolchain of its own.
+    pub fn unpin<D: Distro>(&self) -> Fallible<()> {
+        let mut toolchain = match self.toolchain() {
+            Some(toolchain) => toolchain,
+            None => throw!(NotPinnedError {
+                tool: D::NAME.to_string(),
+            }),
+        };
+
+        if D::NAME == NodeDistro::NAME {
+            if let (&Some(ref workspace), None) = (&self.workspace, self.manifest.platform()) {
+                throw!(InheritedToolchainError {
+                    file: workspace.root.join("package.json").display().to_string(),
+                });
+            }
+            let others: Vec<&str> = registry::tools()
+                .iter()
+                .map(|tool| tool.name())
+                .filter(|name| toolchain.tools.contains_key(*name))
+                .collect();
+            if !others.is_empty() {
+                throw!(OtherToolsPinnedError {
+                    tools: if others.len() == 1 {
+                        format!("{} is", others[0])
+                    } else {
+                        format!("{} are", others.join(", "))
+                    },
+                });
+            }
+            Manifest::remove_toolchain(self.package_file())?;
         } else {
-            throw!(NoPinnedNodeVersion::new());
+            if toolchain.tools.remove(D::NAME).is_none() {
+                throw!(NotPinnedError {
+                    tool: D::NAME.to_string(),
+                });
+            }
+            Manifest::update_toolchain(toolchain, self.package_file())?;
         }
+        println!("Unpinned {} in package.json", D::NAME);
         Ok(())
     }
 }
@@ -221,9 +521,16 @@ pub mod tests {
     use std::collections::HashMap;
     use std::collections::HashSet;
     use std::ffi::OsStr;
-    use std::path::PathBuf;
+    use std::fs::{create_dir_all, read_to_string, write};
+    use std::path::{Path, PathBuf};
 
-    use project::Project;
+    use distro::node::NodeDistro;
+    use distro::pnpm::PnpmDistro;
+    use distro::yarn::YarnDistro;
+    use project::{matches_workspace, Project, VersionFile};
+    use semver::{Version, VersionReq};
+    use tempfile::tempdir;
+    use version::VersionSpec;
 
     fn fixture_path(fixture_dir: &str) -> PathBuf {
         let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -285,4 +592,188 @@ pub mod tests {
             .has_direct_bin(&OsStr::new("tsserver"))
             .unwrap());
     }
+
+    #[test]
+    fn workspace_inherits_toolchain() {
+        let project_path = fixture_path("workspace/packages/app");
+        let test_project = Project::for_dir(&project_path).unwrap().unwrap();
+        assert_eq!(
+            test_project.workspace_root(),
+            Some(fixture_path("workspace").as_path())
+        );
+        let platform = test_project.platform().expect("Could not get platform");
+        assert_eq!(platform.node().to_string(), "10.13.0");
+        assert_eq!(
+            platform.pinned::<YarnDistro>().map(|version| version.to_string()),
+            Some("1.12.3".to_string())
+        );
+        assert!(test_project.manifest().platform().is_none());
+    }
+
+    #[test]
+    fn workspace_package_toolchain_takes_precedence() {
+        let project_path = fixture_path("workspace/packages/pinned");
+        let test_project = Project::for_dir(&project_path).unwrap().unwrap();
+        let platform = test_project.platform().expect("Could not get platform");
+        assert_eq!(platform.node().to_string(), "8.9.4");
+    }
+
+    #[test]
+    fn workspace_hoisted_bin() {
+        let project_path = fixture_path("workspace/packages/app");
+        let test_project = Project::for_dir(&project_path).unwrap().unwrap();
+        assert!(test_project.has_direct_bin(&OsStr::new("rsvp")).unwrap());
+        assert_eq!(
+            test_project.local_bin_file(&OsStr::new("rsvp")),
+            fixture_path("workspace/node_modules/.bin/rsvp")
+        );
+    }
+
+    #[test]
+    fn workspace_skips_unreadable_ancestor() {
+        let dir = tempdir().expect("Could not create temporary directory");
+        let nested = dir.path().join("packages").join("app");
+        create_dir_all(&nested).expect("Could not create package directory");
+        write(
+            dir.path().join("package.json"),
+            r#"{ "workspaces": ["packages/**"], "toolchain": { "node": "10.13.0" } }"#,
+        ).expect("Could not write package.json");
+        write(dir.path().join("packages").join("package.json"), "{ not json")
+            .expect("Could not write package.json");
+        write(nested.join("package.json"), "{}").expect("Could not write package.json");
+
+        let project = Project::for_dir(&nested).unwrap().unwrap();
+        assert_eq!(project.workspace_root(), Some(dir.path()));
+        let platform = project.platform().expect("Could not get platform");
+        assert_eq!(platform.node().to_string(), "10.13.0");
+    }
+
+    #[test]
+    fn workspace_package_pins_its_own_toolchain() {
+        let dir = tempdir().expect("Could not create temporary directory");
+        let member = dir.path().join("packages").join("app");
+        create_dir_all(&member).expect("Could not create package directory");
+        let root_json = r#"{
+  "workspaces": ["packages/*"],
+  "toolchain": {
+    "node": "10.13.0",
+    "yarn": "1.12.3"
+  }
+}
+"#;
+        write(dir.path().join("package.json"), root_json).expect("Could not write package.json");
+        write(member.join("package.json"), "{\n  \"name\": \"app\"\n}\n")
+            .expect("Could not write package.json");
+
+        let project = Project::for_dir(&member).unwrap().unwrap();
+        assert!(project.unpin::<NodeDistro>().is_err());
+        project
+            .pin::<PnpmDistro>(Version::parse("2.25.5").unwrap())
+            .expect("Could not pin pnpm");
+
+        assert_eq!(
+            read_to_string(dir.path().join("package.json")).unwrap(),
+            root_json
+        );
+        let project = Project::for_dir(&member).unwrap().unwrap();
+        let platform = project
+            .manifest()
+            .platform()
+            .expect("Could not get the package's own platform");
+        assert_eq!(platform.node().to_string(), "10.13.0");
+        assert_eq!(
+            platform.pinned::<YarnDistro>().map(|version| version.to_string()),
+            Some("1.12.3".to_string())
+        );
+        assert_eq!(
+            platform.pinned::<PnpmDistro>().map(|version| version.to_string()),
+            Some("2.25.5".to_string())
+        );
+    }
+
+    #[test]
+    fn workspace_globs() {
+        assert!(matches_workspace("packages/*", Path::new("packages/app")));
+        assert!(matches_workspace("./packages/app-*", Path::new("packages/app-web")));
+        assert!(matches_workspace("packages/**", Path::new("packages/a/b")));
+        assert!(!matches_workspace("packages/*", Path::new("packages/a/b")));
+        assert!(!matches_workspace("packages/app-*", Path::new("packages/lib")));
+        assert!(!matches_workspace("packages/*", Path::new("tools/app")));
+    }
+
+    #[test]
+    fn unpin_checks_registry_tools() {
+        let dir = tempdir().expect("Could not create temporary directory");
+        write(
+            dir.path().join("package.json"),
+            r#"{
+  "name": "pinned",
+  "toolchain": {
+    "node": "10.13.0",
+    "pnpm": "2.25.5",
+    "yarn": "1.12.3"
+  }
+}
+"#,
+        ).expect("Could not write package.json");
+
+        let project = Project::for_dir(dir.path()).unwrap().unwrap();
+        assert!(project.unpin::<NodeDistro>().is_err());
+        project.unpin::<YarnDistro>().expect("Could not unpin yarn");
+
+        let project = Project::for_dir(dir.path()).unwrap().unwrap();
+        let platform = project.platform().expect("Could not get platform");
+        assert_eq!(platform.pinned::<YarnDistro>(), None);
+        assert!(platform.pinned::<PnpmDistro>().is_some());
+        assert!(project.unpin::<YarnDistro>().is_err());
+        project.unpin::<PnpmDistro>().expect("Could not unpin pnpm");
+
+        let project = Project::for_dir(dir.path()).unwrap().unwrap();
+        project.unpin::<NodeDistro>().expect("Could not unpin node");
+
+        let project = Project::for_dir(dir.path()).unwrap().unwrap();
+        assert!(project.platform().is_none());
+    }
+
+    fn version_file(name: &str, contents: &str) -> VersionFile {
+        VersionFile {
+            path: PathBuf::from(name),
+            contents: contents.to_string(),
+        }
+    }
+
+    #[test]
+    fn version_file_spec() {
+        match version_file(".nvmrc", "v10.13.0\n").spec().unwrap() {
+            VersionSpec::Semver(ref req) => {
+                assert_eq!(req, &VersionReq::parse("=10.13.0").unwrap())
+            }
+            spec => panic!("unexpected version spec: {}", spec),
+        }
+        match version_file(".nvmrc", "lts/*\n").spec().unwrap() {
+            VersionSpec::Lts => {}
+            spec => panic!("unexpected version spec: {}", spec),
+        }
+        match version_file(".node-version", "node").spec().unwrap() {
+            VersionSpec::Latest => {}
+            spec => panic!("unexpected version spec: {}", spec),
+        }
+        assert!(version_file(".nvmrc", "not a version").spec().is_err());
+    }
+
+    #[test]
+    fn version_file_is_read_lazily() {
+        let dir = tempdir().expect("Could not create temporary directory");
+        write(dir.path().join("package.json"), "{}\n").expect("Could not write package.json");
+        write(dir.path().join(".nvmrc"), &[0xff, 0xfe][..]).expect("Could not write .nvmrc");
+
+        // an unreadable version file only matters once it's needed
+        let project = Project::for_dir(dir.path()).unwrap().unwrap();
+        assert!(project.version_file().is_err());
+
+        write(dir.path().join(".nvmrc"), "v10.13.0\n").expect("Could not write .nvmrc");
+        let project = Project::for_dir(dir.path()).unwrap().unwrap();
+        let version_file = project.version_file().unwrap().unwrap();
+        assert_eq!(version_file.name(), ".nvmrc");
+    }
 }
@@ -0,0 +1,174 @@
+//! Provides the registry of the tools whose versions Notion manages, which lets the
+//! catalog, the configuration and the command-line interface handle every tool alike.
+
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use catalog::Installed;
+use distro::node::NodeDistro;
+use distro::npm::NpmDistro;
+use distro::pnpm::PnpmDistro;
+use distro::yarn::YarnDistro;
+use distro::Distro;
+use image::Image;
+use notion_fail::Fallible;
+use semver::Version;
+use session::Session;
+use version::VersionSpec;
+
+/// An operation on each tool in the registry, which is generic over the tool's `Distro`.
+pub(crate) trait Visitor {
+    fn visit<D: Distro>(&mut self) -> Fallible<()>;
+}
+
+/// Applies a visitor to each tool in the registry, in order.
+pub(crate) fn visit<V: Visitor>(visitor: &mut V) -> Fallible<()> {
+    visitor.visit::<NodeDistro>()?;
+    visitor.visit::<NpmDistro>()?;
+    visitor.visit::<YarnDistro>()?;
+    visitor.visit::<PnpmDistro>()?;
+    Ok(())
+}
+
+/// A tool whose versions Notion manages, for callers that dispatch on the tool's name.
+pub trait ManagedTool {
+    /// The name of the tool, as used on the command line (e.g. `node`).
+    fn name(&self) -> &'static str;
+
+    /// The name of the tool as displayed in messages (e.g. `Node`).
+    fn display_name(&self) -> &'static str;
+
+    /// Fetches a version of the tool matching the specified semantic versioning requirements.
+    fn fetch(&self, session: &mut Session, matching: &VersionSpec) -> Fallible<()>;
+
+    /// Sets the version of the tool in the user toolchain to one matching the specified
+    /// semantic versioning requirements.
+    fn install(&self, session: &mut Session, matching: &VersionSpec) -> Fallible<()>;
+
+    /// Pins a version of the tool matching the specified semantic versioning requirements
+    /// in the toolchain of the current project.
+    fn pin(&self, session: &Session, matching: &VersionSpec) -> Fallible<()>;
+
+    /// Removes the tool from the toolchain of the current project.
+    fn unpin(&self, session: &Session) -> Fallible<()>;
+
+    /// Returns the newest available version of the tool that satisfies the current project's
+    /// `engines` section, if it has a range for the tool.
+    fn resolve_engine(&self, session: &Session) -> Fallible<Option<Version>>;
+
+    /// Uninstalls a version of the tool. Unless `force` is set, this refuses to uninstall
+    /// a version that is in use.
+    fn uninstall(&self, session: &mut Session, version: &Version, force: bool) -> Fallible<()>;
+
+    /// Lists the versions of the tool available from the remote distributor, sorted from
+    /// oldest to newest.
+    fn ls_remote(&self, session: &Session) -> Fallible<Vec<Version>>;
+
+    /// Lists the installed versions of the tool, sorted from oldest to newest.
+    fn installed(&self, session: &Session) -> Fallible<Vec<Installed>>;
+
+    /// Tests whether a version of the tool is installed.
+    fn is_installed(&self, session: &Session, version: &Version) -> Fallible<bool>;
+
+    /// Returns the user's version of the tool, if any.
+    fn user_version(&self, session: &Session) -> Fallible<Option<Version>>;
+
+    /// Returns the user's default version of the tool recorded in the catalog, ignoring any
+    /// version set in the environment.
+    fn default_version(&self, session: &Session) -> Fallible<Option<Version>>;
+
+    /// Returns the version of the tool pinned by a platform image, if any.
+    fn pinned(&self, image: &Image) -> Option<Version>;
+
+    /// Returns the directory containing the executables of a version of the tool.
+    fn bin_dir(&self, version: &Version) -> Fallible<PathBuf>;
+}
+
+/// A tool in the registry, identified by its `Distro`.
+struct Registered<D>(PhantomData<fn() -> D>);
+
+impl<D: Distro> ManagedTool for Registered<D> {
+    fn name(&self) -> &'static str {
+        D::NAME
+    }
+
+    fn display_name(&self) -> &'static str {
+        D::DISPLAY_NAME
+    }
+
+    fn fetch(&self, session: &mut Session, matching: &VersionSpec) -> Fallible<()> {
+        session.fetch::<D>(matching)?;
+        Ok(())
+    }
+
+    fn install(&self, session: &mut Session, matching: &VersionSpec) -> Fallible<()> {
+        session.set_user::<D>(matching)
+    }
+
+    fn pin(&self, session: &Session, matching: &VersionSpec) -> Fallible<()> {
+        session.pin::<D>(matching)
+    }
+
+    fn unpin(&self, session: &Session) -> Fallible<()> {
+        session.unpin::<D>()
+    }
+
+    fn resolve_engine(&self, session: &Session) -> Fallible<Option<Version>> {
+        session.resolve_engine::<D>()
+    }
+
+    fn uninstall(&self, session: &mut Session, version: &Version, force: bool) -> Fallible<()> {
+        session.uninstall::<D>(version, force)
+    }
+
+    fn ls_remote(&self, session: &Session) -> Fallible<Vec<Version>> {
+        session.ls_remote::<D>()
+    }
+
+    fn installed(&self, session: &Session) -> Fallible<Vec<Installed>> {
+        session.catalog()?.installed::<D>()
+    }
+
+    fn is_installed(&self, session: &Session, version: &Version) -> Fallible<bool> {
+        Ok(session.catalog()?.collection::<D>()?.contains(version))
+    }
+
+    fn user_version(&self, session: &Session) -> Fallible<Option<Version>> {
+        session.user_version::<D>()
+    }
+
+    fn default_version(&self, session: &Session) -> Fallible<Option<Version>> {
+        Ok(session.catalog()?.collection::<D>()?.default.clone())
+    }
+
+    fn pinned(&self, image: &Image) -> Option<Version> {
+        image.pinned::<D>().cloned()
+    }
+
+    fn bin_dir(&self, version: &Version) -> Fallible<PathBuf> {
+        D::bin_dir(&version.to_string())
+    }
+}
+
+/// Collects the tools in the registry.
+struct Collector(Vec<Box<ManagedTool>>);
+
+impl Visitor for Collector {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        self.0.push(Box::new(Registered::<D>(PhantomData)));
+        Ok(())
+    }
+}
+
+/// Lists the tools in the registry.
+pub fn tools() -> Vec<Box<ManagedTool>> {
+    let mut collector = Collector(vec![]);
+    // Collecting the tools never fails.
+    let _ = visit(&mut collector);
+    collector.0
+}
+
+/// Looks up a tool in the registry by name.
+pub fn lookup(name: &str) -> Option<Box<ManagedTool>> {
+    tools().into_iter().find(|tool| tool.name() == name)
+}
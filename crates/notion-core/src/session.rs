@@ -5,12 +5,23 @@
 use std::env::{self, VarError};
 use std::rc::Rc;
 
-use catalog::{Catalog, LazyCatalog};
+use lazycell::LazyCell;
+
+use catalog::{self, Catalog, LazyCatalog, Package};
 use config::{Config, LazyConfig};
-use distro::Fetched;
+use distro::node::NodeDistro;
+use distro::npm::NpmDistro;
+use distro::{Distro, Fetched};
+use env as notion_env;
 use image::Image;
+use manifest::Manifest;
+use package;
+use path;
 use plugin::Publish;
-use project::Project;
+use project::{Project, VersionFile};
+use registry::{self, Visitor};
+use shim;
+use style;
 use version::VersionSpec;
 
 use std::fmt::{self, Display, Formatter};
@@ -25,12 +36,17 @@ pub enum ActivityKind {
     Fetch,
     Install,
     Uninstall,
+    LsRemote,
+    List,
     Current,
     Deactivate,
     Default,
     Use,
-    Node,
-    Yarn,
+    Unpin,
+    Config,
+    /// Running an executable of the tool with the given name, such as `node`.
+    Managed(&'static str),
+    Npx,
     Notion,
     Tool,
     Help,
@@ -45,12 +61,16 @@ impl Display for ActivityKind {
             &ActivityKind::Fetch => "fetch",
             &ActivityKind::Install => "install",
             &ActivityKind::Uninstall => "uninstall",
+            &ActivityKind::LsRemote => "ls-remote",
+            &ActivityKind::List => "list",
             &ActivityKind::Current => "current",
             &ActivityKind::Deactivate => "deactivate",
             &ActivityKind::Default => "default",
             &ActivityKind::Use => "use",
-            &ActivityKind::Node => "node",
-            &ActivityKind::Yarn => "yarn",
+            &ActivityKind::Unpin => "unpin",
+            &ActivityKind::Config => "config",
+            &ActivityKind::Managed(tool) => tool,
+            &ActivityKind::Npx => "npx",
             &ActivityKind::Notion => "notion",
             &ActivityKind::Tool => "tool",
             &ActivityKind::Help => "help",
@@ -74,6 +94,91 @@ impl NotInPackageError {
     }
 }
 
+/// Thrown when the user tries to uninstall a tool version that isn't installed.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "{} version {} is not installed", tool, version)]
+#[notion_fail(code = "NoVersionMatch")]
+pub(crate) struct NotInstalledError {
+    tool: String,
+    version: String,
+}
+
+impl NotInstalledError {
+    pub(crate) fn new(tool: &str, version: &Version) -> Self {
+        NotInstalledError {
+            tool: tool.to_string(),
+            version: version.to_string(),
+        }
+    }
+}
+
+/// Thrown when the user tries to uninstall a tool version that is still in use.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "{} version {} is {}\nUse `--force` to uninstall it anyway", tool, version,
+       usage)]
+#[notion_fail(code = "InvalidArguments")]
+pub(crate) struct VersionInUseError {
+    tool: String,
+    version: String,
+    usage: String,
+}
+
+impl VersionInUseError {
+    pub(crate) fn user_default(tool: &str, version: &Version) -> Self {
+        VersionInUseError {
+            tool: tool.to_string(),
+            version: version.to_string(),
+            usage: "the user default".to_string(),
+        }
+    }
+
+    pub(crate) fn project_pinned(tool: &str, version: &Version) -> Self {
+        VersionInUseError {
+            tool: tool.to_string(),
+            version: version.to_string(),
+            usage: "pinned by the current project".to_string(),
+        }
+    }
+}
+
+/// Thrown when the user tries to install a package without selecting a version of Node.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No Node version selected to install {} with\nUse `notion install node` to select one",
+       name)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct NoPackageNodeError {
+    name: String,
+}
+
+/// Thrown when no available version of a tool satisfies the range in the `engines` section of
+/// the project manifest.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No {} version found that satisfies engines range {}", tool, range)]
+#[notion_fail(code = "NoVersionMatch")]
+pub(crate) struct NoEngineVersionFoundError {
+    tool: String,
+    range: String,
+}
+
+/// Thrown when offline and no locally available version of a tool satisfies the range in the
+/// `engines` section of the project manifest.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No {} version available locally satisfies engines range {} while offline",
+       tool, range)]
+#[notion_fail(code = "NoVersionMatch")]
+pub(crate) struct NoOfflineEngineVersionFoundError {
+    tool: String,
+    range: String,
+}
+
+/// Thrown when the user tries to install a package while offline.
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "Cannot install {} while offline", name)]
+#[notion_fail(code = "NetworkError")]
+pub(crate) struct OfflinePackageInstallError {
+    name: String,
+}
+
 /// Represents the user's state during an execution of a Notion tool. The session
 /// encapsulates a number of aspects of the environment in which the tool was
 /// invoked, including:
@@ -85,16 +190,19 @@ pub struct Session {
     config: LazyConfig,
     catalog: LazyCatalog,
     project: Option<Rc<Project>>,
+    project_platform: LazyCell<Option<Rc<Image>>>,
     event_log: EventLog,
 }
 
 impl Session {
     /// Constructs a new `Session`.
     pub fn new() -> Fallible<Session> {
+        let project = Project::for_current_dir()?.map(Rc::new);
         Ok(Session {
-            config: LazyConfig::new(),
+            config: LazyConfig::new(project.clone()),
             catalog: LazyCatalog::new(),
-            project: Project::for_current_dir()?.map(Rc::new),
+            project,
+            project_platform: LazyCell::new(),
             event_log: EventLog::new()?,
         })
     }
@@ -105,48 +213,171 @@ impl Session {
     }
 
     pub fn current_platform(&mut self) -> Fallible<Option<Rc<Image>>> {
-        if let Some(image) = self.project_platform() {
+        if let Some(image) = self.project_platform()? {
             return Ok(Some(image));
         }
 
         if let Some(image) = self.user_platform()? {
-            return Ok(Some(image));
+            return Ok(Some(self.resolve_engines(image)?));
         }
 
         return Ok(None);
     }
 
+    /// Warns about each tool in the current platform image whose version doesn't satisfy the
+    /// `engines` section of the current project's manifest, if the project isn't pinned. This
+    /// is left to Notion's own commands, so that running a tool through a shim stays quiet.
+    pub fn check_engines(&mut self) -> Fallible<()> {
+        if self.project_platform()?.is_some() {
+            return Ok(());
+        }
+        let image = match self.user_platform()? {
+            Some(image) => self.resolve_engines(image)?,
+            None => {
+                return Ok(());
+            }
+        };
+        if let Some(ref project) = self.project {
+            registry::visit(&mut EngineChecker {
+                manifest: project.manifest(),
+                image: &image,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Checks the user platform image against the `engines` section of the current project's
+    /// manifest. If the user's Node doesn't satisfy `engines.node` and resolving engines is
+    /// enabled, this switches to the newest installed version of Node that does (if any).
+    fn resolve_engines(&self, image: Rc<Image>) -> Fallible<Rc<Image>> {
+        let project = match self.project {
+            Some(ref project) => project.clone(),
+            None => {
+                return Ok(image);
+            }
+        };
+        let manifest = project.manifest();
+        let mut image = image;
+
+        if let Some(range) = manifest.engine(NodeDistro::NAME) {
+            if !range.matches(image.node()) && self.config()?.resolve_engines() {
+                let installed = self
+                    .catalog()?
+                    .collection::<NodeDistro>()?
+                    .versions
+                    .iter()
+                    .rev()
+                    .find(|version| range.matches(version))
+                    .cloned();
+                if let Some(node) = installed {
+                    let mut resolved = (*image).clone();
+                    resolved.pin::<NodeDistro>(node);
+                    image = Rc::new(resolved);
+                }
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Returns the newest version of a tool available from the remote distributor that
+    /// satisfies the current project's `engines` section, if it has a range for the tool.
+    /// Offline, only the versions that are installed or whose archive is cached are available.
+    pub fn resolve_engine<D: Distro>(&self) -> Fallible<Option<Version>> {
+        let range = match self.project {
+            Some(ref project) => match project.manifest().engine(D::NAME) {
+                Some(range) => range.clone(),
+                None => {
+                    return Ok(None);
+                }
+            },
+            None => {
+                return Ok(None);
+            }
+        };
+
+        if notion_env::offline() {
+            let version = self
+                .catalog()?
+                .collection::<D>()?
+                .local_versions()?
+                .into_iter()
+                .rev()
+                .find(|version| range.matches(version));
+            return match version {
+                Some(version) => Ok(Some(version)),
+                None => throw!(NoOfflineEngineVersionFoundError {
+                    tool: D::DISPLAY_NAME.to_string(),
+                    range: range.to_string(),
+                }),
+            };
+        }
+
+        let version = self
+            .ls_remote::<D>()?
+            .into_iter()
+            .filter(|version| range.matches(version))
+            .max();
+        match version {
+            Some(version) => Ok(Some(version)),
+            None => throw!(NoEngineVersionFoundError {
+                tool: D::DISPLAY_NAME.to_string(),
+                range: range.to_string(),
+            }),
+        }
+    }
+
     pub fn user_platform(&mut self) -> Fallible<Option<Rc<Image>>> {
-        if let Some(node) = self.user_node()? {
-            let node_str = node.to_string();
-
-            if let Some(yarn) = self.user_yarn()? {
-                let yarn_str = yarn.to_string();
-
-                return Ok(Some(Rc::new(Image {
-                    node,
-                    node_str,
-                    yarn: Some(yarn),
-                    yarn_str: Some(yarn_str)
-                })));
+        if let Some(node) = self.user_version::<NodeDistro>()? {
+            let mut collector = UserVersions {
+                session: self,
+                image: Image::for_node(node),
+            };
+            registry::visit(&mut collector)?;
+            return Ok(Some(Rc::new(collector.image)));
+        }
+        Ok(None)
+    }
+
+    /// Returns the current project's platform image, if any. This is the toolchain pinned in
+    /// the project manifest or, failing that, the Node version in the project's version file
+    /// (if version files are enabled).
+    pub fn project_platform(&self) -> Fallible<Option<Rc<Image>>> {
+        self.project_platform
+            .try_borrow_with(|| self.find_project_platform())
+            .map(|image| image.clone())
+    }
+
+    fn find_project_platform(&self) -> Fallible<Option<Rc<Image>>> {
+        if let Some(ref project) = self.project {
+            if let Some(image) = project.platform() {
+                return Ok(Some(image));
             }
+        }
 
-            return Ok(Some(Rc::new(Image {
-                node,
-                node_str,
-                yarn: None,
-                yarn_str: None
-            })));
+        if let Some(version_file) = self.project_version_file()? {
+            // This runs every time a shim is launched, so prefer an installed version over
+            // asking the remote distributor.
+            let spec = version_file.spec()?;
+            let node = match self.catalog()?.resolve_installed::<NodeDistro>(&spec)? {
+                Some(node) => node,
+                None => self.resolve::<NodeDistro>(&spec)?,
+            };
+            return Ok(Some(Rc::new(Image::for_node(node))));
         }
         Ok(None)
     }
 
-    /// Returns the current project's pinned platform image, if any.
-    pub fn project_platform(&self) -> Option<Rc<Image>> {
+    /// Returns the version file (`.nvmrc` or `.node-version`) that supplies the current
+    /// project's Node version, if any. A version file is only used (or even read) if version
+    /// files are enabled and the project manifest has no toolchain.
+    pub fn project_version_file(&self) -> Fallible<Option<&VersionFile>> {
         if let Some(ref project) = self.project {
-            return project.platform();
+            if !project.is_pinned() && self.config()?.version_files() {
+                return project.version_file();
+            }
         }
-        None
+        Ok(None)
     }
 
     /// Produces a reference to the current tool catalog.
@@ -166,103 +397,160 @@ impl Session {
 
     /// Ensures that a platform image has been fully fetched and set up.
     pub(crate) fn prepare_image(&mut self, image: &Image) -> Fallible<()> {
-        let catalog = self.catalog.get_mut()?;
-
-        if !catalog.node.contains(&image.node) {
-            let config = self.config.get()?;
-            let _ = catalog.fetch_node(&VersionSpec::exact(&image.node), config)?;
-        }
-
-        if let Some(ref yarn_version) = &image.yarn {
-            let config = self.config.get()?;
-            let _ = catalog.fetch_yarn(&VersionSpec::exact(yarn_version), config)?;
-        }
-
-        Ok(())
+        registry::visit(&mut Preparer {
+            session: self,
+            image,
+        })
     }
 
-    pub fn user_node(&self) -> Fallible<Option<Version>> {
-        match env::var("NOTION_NODE_VERSION") {
+    /// Returns the user's version of a tool, if any.
+    pub fn user_version<D: Distro>(&self) -> Fallible<Option<Version>> {
+        match env::var(notion_env::version_var(D::NAME)) {
             Ok(s) => Ok(Some(Version::parse(&s[..]).unknown()?)),
-            Err(VarError::NotPresent) => Ok(self.catalog()?.node.default.clone()),
+            Err(VarError::NotPresent) => Ok(self.catalog()?.collection::<D>()?.default.clone()),
             Err(VarError::NotUnicode(_)) => unimplemented!(),
         }
     }
 
-    /// Fetches a version of Node matching the specified semantic verisoning
+    /// Returns the user's version of Node, if any.
+    pub fn user_node(&self) -> Fallible<Option<Version>> {
+        self.user_version::<NodeDistro>()
+    }
+
+    /// Fetches a version of a tool matching the specified semantic versioning
     /// requirements.
-    pub fn fetch_node(&mut self, matching: &VersionSpec) -> Fallible<Fetched> {
+    pub fn fetch<D: Distro>(&mut self, matching: &VersionSpec) -> Fallible<Fetched> {
         let catalog = self.catalog.get_mut()?;
         let config = self.config.get()?;
-        catalog.fetch_node(matching, config)
+        catalog.fetch::<D>(matching, config)
     }
 
-    /// Sets the user toolchain's Node version to one matching the specified semantic versioning
-    /// requirements.
-    pub fn set_user_node(&mut self, matching: &VersionSpec) -> Fallible<()> {
+    /// Sets the version of a tool in the user toolchain to one matching the specified
+    /// semantic versioning requirements.
+    pub fn set_user<D: Distro>(&mut self, matching: &VersionSpec) -> Fallible<()> {
         let catalog = self.catalog.get_mut()?;
         let config = self.config.get()?;
-        catalog.set_user_node(matching, config)
+        catalog.set_user::<D>(matching, config)
     }
 
-    /// Returns the version of Node matching the specified semantic versioning requirements.
-    pub fn get_matching_node(&self, matching: &VersionSpec) -> Fallible<Version> {
+    /// Returns the version of a tool matching the specified semantic versioning requirements.
+    pub fn resolve<D: Distro>(&self, matching: &VersionSpec) -> Fallible<Version> {
         let catalog = self.catalog.get()?;
         let config = self.config.get()?;
-        catalog.resolve_node(matching, config)
+        catalog.resolve::<D>(matching, config)
     }
 
-    /// Updates toolchain in package.json with the Node version matching the specified semantic
-    /// versioning requirements.
-    pub fn pin_node_version(&self, matching: &VersionSpec) -> Fallible<()> {
-        if let Some(ref project) = self.project() {
-            let node_version = self.get_matching_node(matching)?;
-            project.pin_node_in_toolchain(node_version)?;
-        } else {
-            throw!(NotInPackageError::new());
-        }
-        Ok(())
+    /// Lists the versions of a tool available from the remote distributor.
+    pub fn ls_remote<D: Distro>(&self) -> Fallible<Vec<Version>> {
+        let catalog = self.catalog.get()?;
+        let config = self.config.get()?;
+        catalog.ls_remote::<D>(config)
     }
 
-    pub fn user_yarn(&mut self) -> Fallible<Option<Version>> {
-        Ok(self.catalog()?.yarn.default.clone())
-    }
+    /// Uninstalls the specified version of a tool. Unless `force` is set, this refuses to
+    /// uninstall the user's default version or the version pinned by the current project.
+    pub fn uninstall<D: Distro>(&mut self, version: &Version, force: bool) -> Fallible<()> {
+        if !self.catalog()?.collection::<D>()?.contains(version) {
+            throw!(NotInstalledError::new(D::DISPLAY_NAME, version));
+        }
 
-    /// Fetches a version of Node matching the specified semantic verisoning
-    /// requirements.
-    pub fn fetch_yarn(&mut self, matching: &VersionSpec) -> Fallible<Fetched> {
-        let catalog = self.catalog.get_mut()?;
-        let config = self.config.get()?;
-        catalog.fetch_yarn(matching, config)
-    }
+        if !force {
+            if self.catalog()?.collection::<D>()?.default.as_ref() == Some(version) {
+                throw!(VersionInUseError::user_default(D::DISPLAY_NAME, version));
+            }
 
-    /// Sets the Yarn version in the user toolchain to one matching the specified semantic versioning
-    /// requirements.
-    pub fn set_user_yarn(&mut self, matching: &VersionSpec) -> Fallible<()> {
-        let catalog = self.catalog.get_mut()?;
-        let config = self.config.get()?;
-        catalog.set_user_yarn(matching, config)
+            if let Some(image) = self.project_platform()? {
+                if image.pinned::<D>() == Some(version) {
+                    throw!(VersionInUseError::project_pinned(D::DISPLAY_NAME, version));
+                }
+            }
+        }
+
+        self.catalog_mut()?.uninstall::<D>(version)
     }
 
-    /// Returns the version of Yarn matching the specified semantic versioning requirements
-    pub fn get_matching_yarn(&self, matching: &VersionSpec) -> Fallible<Version> {
-        let catalog = self.catalog.get()?;
-        let config = self.config.get()?;
-        catalog.resolve_yarn(matching, config)
+    /// Updates toolchain in package.json with the version of a tool matching the specified
+    /// semantic versioning requirements.
+    pub fn pin<D: Distro>(&self, matching: &VersionSpec) -> Fallible<()> {
+        if let Some(ref project) = self.project() {
+            let version = self.resolve::<D>(matching)?;
+            project.pin::<D>(version)?;
+        } else {
+            throw!(NotInPackageError::new());
+        }
+        Ok(())
     }
 
-    /// Updates toolchain in package.json with the Yarn version matching the specified semantic
-    /// versioning requirements.
-    pub fn pin_yarn_version(&self, matching: &VersionSpec) -> Fallible<()> {
+    /// Removes a tool from the toolchain in package.json.
+    pub fn unpin<D: Distro>(&self) -> Fallible<()> {
         if let Some(ref project) = self.project() {
-            let yarn_version = self.get_matching_yarn(matching)?;
-            project.pin_yarn_in_toolchain(yarn_version)?;
+            project.unpin::<D>()?;
         } else {
             throw!(NotInPackageError::new());
         }
         Ok(())
     }
 
+    /// Installs a version of a package matching the specified semantic versioning requirements
+    /// into its own directory, bound to the user's current version of Node, and creates shims
+    /// for the executables it provides.
+    pub fn install_package(&mut self, name: &str, matching: &VersionSpec) -> Fallible<Package> {
+        if notion_env::offline() {
+            throw!(OfflinePackageInstallError {
+                name: name.to_string(),
+            });
+        }
+
+        let image = match self.user_platform()? {
+            Some(image) => image,
+            None => throw!(NoPackageNodeError {
+                name: name.to_string(),
+            }),
+        };
+        self.prepare_image(&image)?;
+
+        let version = {
+            let config = self.config.get()?;
+            let mirror = config
+                .tool::<NpmDistro>()
+                .and_then(|config| config.mirror.as_ref())
+                .map(|mirror| mirror.as_str());
+            catalog::resolve_registry_package(name, matching, mirror)?
+        };
+
+        let bins = package::install(name, &version, &image)?;
+        let previous = self.catalog()?
+            .packages
+            .get(name)
+            .map(|package| package.version.clone());
+
+        if let Err(error) = self.catalog()?.check_package_bins(name, &bins) {
+            // An installation of the same version has already been replaced in place.
+            if previous.as_ref() != Some(&version) {
+                package::remove(name, &version)?;
+            }
+            return Err(error);
+        }
+
+        for bin in bins.iter() {
+            if !path::shim_file(bin)?.exists() {
+                shim::create(bin)?;
+            }
+        }
+
+        let installed = Package {
+            version,
+            node: image.node().clone(),
+            bins,
+        };
+        if let Some(replaced) = self.catalog_mut()?.add_package(name, installed.clone())? {
+            if replaced.version != installed.version {
+                package::remove(name, &replaced.version)?;
+            }
+        }
+        Ok(installed)
+    }
+
     pub fn add_event_start(&mut self, activity_kind: ActivityKind) {
         self.event_log.add_event_start(activity_kind)
     }
@@ -298,6 +586,65 @@ impl Session {
     }
 }
 
+/// Warns about each tool version in a platform image that doesn't satisfy the version
+/// range required by the `engines` section of a manifest.
+struct EngineChecker<'a> {
+    manifest: &'a Manifest,
+    image: &'a Image,
+}
+
+impl<'a> Visitor for EngineChecker<'a> {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        if let Some(range) = self.manifest.engine(D::NAME) {
+            if let Some(version) = self.image.pinned::<D>() {
+                if !range.matches(version) {
+                    style::display_warning(&format!(
+                        "this project requires {} {}, but the active version is {}",
+                        D::DISPLAY_NAME,
+                        range,
+                        version
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds the user's version of each tool besides Node, if any, to the user platform image.
+struct UserVersions<'a> {
+    session: &'a Session,
+    image: Image,
+}
+
+impl<'a> Visitor for UserVersions<'a> {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        if D::NAME != NodeDistro::NAME {
+            if let Some(version) = self.session.user_version::<D>()? {
+                self.image.pin::<D>(version);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fetches each tool version pinned by a platform image that isn't installed yet.
+struct Preparer<'a> {
+    session: &'a mut Session,
+    image: &'a Image,
+}
+
+impl<'a> Visitor for Preparer<'a> {
+    fn visit<D: Distro>(&mut self) -> Fallible<()> {
+        if let Some(version) = self.image.pinned::<D>() {
+            if !self.session.catalog()?.collection::<D>()?.contains(version) {
+                self.session.fetch::<D>(&VersionSpec::exact(version))?;
+            }
+        }
+        Ok(())
+    }
+}
+
 fn publish_plugin(config: &LazyConfig) -> Fallible<Option<&Publish>> {
     let config = config.get()?;
     Ok(config
@@ -325,11 +672,11 @@ pub mod tests {
         let project_pinned = fixture_path("basic");
         env::set_current_dir(&project_pinned).expect("Could not set current directory");
         let pinned_session = Session::new().expect("Couldn't create new Session");
-        assert_eq!(pinned_session.project_platform().is_some(), true);
+        assert_eq!(pinned_session.project_platform().unwrap().is_some(), true);
 
         let project_unpinned = fixture_path("no_toolchain");
         env::set_current_dir(&project_unpinned).expect("Could not set current directory");
         let unpinned_session = Session::new().expect("Couldn't create new Session");
-        assert_eq!(unpinned_session.project_platform().is_none(), true);
+        assert_eq!(unpinned_session.project_platform().unwrap().is_none(), true);
     }
 }
@@ -28,7 +28,7 @@ impl SymlinkError {
 
 fn is_3p_shim(name: &str) -> bool {
     match name {
-        "node" | "yarn" | "npm" | "npx" => false,
+        "node" | "yarn" | "npm" | "npx" | "pnpm" => false,
         _ => true,
     }
 }
@@ -41,6 +41,11 @@ pub fn display_error_prefix(cx: ErrorContext) {
     }
 }
 
+/// Displays a warning to stderr.
+pub fn display_warning<M: Display>(message: &M) {
+    eprintln!("{} {}", style("warning:").yellow().bold(), message);
+}
+
 /// Displays a generic message for internal errors to stderr.
 pub fn display_unknown_error<E: Fail>(cx: ErrorContext, err: &E) {
     display_error_prefix(cx);
@@ -81,6 +86,7 @@ pub fn display_unknown_error<E: Fail>(cx: ErrorContext, err: &E) {
 #[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
 pub enum Action {
     Fetching,
+    Verifying,
 }
 
 impl Action {
@@ -92,6 +98,7 @@ impl Display for Action {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
         let s = match self {
             &Action::Fetching => "Fetching",
+            &Action::Verifying => "Verifying",
         };
         f.write_str(s)
     }
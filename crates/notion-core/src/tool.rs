@@ -3,10 +3,16 @@
 use std::env::{args_os, ArgsOs};
 use std::ffi::{OsStr, OsString};
 use std::io;
-use std::marker::Sized;
-use std::path::Path;
+use std::marker::{PhantomData, Sized};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use catalog::Catalog;
+use distro::node::NodeDistro;
+use distro::pnpm::PnpmDistro;
+use distro::yarn::YarnDistro;
+use distro::Distro;
+use image::Image;
 use notion_fail::{ExitCode, FailExt, Fallible, NotionError, NotionFail};
 use path;
 use session::{ActivityKind, Session};
@@ -117,11 +123,20 @@ pub struct Script(Command);
 /// Represents a delegated binary executable.
 pub struct Binary(Command);
 
+/// Represents an executable of a tool whose versions Notion manages.
+pub struct Managed<D>(Command, PhantomData<fn() -> D>);
+
 /// Represents a Node executable.
-pub struct Node(Command);
+pub type Node = Managed<NodeDistro>;
 
 /// Represents a Yarn executable.
-pub struct Yarn(Command);
+pub type Yarn = Managed<YarnDistro>;
+
+/// Represents a pnpm executable.
+pub type Pnpm = Managed<PnpmDistro>;
+
+/// Represents an npx executable.
+pub struct Npx(Command);
 
 #[cfg(windows)]
 impl Tool for Script {
@@ -196,11 +211,10 @@ impl Tool for Binary {
             // check if the executable is a direct dependency
             if project.has_direct_bin(&exe)? {
                 // use the full path to the file
-                let mut path_to_bin = project.local_bin_dir();
-                path_to_bin.push(&exe);
+                let path_to_bin = project.local_bin_file(&exe);
 
                 // if we're in a pinned project, use the project's platform.
-                if let Some(ref platform) = session.project_platform() {
+                if let Some(ref platform) = session.project_platform()? {
                     return Ok(Self::from_components(
                         &path_to_bin.as_os_str(),
                         args,
@@ -224,11 +238,24 @@ impl Tool for Binary {
             }
         }
 
+        // next try a package installed with `notion install`, which runs on the version
+        // of Node it was installed with
+        let installed = package_bin(session.catalog()?, &exe)?;
+
+        if let Some((path_to_bin, platform)) = installed {
+            session.prepare_image(&platform)?;
+            return Ok(Self::from_components(
+                &path_to_bin.as_os_str(),
+                args,
+                &platform.path()?,
+            ));
+        }
+
         // next try to use the user toolchain
         if let Some(ref platform) = session.user_platform()? {
-            // use the full path to the binary
-            // ISSUE (#160): Look up the platform image bound to the user tool.
-            let mut third_p_bin_dir = path::node_version_3p_bin_dir(&platform.node_str)?;
+            // use the full path to the binary, which was installed globally with npm
+            // itself and so isn't bound to a platform
+            let mut third_p_bin_dir = path::node_version_3p_bin_dir(&platform.node().to_string())?;
             third_p_bin_dir.push(&exe);
             return Ok(Self::from_components(
                 &third_p_bin_dir.as_os_str(),
@@ -253,6 +280,27 @@ impl Tool for Binary {
     }
 }
 
+/// Returns the path to an executable provided by a package installed with `notion install`,
+/// along with the platform image of the version of Node the package was installed with,
+/// if any package provides the executable.
+pub fn package_bin(catalog: &Catalog, exe: &OsStr) -> Fallible<Option<(PathBuf, Image)>> {
+    let bin = match exe.to_str() {
+        Some(bin) => bin,
+        None => {
+            return Ok(None);
+        }
+    };
+
+    match catalog.package_for_bin(bin)? {
+        Some((name, package)) => {
+            let mut path_to_bin = path::package_bin_dir(name, &package.version.to_string())?;
+            path_to_bin.push(exe);
+            Ok(Some((path_to_bin, Image::for_node(package.node.clone()))))
+        }
+        None => Ok(None),
+    }
+}
+
 #[derive(Fail, Debug)]
 #[fail(display = "Tool name could not be determined")]
 struct NoArg0Error;
@@ -282,24 +330,32 @@ struct NoSuchToolError {
     tool: String,
 }
 
-impl Tool for Node {
-    fn new(session: &mut Session) -> Fallible<Self> {
-        session.add_event_start(ActivityKind::Node);
+/// Starts a session activity that runs an executable from the current platform image,
+/// fetching any tool versions in the image that aren't installed yet. The `tool` names what
+/// must be selected for this to work, in the error when there is no platform.
+fn platform_command(session: &mut Session, activity: ActivityKind, tool: &str) -> Fallible<Command> {
+    session.add_event_start(activity);
+
+    let mut args = args_os();
+    let exe = arg0(&mut args)?;
+    if let Some(ref platform) = session.current_platform()? {
+        session.prepare_image(platform)?;
+        Ok(command_for(&exe, args, &platform.path()?))
+    } else {
+        throw!(NoSuchToolError {
+            tool: tool.to_string()
+        });
+    }
+}
 
-        let mut args = args_os();
-        let exe = arg0(&mut args)?;
-        if let Some(ref platform) = session.current_platform()? {
-            session.prepare_image(platform)?;
-            Ok(Self::from_components(&exe, args, &platform.path()?))
-        } else {
-            throw!(NoSuchToolError {
-                tool: "Node".to_string()
-            });
-        }
+impl<D: Distro> Tool for Managed<D> {
+    fn new(session: &mut Session) -> Fallible<Self> {
+        let command = platform_command(session, ActivityKind::Managed(D::NAME), D::DISPLAY_NAME)?;
+        Ok(Managed(command, PhantomData))
     }
 
     fn from_components(exe: &OsStr, args: ArgsOs, path_var: &OsStr) -> Self {
-        Node(command_for(exe, args, path_var))
+        Managed(command_for(exe, args, path_var), PhantomData)
     }
 
     fn command(self) -> Command {
@@ -307,27 +363,64 @@ impl Tool for Node {
     }
 }
 
-impl Tool for Yarn {
+impl Tool for Npx {
     fn new(session: &mut Session) -> Fallible<Self> {
-        session.add_event_start(ActivityKind::Yarn);
-
-        let mut args = args_os();
-        let exe = arg0(&mut args)?;
-        if let Some(ref platform) = session.current_platform()? {
-            session.prepare_image(platform)?;
-            Ok(Self::from_components(&exe, args, &platform.path()?))
-        } else {
-            throw!(NoSuchToolError {
-                tool: "Yarn".to_string()
-            });
-        }
+        // npx is bundled with npm, which comes with Node unless a version of npm is selected
+        let command = platform_command(session, ActivityKind::Npx, NodeDistro::DISPLAY_NAME)?;
+        Ok(Npx(command))
     }
 
     fn from_components(exe: &OsStr, args: ArgsOs, path_var: &OsStr) -> Self {
-        Yarn(command_for(exe, args, path_var))
+        Npx(command_for(exe, args, path_var))
     }
 
     fn command(self) -> Command {
         self.0
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+
+    use std::ffi::OsStr;
+
+    use catalog::Catalog;
+    use path;
+    use tool::package_bin;
+
+    const CATALOG: &'static str = "\
+[node]
+default = '10.13.0'
+versions = [ '8.9.4', '10.13.0' ]
+
+[packages.ember-cli]
+version = '3.5.0'
+node = '8.9.4'
+bins = [ 'ember' ]
+";
+
+    #[test]
+    fn package_bin_uses_bound_node() {
+        let catalog: Catalog = CATALOG.parse().expect("Could not parse catalog");
+
+        let (bin, platform) = package_bin(&catalog, OsStr::new("ember"))
+            .expect("Could not look up ember")
+            .expect("No package provides ember");
+        let mut expected = path::package_bin_dir("ember-cli", "3.5.0").unwrap();
+        expected.push("ember");
+        assert_eq!(bin, expected);
+        assert_eq!(platform.node().to_string(), "8.9.4");
+
+        assert!(package_bin(&catalog, OsStr::new("tsc")).unwrap().is_none());
+    }
+
+    #[test]
+    fn package_bin_conflict() {
+        let src = format!(
+            "{}\n[packages.ember-cli-fork]\nversion = '1.0.0'\nnode = '10.13.0'\nbins = [ 'ember' ]\n",
+            CATALOG
+        );
+        let catalog: Catalog = src.parse().expect("Could not parse catalog");
+        assert!(package_bin(&catalog, OsStr::new("ember")).is_err());
+    }
+}
@@ -13,6 +13,11 @@ use self::serial::parse_requirements;
 pub enum VersionSpec {
     Latest,
     Semver(VersionReq),
+    /// The latest long-term support release (`lts` or `lts/*`).
+    Lts,
+    /// The latest release of a named long-term support line (e.g. `lts/carbon`). The name is
+    /// stored in lowercase.
+    LtsLine(String),
 }
 
 impl fmt::Display for VersionSpec {
@@ -20,6 +25,8 @@ impl fmt::Display for VersionSpec {
         match *self {
             VersionSpec::Latest => write!(f, "latest"),
             VersionSpec::Semver(ref req) => req.fmt(f),
+            VersionSpec::Lts => write!(f, "lts"),
+            VersionSpec::LtsLine(ref name) => write!(f, "lts/{}", name),
         }
     }
 }
@@ -58,6 +65,14 @@ impl FromStr for VersionSpec {
             return Ok(VersionSpec::Latest);
         }
 
+        if s == "lts" || s == "lts/*" {
+            return Ok(VersionSpec::Lts);
+        }
+
+        if s.starts_with("lts/") && s.len() > 4 {
+            return Ok(VersionSpec::LtsLine(s[4..].to_lowercase()));
+        }
+
         Ok(VersionSpec::Semver(parse_requirements(s)?))
     }
 }
@@ -82,3 +97,40 @@ impl VersionParseError {
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+
+    use semver::VersionReq;
+    use version::VersionSpec;
+
+    #[test]
+    fn test_parse_lts() {
+        match VersionSpec::parse("lts").unwrap() {
+            VersionSpec::Lts => {}
+            spec => panic!("unexpected version spec: {}", spec),
+        }
+        match VersionSpec::parse("lts/*").unwrap() {
+            VersionSpec::Lts => {}
+            spec => panic!("unexpected version spec: {}", spec),
+        }
+        match VersionSpec::parse("lts/Carbon").unwrap() {
+            VersionSpec::LtsLine(ref name) => assert_eq!(name, "carbon"),
+            spec => panic!("unexpected version spec: {}", spec),
+        }
+        match VersionSpec::parse("8").unwrap() {
+            VersionSpec::Semver(ref req) => assert_eq!(req, &VersionReq::parse("=8").unwrap()),
+            spec => panic!("unexpected version spec: {}", spec),
+        }
+        assert!(VersionSpec::parse("lts/").is_err());
+    }
+
+    #[test]
+    fn test_display_lts() {
+        assert_eq!(VersionSpec::Lts.to_string(), "lts");
+        assert_eq!(
+            VersionSpec::LtsLine("dubnium".to_string()).to_string(),
+            "lts/dubnium"
+        );
+    }
+}
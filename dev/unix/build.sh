@@ -41,14 +41,18 @@ shell_dir="$script_dir/../../shell"
 
 encode_base64_sed_command notion NOTION "$build_dir/notion"
 encode_base64_sed_command node NODE "$build_dir/node"
+encode_base64_sed_command npx NPX "$build_dir/npx"
 encode_base64_sed_command yarn YARN "$build_dir/yarn"
+encode_base64_sed_command pnpm PNPM "$build_dir/pnpm"
 encode_base64_sed_command launchbin LAUNCHBIN "$build_dir/launchbin"
 encode_base64_sed_command launchscript LAUNCHSCRIPT "$build_dir/launchscript"
 encode_expand_sed_command bash_launcher BASH_LAUNCHER "$shell_dir/unix/load.sh"
 
 sed -f notion.base64.txt \
     -f node.base64.txt \
+    -f npx.base64.txt \
     -f yarn.base64.txt \
+    -f pnpm.base64.txt \
     -f launchbin.base64.txt \
     -f launchscript.base64.txt \
     -f bash_launcher.expand.txt \
@@ -58,7 +62,9 @@ chmod 755 "$script_dir/install.sh"
 
 rm notion.base64.txt \
    node.base64.txt \
+   npx.base64.txt \
    yarn.base64.txt \
+   pnpm.base64.txt \
    launchbin.base64.txt \
    launchscript.base64.txt \
    bash_launcher.expand.txt
@@ -4,19 +4,27 @@ use std::str::FromStr;
 use docopt::Docopt;
 use serde::Deserialize;
 
-use notion_core::session::Session;
-use notion_fail::{FailExt, Fallible};
+use notion_core::config::{ConfigFile, ConfigKey, ConfigLayers, ConfigLevel, ConfigOrigin};
+use notion_core::path::user_catalog_file;
+use notion_core::project::Project;
+use notion_core::registry::{self, ManagedTool};
+use notion_core::session::{ActivityKind, Session};
+use notion_core::style::display_warning;
+use notion_fail::{ExitCode, FailExt, Fallible};
 
 use Notion;
 use command::{Command, CommandName, Help};
 
 use CliParseError;
-use CommandUnimplementedError;
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct Args {
     arg_command: SubcommandName,
     arg_args: Option<Vec<String>>,
+    flag_system: bool,
+    flag_user: bool,
+    flag_project: bool,
+    flag_show_origin: bool,
 }
 
 #[derive(Debug, Deserialize, Clone, Copy)]
@@ -77,33 +85,32 @@ impl FromStr for SubcommandName {
 
 pub(crate) enum Config {
     Help,
-    Subcommand(Subcommand),
+    Subcommand(Subcommand, Option<ConfigLevel>),
+    /// Prints the user's default version of a tool, which is kept in the catalog rather than
+    /// in a configuration file.
+    Default {
+        tool: Box<ManagedTool>,
+        show_origin: bool,
+    },
 }
 
 pub(crate) enum Subcommand {
-    Get {
-        // Not yet implemented.
-        #[allow(dead_code)]
-        key: String,
-    },
-    Set {
-        // Not yet implemented.
-        #[allow(dead_code)]
-        key: String,
-
-        // Not yet implemented.
-        #[allow(dead_code)]
-        value: String,
-    },
-    Delete {
-        // Not yet implemented.
-        #[allow(dead_code)]
-        key: String,
-    },
-    List,
+    Get { key: ConfigKey, show_origin: bool },
+    Set { key: ConfigKey, value: String },
+    Delete { key: ConfigKey },
+    List { show_origin: bool },
     Edit,
 }
 
+/// Parses a `<tool>.default` key, such as `node.default`.
+fn parse_default_key(key: &str) -> Option<Box<ManagedTool>> {
+    let mut segments = key.splitn(2, '.');
+    match (segments.next(), segments.next()) {
+        (Some(tool), Some("default")) => registry::lookup(tool),
+        _ => None,
+    }
+}
+
 fn parse_subcommand<'de, T: Deserialize<'de>>(
     subcommand: &str,
     usage: &str,
@@ -128,18 +135,46 @@ impl Command for Config {
 Get or set configuration values
 
 Usage:
-    notion config <command> [<args> ...]
+    notion config [--system | --user | --project] [--show-origin] <command> [<args> ...]
     notion config -h | --help
 
 Options:
-    -h, --help     Display this message
+    -h, --help       Display this message
+    --system         Use the system-wide configuration file
+    --user           Use the user's configuration file
+    --project        Use the project's .notionrc.toml, which can only set the
+                     resolve.url and ls-remote.url plugins of tools
+    --show-origin    Show the file each setting comes from (get and list only)
 
 Config commands:
-    get <key>
-    set <key> <value>
-    delete <key>
-    list
-    edit
+    get <key>            Print the value of a setting
+    set <key> <value>    Change the value of a setting
+    delete <key>         Remove a setting
+    list                 Print every setting that is set
+    edit                 Open the configuration file in $VISUAL or $EDITOR
+
+Settings:
+    <tool>.default                The user's default version of <tool> (get only;
+                                  change it with `notion install`)
+    <tool>.mirror                 Root URL of a mirror to download <tool> from
+    <tool>.resolve.<url|bin>      Plugin for resolving versions of <tool>
+    <tool>.ls-remote.<url|bin>    Plugin for listing the available versions of <tool>
+    node.require-checksums        Refuse node archives missing from the checksums
+                                  published for their version (true or false,
+                                  defaults to true)
+    events.publish.<url|bin>      Plugin for publishing events
+    project.version-files         Honor .nvmrc and .node-version files (true or false)
+    project.resolve-engines       Select Node from package.json engines (true or false)
+
+    where <tool> is node, npm, yarn or pnpm
+
+Without a file option, `get` and `list` show the settings in effect, which come from
+the project's .notionrc.toml, then the user's file, then the system-wide file, and
+`set`, `delete` and `edit` change the user's file.
+
+Every setting can also be overridden with an environment variable named after its
+key, e.g. NOTION_NODE_MIRROR for node.mirror or NOTION_YARN_RESOLVE_URL for
+yarn.resolve.url, which takes precedence over all of the files.
 ";
 
     fn help() -> Self {
@@ -149,49 +184,151 @@ Config commands:
     fn parse(_: Notion, args: Args) -> Fallible<Config> {
         let command = args.arg_command;
         let argv = args.arg_args.unwrap_or_else(|| vec![]);
-        Ok(match command {
+        let show_origin = args.flag_show_origin;
+        let level = if args.flag_system {
+            Some(ConfigLevel::System)
+        } else if args.flag_user {
+            Some(ConfigLevel::User)
+        } else if args.flag_project {
+            Some(ConfigLevel::Project)
+        } else {
+            None
+        };
+
+        let subcommand = match command {
             SubcommandName::Get => {
                 let Key { arg_key } = parse_subcommand("get", "<key>", argv)?;
-                Config::Subcommand(Subcommand::Get { key: arg_key })
+                if let Some(tool) = parse_default_key(&arg_key) {
+                    if level.is_some() {
+                        throw!(CliParseError {
+                            usage: None,
+                            error: format!(
+                                "`{}` is read from the catalog, not from a configuration file",
+                                arg_key
+                            ),
+                        });
+                    }
+                    return Ok(Config::Default { tool, show_origin });
+                }
+                Subcommand::Get {
+                    key: arg_key.parse()?,
+                    show_origin,
+                }
+            }
+            SubcommandName::List => {
+                let Nullary = parse_subcommand("list", "", argv)?;
+                Subcommand::List { show_origin }
+            }
+            _ if show_origin => {
+                throw!(CliParseError {
+                    usage: None,
+                    error: format!(
+                        "--show-origin cannot be used with `notion config {}`",
+                        command
+                    ),
+                });
             }
             SubcommandName::Set => {
                 let KeyValue { arg_key, arg_value } =
                     parse_subcommand("set", "<key> <value>", argv)?;
-                Config::Subcommand(Subcommand::Set {
-                    key: arg_key,
+                Subcommand::Set {
+                    key: arg_key.parse()?,
                     value: arg_value,
-                })
+                }
             }
             SubcommandName::Delete => {
                 let Key { arg_key } = parse_subcommand("delete", "<key>", argv)?;
-                Config::Subcommand(Subcommand::Delete { key: arg_key })
-            }
-            SubcommandName::List => {
-                let Nullary = parse_subcommand("list", "", argv)?;
-                Config::Subcommand(Subcommand::List)
+                Subcommand::Delete {
+                    key: arg_key.parse()?,
+                }
             }
             SubcommandName::Edit => {
                 let Nullary = parse_subcommand("edit", "", argv)?;
-                Config::Subcommand(Subcommand::Edit)
+                Subcommand::Edit
             }
-        })
+        };
+        Ok(Config::Subcommand(subcommand, level))
     }
 
     fn run(self, session: &mut Session) -> Fallible<()> {
-        //session.add_event_start(ActivityKind::Version);
-        let result = match self {
-            Config::Help => Help::Command(CommandName::Config).run(session),
-            Config::Subcommand(Subcommand::Get { key: _ }) => Ok(()),
-            Config::Subcommand(Subcommand::Set { key: _, value: _ }) => {
-                throw!(CommandUnimplementedError::new("set"))
+        session.add_event_start(ActivityKind::Config);
+        match self {
+            Config::Help => Help::Command(CommandName::Config).run(session)?,
+            Config::Subcommand(subcommand, level) => {
+                let project = session.project();
+                let project = project.as_ref().map(|project| &**project);
+                run_subcommand(subcommand, level, project)?;
             }
-            Config::Subcommand(Subcommand::Delete { key: _ }) => {
-                throw!(CommandUnimplementedError::new("delete"))
+            Config::Default { tool, show_origin } => {
+                if let Some(version) = tool.default_version(session)? {
+                    if show_origin {
+                        println!("{}\t{}", user_catalog_file()?.display(), version);
+                    } else {
+                        println!("{}", version);
+                    }
+                }
             }
-            Config::Subcommand(Subcommand::List) => throw!(CommandUnimplementedError::new("list")),
-            Config::Subcommand(Subcommand::Edit) => throw!(CommandUnimplementedError::new("edit")),
         };
-        //session.add_event_end(ActivityKind::Version, 0);
-        result
+        session.add_event_end(ActivityKind::Config, ExitCode::Success);
+        Ok(())
+    }
+}
+
+fn run_subcommand(
+    subcommand: Subcommand,
+    level: Option<ConfigLevel>,
+    project: Option<&Project>,
+) -> Fallible<()> {
+    match subcommand {
+        Subcommand::Get { key, show_origin } => {
+            if let Some(level) = level {
+                let file = ConfigFile::for_level(level, project)?;
+                if let Some(value) = file.get(&key) {
+                    print_setting(&value, &ConfigOrigin::File(&file), show_origin);
+                }
+            } else if let Some((value, origin)) = ConfigLayers::current(project)?.get(&key) {
+                print_setting(&value, &origin, show_origin);
+            }
+        }
+        Subcommand::List { show_origin } => {
+            if let Some(level) = level {
+                let file = ConfigFile::for_level(level, project)?;
+                for (key, value) in file.list() {
+                    let setting = format!("{} = {}", key, value);
+                    print_setting(&setting, &ConfigOrigin::File(&file), show_origin);
+                }
+            } else {
+                for (key, value, origin) in ConfigLayers::current(project)?.list() {
+                    print_setting(&format!("{} = {}", key, value), &origin, show_origin);
+                }
+            }
+        }
+        Subcommand::Set { key, value } => {
+            let mut file = ConfigFile::for_level(level.unwrap_or(ConfigLevel::User), project)?;
+            file.set(&key, &value)?;
+            file.save()?;
+        }
+        Subcommand::Delete { key } => {
+            let mut file = ConfigFile::for_level(level.unwrap_or(ConfigLevel::User), project)?;
+            if file.delete(&key)? {
+                file.save()?;
+            } else {
+                display_warning(&format!("`{}` is not set", key));
+            }
+        }
+        Subcommand::Edit => {
+            ConfigFile::for_level(level.unwrap_or(ConfigLevel::User), project)?.edit()?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints a setting, preceded by the file or environment variable it comes from if
+/// `show_origin` is set.
+fn print_setting(setting: &str, origin: &ConfigOrigin, show_origin: bool) {
+    if show_origin {
+        println!("{}\t{}", origin, setting);
+    } else {
+        println!("{}", setting);
     }
 }
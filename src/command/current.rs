@@ -78,6 +78,8 @@ Options:
                 })
                 .is_some(),
             Current::All => {
+                session.check_engines()?;
+
                 let (project, user) = (
                     project_node_version(&session)?,
                     user_node_version(&session)?,
@@ -86,16 +88,32 @@ Options:
                 let user_active = project.is_none() && user.is_some();
                 let any = project.is_some() || user.is_some();
 
+                // With `project.resolve-engines`, the user's Node may have been switched for
+                // one that satisfies the project's `engines.node` range.
+                let resolved = if user_active {
+                    active_node_version(session)?
+                } else {
+                    None
+                };
+
                 for version in project {
-                    println!("project: v{} (active)", version);
+                    match project_version_file(&session)? {
+                        Some(file) => println!("project: v{} (active, from {})", version, file),
+                        None => println!("project: v{} (active)", version),
+                    }
                 }
 
                 for version in user {
-                    println!(
-                        "user: v{}{}",
-                        version,
-                        if user_active { " (active)" } else { "" }
-                    );
+                    match resolved {
+                        Some(ref resolved) if *resolved != version => {
+                            println!("user: v{} (active, from engines.node)", resolved)
+                        }
+                        _ => println!(
+                            "user: v{}{}",
+                            version,
+                            if user_active { " (active)" } else { "" }
+                        ),
+                    }
                 }
 
                 any
@@ -110,12 +128,26 @@ Options:
 }
 
 fn project_node_version(session: &Session) -> Fallible<Option<String>> {
-    if let Some(ref image) = session.project_platform() {
-        return Ok(Some(image.node_str.clone()));
+    if let Some(ref image) = session.project_platform()? {
+        return Ok(Some(image.node().to_string()));
     }
     Ok(None)
 }
 
+// the name of the version file that supplied the project's Node version, if any
+fn project_version_file(session: &Session) -> Fallible<Option<String>> {
+    Ok(session
+        .project_version_file()?
+        .map(|version_file| version_file.name()))
+}
+
 fn user_node_version(session: &Session) -> Fallible<Option<String>> {
     Ok(session.user_node()?.clone().map(|v| v.to_string()))
 }
+
+// the Node version of the current platform, after resolving the project's engines
+fn active_node_version(session: &mut Session) -> Fallible<Option<String>> {
+    Ok(session
+        .current_platform()?
+        .map(|image| image.node().to_string()))
+}
@@ -1,3 +1,4 @@
+use notion_core::registry::{self, ManagedTool};
 use notion_core::session::{ActivityKind, Session};
 use notion_core::version::VersionSpec;
 use notion_fail::{ExitCode, Fallible};
@@ -13,8 +14,7 @@ pub(crate) struct Args {
 
 pub(crate) enum Fetch {
     Help,
-    Node(VersionSpec),
-    Yarn(VersionSpec),
+    Tool(Box<ManagedTool>, VersionSpec),
 }
 
 impl Command for Fetch {
@@ -42,13 +42,12 @@ Options:
             arg_version,
         }: Args,
     ) -> Fallible<Self> {
-        match &arg_tool[..] {
-            "node" => Ok(Fetch::Node(VersionSpec::parse(&arg_version)?)),
-            "yarn" => Ok(Fetch::Yarn(VersionSpec::parse(&arg_version)?)),
-            ref tool => {
+        match registry::lookup(&arg_tool) {
+            Some(tool) => Ok(Fetch::Tool(tool, VersionSpec::parse(&arg_version)?)),
+            None => {
                 throw!(CliParseError {
                     usage: None,
-                    error: format!("no such tool: `{}`", tool),
+                    error: format!("no such tool: `{}`", arg_tool),
                 });
             }
         }
@@ -58,11 +57,8 @@ Options:
         session.add_event_start(ActivityKind::Fetch);
         match self {
             Fetch::Help => Help::Command(CommandName::Fetch).run(session)?,
-            Fetch::Node(version) => {
-                session.fetch_node(&version)?;
-            }
-            Fetch::Yarn(version) => {
-                session.fetch_yarn(&version)?;
+            Fetch::Tool(tool, version) => {
+                tool.fetch(session, &version)?;
             }
         };
         session.add_event_end(ActivityKind::Fetch, ExitCode::Success);
@@ -1,8 +1,8 @@
 use notion_core::session::{ActivityKind, Session};
 use notion_fail::{ExitCode, Fallible};
 
-use command::{Command, CommandName, Config, Current, Deactivate, Fetch, Install, Shim, Use,
-              Version};
+use command::{Command, CommandName, Config, Current, Deactivate, Fetch, Install, List, LsRemote,
+              Shim, Uninstall, Unpin, Use, Version};
 use {CliParseError, Notion};
 
 #[derive(Debug, Deserialize)]
@@ -56,6 +56,7 @@ Options:
             match self {
                 Help::Notion => Notion::USAGE,
                 Help::Command(CommandName::Use) => Use::USAGE,
+                Help::Command(CommandName::Unpin) => Unpin::USAGE,
                 Help::Command(CommandName::Config) => Config::USAGE,
                 Help::Command(CommandName::Current) => Current::USAGE,
                 Help::Command(CommandName::Deactivate) => Deactivate::USAGE,
@@ -63,6 +64,9 @@ Options:
                 Help::Command(CommandName::Version) => Version::USAGE,
                 Help::Command(CommandName::Fetch) => Fetch::USAGE,
                 Help::Command(CommandName::Install) => Install::USAGE,
+                Help::Command(CommandName::Uninstall) => Uninstall::USAGE,
+                Help::Command(CommandName::List) => List::USAGE,
+                Help::Command(CommandName::LsRemote) => LsRemote::USAGE,
                 Help::Command(CommandName::Shim) => Shim::USAGE,
             }
         );
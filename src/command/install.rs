@@ -1,10 +1,10 @@
+use notion_core::registry::{self, ManagedTool};
 use notion_core::session::{ActivityKind, Session};
 use notion_core::version::VersionSpec;
 use notion_fail::{ExitCode, Fallible};
 
 use result::ResultOptionExt;
 
-use CommandUnimplementedError;
 use Notion;
 use command::{Command, CommandName, Help};
 
@@ -16,14 +16,8 @@ pub(crate) struct Args {
 
 pub(crate) enum Install {
     Help,
-    Node(VersionSpec),
-    Yarn(VersionSpec),
-    Other {
-        package: String,
-        // not used
-        #[allow(dead_code)]
-        version: VersionSpec,
-    },
+    Tool(Box<ManagedTool>, VersionSpec),
+    Package { name: String, version: VersionSpec },
 }
 
 impl Command for Install {
@@ -33,14 +27,16 @@ impl Command for Install {
 Install a tool in the user toolchain
 
 Usage:
-    notion install <tool> [<version>]
+    notion install <tool>[@<version>] [<version>]
     notion install -h | --help
 
 Options:
     -h, --help     Display this message
 
 Supported Tools:
-    Currently Notion supports installing `node` and `yarn` - support for more tools is coming soon!
+    Notion manages the `node`, `yarn`, `npm` and `pnpm` toolchain tools. Any other
+    tool is installed as a package from the npm registry, e.g. `notion install ember-cli@3`,
+    and always runs with the Node version it was installed with.
 ";
 
     fn help() -> Self {
@@ -54,18 +50,16 @@ Supported Tools:
             arg_version,
         }: Args,
     ) -> Fallible<Self> {
+        let (name, arg_version) = split_version(arg_tool, arg_version);
+
         let version = arg_version
             .map(VersionSpec::parse)
             .invert()?
             .unwrap_or_default();
 
-        match &arg_tool[..] {
-            "node" => Ok(Install::Node(version)),
-            "yarn" => Ok(Install::Yarn(version)),
-            ref package => Ok(Install::Other {
-                package: package.to_string(),
-                version: version,
-            }),
+        match registry::lookup(&name) {
+            Some(tool) => Ok(Install::Tool(tool, version)),
+            None => Ok(Install::Package { name, version }),
         }
     }
 
@@ -75,21 +69,70 @@ Supported Tools:
             Install::Help => {
                 Help::Command(CommandName::Install).run(session)?;
             }
-            Install::Node(requirements) => {
-                session.set_user_node(&requirements)?;
+            Install::Tool(tool, requirements) => {
+                tool.install(session, &requirements)?;
             }
-            Install::Yarn(requirements) => {
-                session.set_user_yarn(&requirements)?;
+            Install::Package { name, version } => {
+                let package = session.install_package(&name, &version)?;
+                println!(
+                    "Installed {}@{} with Node {}",
+                    name, package.version, package.node
+                );
+                if !package.bins.is_empty() {
+                    println!("    executables: {}", package.bins.join(", "));
+                }
             }
-            Install::Other {
-                package,
-                version: _,
-            } => throw!(CommandUnimplementedError::new(&format!(
-                "notion install {}",
-                package
-            ))),
         };
         session.add_event_end(ActivityKind::Install, ExitCode::Success);
         Ok(())
     }
 }
+
+/// Splits the version off a `<tool>@<version>` argument, if it has one; otherwise the version
+/// is the separate `<version>` argument, if any. Scoped package names start with `@`, so only a
+/// later `@` separates the version.
+fn split_version(arg_tool: String, arg_version: Option<String>) -> (String, Option<String>) {
+    match arg_tool.rfind('@') {
+        Some(index) if index > 0 => (
+            arg_tool[..index].to_string(),
+            Some(arg_tool[index + 1..].to_string()),
+        ),
+        _ => (arg_tool, arg_version),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use super::split_version;
+
+    fn split(arg_tool: &str, arg_version: Option<&str>) -> (String, Option<String>) {
+        split_version(arg_tool.to_string(), arg_version.map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn splits_name_and_version() {
+        assert_eq!(
+            split("ember-cli@3", None),
+            ("ember-cli".to_string(), Some("3".to_string()))
+        );
+        assert_eq!(
+            split("ember-cli", Some("3.5")),
+            ("ember-cli".to_string(), Some("3.5".to_string()))
+        );
+        assert_eq!(split("ember-cli", None), ("ember-cli".to_string(), None));
+    }
+
+    #[test]
+    fn splits_scoped_name_and_version() {
+        assert_eq!(
+            split("@angular/cli@7.0.6", None),
+            ("@angular/cli".to_string(), Some("7.0.6".to_string()))
+        );
+        assert_eq!(
+            split("@angular/cli", Some("7")),
+            ("@angular/cli".to_string(), Some("7".to_string()))
+        );
+        assert_eq!(split("@angular/cli", None), ("@angular/cli".to_string(), None));
+    }
+}
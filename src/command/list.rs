@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use notion_core::catalog::Installed;
+use notion_core::registry::{self, ManagedTool};
+use notion_core::session::{ActivityKind, Session};
+use notion_fail::{ExitCode, Fallible, ResultExt};
+
+use semver::Version;
+use serde_json;
+
+use command::{Command, CommandName, Help};
+use {CliParseError, Notion};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Args {
+    arg_tool: Option<String>,
+    flag_json: bool,
+}
+
+pub(crate) enum List {
+    Help,
+    Tools {
+        tools: Vec<Box<ManagedTool>>,
+        json: bool,
+    },
+}
+
+/// An installed tool version, as reported by `notion list`.
+#[derive(Serialize)]
+struct Entry {
+    version: String,
+    default: bool,
+    pinned: bool,
+    size: u64,
+    cached: bool,
+}
+
+impl Entry {
+    fn new(installed: Installed, pinned: Option<&Version>) -> Self {
+        Entry {
+            pinned: pinned == Some(&installed.version),
+            version: installed.version.to_string(),
+            default: installed.default,
+            size: installed.size,
+            cached: installed.cached,
+        }
+    }
+}
+
+impl Command for List {
+    type Args = Args;
+
+    const USAGE: &'static str = "
+List the tool versions installed on the local machine
+
+Usage:
+    notion list [options] [<tool>]
+    notion list -h | --help
+
+Options:
+    --json         Print the installed versions as JSON
+    -h, --help     Display this message
+
+Supported Tools:
+    node, yarn, npm, pnpm (all are listed if no tool is given)
+";
+
+    fn help() -> Self {
+        List::Help
+    }
+
+    fn parse(
+        _: Notion,
+        Args {
+            arg_tool,
+            flag_json,
+        }: Args,
+    ) -> Fallible<Self> {
+        let tools = match arg_tool {
+            None => registry::tools(),
+            Some(tool) => match registry::lookup(&tool) {
+                Some(tool) => vec![tool],
+                None => {
+                    throw!(CliParseError {
+                        usage: None,
+                        error: format!("no such tool: `{}`", tool),
+                    });
+                }
+            },
+        };
+
+        Ok(List::Tools {
+            tools,
+            json: flag_json,
+        })
+    }
+
+    fn run(self, session: &mut Session) -> Fallible<()> {
+        session.add_event_start(ActivityKind::List);
+        match self {
+            List::Help => Help::Command(CommandName::List).run(session)?,
+            List::Tools { tools, json } => {
+                let image = session.project_platform()?;
+
+                // The installed versions of each tool, as reported by `notion list --json`.
+                let mut listing = BTreeMap::new();
+                for tool in &tools {
+                    let pinned = image.as_ref().and_then(|image| tool.pinned(image));
+                    let installed = tool.installed(session)?;
+                    listing.insert(tool.name(), entries(installed, pinned.as_ref()));
+                }
+
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&listing).unknown()?);
+                } else {
+                    for tool in &tools {
+                        display(tool.name(), &listing[tool.name()]);
+                    }
+                }
+            }
+        };
+        session.add_event_end(ActivityKind::List, ExitCode::Success);
+        Ok(())
+    }
+}
+
+fn entries(installed: Vec<Installed>, pinned: Option<&Version>) -> Vec<Entry> {
+    installed
+        .into_iter()
+        .map(|installed| Entry::new(installed, pinned))
+        .collect()
+}
+
+fn display(tool: &str, entries: &[Entry]) {
+    println!("{}:", tool);
+
+    if entries.is_empty() {
+        println!("    (none installed)");
+        return;
+    }
+
+    for entry in entries {
+        let mut flags = vec![];
+        if entry.default {
+            flags.push("default");
+        }
+        if entry.pinned {
+            flags.push("pinned");
+        }
+
+        println!(
+            "    v{}{} [{}{}]",
+            entry.version,
+            if flags.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", flags.join(", "))
+            },
+            format_size(entry.size),
+            if entry.cached { ", archive cached" } else { "" }
+        );
+    }
+}
+
+/// Formats a size in bytes for display, e.g. `41.3 MB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&'static str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
@@ -0,0 +1,103 @@
+use notion_core::registry::{self, ManagedTool};
+use notion_core::session::{ActivityKind, Session};
+use notion_core::version::VersionSpec;
+use notion_fail::{ExitCode, Fallible};
+
+use semver::{Version, VersionReq};
+
+use command::{Command, CommandName, Help};
+use {CliParseError, Notion};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Args {
+    arg_tool: String,
+    arg_range: Option<String>,
+}
+
+pub(crate) enum LsRemote {
+    Help,
+    Tool(Box<ManagedTool>, Option<VersionReq>),
+}
+
+impl Command for LsRemote {
+    type Args = Args;
+
+    const USAGE: &'static str = "
+List the versions of a tool available for download
+
+Usage:
+    notion ls-remote <tool> [<range>]
+    notion ls-remote -h | --help
+
+Options:
+    -h, --help     Display this message
+
+Versions that are already fetched to the local machine are marked as installed.
+";
+
+    fn help() -> Self {
+        LsRemote::Help
+    }
+
+    fn parse(
+        _: Notion,
+        Args {
+            arg_tool,
+            arg_range,
+        }: Args,
+    ) -> Fallible<Self> {
+        let range = match arg_range {
+            Some(range) => Some(VersionSpec::parse_requirements(&range)?),
+            None => None,
+        };
+
+        match registry::lookup(&arg_tool) {
+            Some(tool) => Ok(LsRemote::Tool(tool, range)),
+            None => {
+                throw!(CliParseError {
+                    usage: None,
+                    error: format!("no such tool: `{}`", arg_tool),
+                });
+            }
+        }
+    }
+
+    fn run(self, session: &mut Session) -> Fallible<()> {
+        session.add_event_start(ActivityKind::LsRemote);
+        match self {
+            LsRemote::Help => Help::Command(CommandName::LsRemote).run(session)?,
+            LsRemote::Tool(tool, range) => {
+                let versions = tool.ls_remote(session)?;
+                display(session, &*tool, versions, range)?;
+            }
+        };
+        session.add_event_end(ActivityKind::LsRemote, ExitCode::Success);
+        Ok(())
+    }
+}
+
+fn display(
+    session: &Session,
+    tool: &ManagedTool,
+    versions: Vec<Version>,
+    range: Option<VersionReq>,
+) -> Fallible<()> {
+    let matching = versions.into_iter().filter(|version| match range {
+        Some(ref range) => range.matches(version),
+        None => true,
+    });
+
+    for version in matching {
+        println!(
+            "v{}{}",
+            version,
+            if tool.is_installed(session, &version)? {
+                " (installed)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    Ok(())
+}
@@ -4,7 +4,11 @@ mod deactivate;
 mod fetch;
 mod help;
 mod install;
+mod list;
+mod ls_remote;
 mod shim;
+mod uninstall;
+mod unpin;
 mod use_;
 mod version;
 
@@ -14,7 +18,11 @@ pub(crate) use self::deactivate::Deactivate;
 pub(crate) use self::fetch::Fetch;
 pub(crate) use self::help::Help;
 pub(crate) use self::install::Install;
+pub(crate) use self::list::List;
+pub(crate) use self::ls_remote::LsRemote;
 pub(crate) use self::shim::Shim;
+pub(crate) use self::uninstall::Uninstall;
+pub(crate) use self::unpin::Unpin;
 pub(crate) use self::use_::Use;
 pub(crate) use self::version::Version;
 
@@ -34,7 +42,12 @@ use std::str::FromStr;
 pub(crate) enum CommandName {
     Fetch,
     Install,
+    Uninstall,
+    List,
+    #[serde(rename = "ls-remote")]
+    LsRemote,
     Use,
+    Unpin,
     Config,
     Current,
     Deactivate,
@@ -51,7 +64,11 @@ impl Display for CommandName {
             match *self {
                 CommandName::Fetch => "fetch",
                 CommandName::Install => "install",
+                CommandName::Uninstall => "uninstall",
+                CommandName::List => "list",
+                CommandName::LsRemote => "ls-remote",
                 CommandName::Use => "use",
+                CommandName::Unpin => "unpin",
                 CommandName::Config => "config",
                 CommandName::Deactivate => "deactivate",
                 CommandName::Current => "current",
@@ -70,7 +87,11 @@ impl FromStr for CommandName {
         Ok(match s {
             "fetch" => CommandName::Fetch,
             "install" => CommandName::Install,
+            "uninstall" => CommandName::Uninstall,
+            "list" => CommandName::List,
+            "ls-remote" => CommandName::LsRemote,
             "use" => CommandName::Use,
+            "unpin" => CommandName::Unpin,
             "config" => CommandName::Config,
             "current" => CommandName::Current,
             "deactivate" => CommandName::Deactivate,
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 
 use console::style;
 use notion_core::session::{ActivityKind, Session};
-use notion_core::{path, shim};
+use notion_core::{path, registry, shim, tool};
 use notion_fail::{ExitCode, Fallible, ResultExt};
 use semver::Version;
 
@@ -29,10 +29,10 @@ pub(crate) enum Shim {
 enum ShimKind {
     Project(PathBuf),
     User(PathBuf),
+    Package(PathBuf, Version),
     System,
     NotInstalled,
     WillInstall(Version),
-    Unimplemented,
 }
 
 impl Display for ShimKind {
@@ -40,14 +40,14 @@ impl Display for ShimKind {
         let s = match self {
             &ShimKind::Project(ref path) => format!("{}", path.to_string_lossy()),
             &ShimKind::User(ref path) => format!("{}", path.to_string_lossy()),
+            &ShimKind::Package(ref path, ref node) => {
+                format!("{} (node v{})", path.to_string_lossy(), node)
+            }
             &ShimKind::System => format!("[system]"),
             &ShimKind::NotInstalled => {
                 format!("{}", style("[executable not installed!]").red().bold())
             }
             &ShimKind::WillInstall(ref version) => format!("[will install version {}]", version),
-            &ShimKind::Unimplemented => {
-                format!("{}", style("[shim not implemented!]").red().bold())
-            }
         };
         f.write_str(&s)
     }
@@ -142,78 +142,67 @@ fn delete(_session: &Session, shim_name: String, _verbose: bool) -> Fallible<()>
 
 fn resolve_shim(session: &Session, shim_name: &OsStr) -> Fallible<ShimKind> {
     match shim_name.to_str() {
-        Some("node") | Some("npm") => resolve_node_shims(session, shim_name),
-        Some("yarn") => resolve_yarn_shims(session, shim_name),
-        Some("npx") => resolve_npx_shims(session, shim_name),
+        Some("node") => resolve_tool_shims(session, "node", shim_name),
+        Some("npm") | Some("npx") => resolve_npm_shims(session, shim_name),
+        Some("yarn") => resolve_tool_shims(session, "yarn", shim_name),
+        Some("pnpm") => resolve_tool_shims(session, "pnpm", shim_name),
         Some(_) => resolve_3p_shims(session, shim_name),
         None => panic!("Cannot format {} as a string", shim_name.to_string_lossy()),
     }
 }
 
-fn is_node_version_installed(version: &Version, session: &Session) -> Fallible<bool> {
-    Ok(session.catalog()?.node.contains(version))
-}
-
-// figure out which version of Node is installed or configured,
+// figure out which version of a tool is installed or configured,
 // or which version will be installed if it's not pinned by the project
-fn resolve_node_shims(session: &Session, shim_name: &OsStr) -> Fallible<ShimKind> {
-    if let Some(ref image) = session.project_platform() {
-        if is_node_version_installed(&image.node, &session)? {
-            // Node is pinned by the project - this shim will use that version
-            let mut bin_path = path::node_version_bin_dir(&image.node_str).unknown()?;
-            bin_path.push(&shim_name);
-            return Ok(ShimKind::User(bin_path));
-        }
-
-        return Ok(ShimKind::WillInstall(image.node.clone()));
-    }
-
-    if let Some(user_version) = session.user_node()? {
-        let mut bin_path = path::node_version_bin_dir(&user_version.to_string()).unknown()?;
-        bin_path.push(&shim_name);
-        return Ok(ShimKind::User(bin_path));
-    }
-    Ok(ShimKind::System)
-}
-
-fn resolve_yarn_shims(session: &Session, shim_name: &OsStr) -> Fallible<ShimKind> {
-    if let Some(ref image) = session.project_platform() {
-        if let Some(ref version) = image.yarn {
-            let catalog = session.catalog()?;
-            if catalog.yarn.contains(version) {
-                // Yarn is pinned by the project - this shim will use that version
-                let mut bin_path = path::yarn_version_bin_dir(&version.to_string()).unknown()?;
+fn resolve_tool_shims(session: &Session, tool: &str, shim_name: &OsStr) -> Fallible<ShimKind> {
+    let tool = registry::lookup(tool).expect("tool is missing from the registry");
+
+    if let Some(ref image) = session.project_platform()? {
+        if let Some(version) = tool.pinned(image) {
+            if tool.is_installed(session, &version)? {
+                // the tool is pinned by the project - this shim will use that version
+                let mut bin_path = tool.bin_dir(&version)?;
                 bin_path.push(&shim_name);
                 return Ok(ShimKind::User(bin_path));
             }
 
             // not installed, but will install based on the required version
-            return Ok(ShimKind::WillInstall(version.clone()));
+            return Ok(ShimKind::WillInstall(version));
         }
 
         return Ok(ShimKind::NotInstalled);
     }
 
-    if let Some(ref default_version) = session.catalog()?.yarn.default {
-        let mut bin_path = path::yarn_version_bin_dir(&default_version.to_string()).unknown()?;
+    if let Some(user_version) = tool.user_version(session)? {
+        let mut bin_path = tool.bin_dir(&user_version)?;
         bin_path.push(&shim_name);
         return Ok(ShimKind::User(bin_path));
     }
     Ok(ShimKind::System)
 }
 
-fn resolve_npx_shims(_session: &Session, _shim_name: &OsStr) -> Fallible<ShimKind> {
-    Ok(ShimKind::Unimplemented)
+// npm (and npx along with it) is bundled with Node, so unless a version of npm
+// is selected the shim will use the one that comes with the selected version of Node
+fn resolve_npm_shims(session: &Session, shim_name: &OsStr) -> Fallible<ShimKind> {
+    match resolve_tool_shims(session, "npm", shim_name)? {
+        ShimKind::NotInstalled | ShimKind::System => {
+            resolve_tool_shims(session, "node", shim_name)
+        }
+        kind => Ok(kind),
+    }
 }
 
 fn resolve_3p_shims(session: &Session, shim_name: &OsStr) -> Fallible<ShimKind> {
     if let Some(ref project) = session.project() {
         // if this is a local executable, get the path to that
         if project.has_direct_bin(shim_name)? {
-            let mut path_to_bin = project.local_bin_dir();
-            path_to_bin.push(shim_name);
-            return Ok(ShimKind::Project(path_to_bin));
+            return Ok(ShimKind::Project(project.local_bin_file(shim_name)));
         }
     }
+
+    // next, a package installed with `notion install`, which runs on the version of Node
+    // it was installed with
+    if let Some((path, platform)) = tool::package_bin(session.catalog()?, shim_name)? {
+        return Ok(ShimKind::Package(path, platform.node().clone()));
+    }
     Ok(ShimKind::NotInstalled)
 }
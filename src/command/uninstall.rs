@@ -0,0 +1,89 @@
+use notion_core::registry::{self, ManagedTool};
+use notion_core::session::{ActivityKind, Session};
+use notion_core::version::VersionSpec;
+use notion_fail::{ExitCode, Fallible};
+
+use semver::Version;
+
+use command::{Command, CommandName, Help};
+use {CliParseError, Notion};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Args {
+    arg_tool: String,
+    arg_version: String,
+    flag_force: bool,
+}
+
+pub(crate) enum Uninstall {
+    Help,
+    Tool {
+        tool: Box<ManagedTool>,
+        version: Version,
+        force: bool,
+    },
+}
+
+impl Command for Uninstall {
+    type Args = Args;
+
+    const USAGE: &'static str = "
+Uninstall a tool version from the local machine
+
+Usage:
+    notion uninstall [options] <tool> <version>
+    notion uninstall -h | --help
+
+Options:
+    -f, --force    Uninstall even if the version is in use
+    -h, --help     Display this message
+
+A version is in use if it is the user default or is pinned by the current project.
+";
+
+    fn help() -> Self {
+        Uninstall::Help
+    }
+
+    fn parse(
+        _: Notion,
+        Args {
+            arg_tool,
+            arg_version,
+            flag_force,
+        }: Args,
+    ) -> Fallible<Self> {
+        let version = VersionSpec::parse_version(&arg_version)?;
+
+        match registry::lookup(&arg_tool) {
+            Some(tool) => Ok(Uninstall::Tool {
+                tool,
+                version,
+                force: flag_force,
+            }),
+            None => {
+                throw!(CliParseError {
+                    usage: None,
+                    error: format!("no such tool: `{}`", arg_tool),
+                });
+            }
+        }
+    }
+
+    fn run(self, session: &mut Session) -> Fallible<()> {
+        session.add_event_start(ActivityKind::Uninstall);
+        match self {
+            Uninstall::Help => Help::Command(CommandName::Uninstall).run(session)?,
+            Uninstall::Tool {
+                tool,
+                version,
+                force,
+            } => {
+                tool.uninstall(session, &version, force)?;
+                println!("Uninstalled {} version {}", tool.name(), version);
+            }
+        };
+        session.add_event_end(ActivityKind::Uninstall, ExitCode::Success);
+        Ok(())
+    }
+}
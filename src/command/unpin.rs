@@ -0,0 +1,60 @@
+use notion_core::registry::{self, ManagedTool};
+use notion_core::session::{ActivityKind, Session};
+use notion_fail::{ExitCode, Fallible};
+
+use command::{Command, CommandName, Help};
+use {CliParseError, Notion};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Args {
+    arg_tool: String,
+}
+
+pub(crate) enum Unpin {
+    Help,
+    Tool(Box<ManagedTool>),
+}
+
+impl Command for Unpin {
+    type Args = Args;
+
+    const USAGE: &'static str = "
+Remove a tool from the current project's toolchain
+
+Usage:
+    notion unpin <tool>
+    notion unpin -h | --help
+
+Options:
+    -h, --help     Display this message
+
+Unpinning node removes the whole toolchain, so any other pinned tools must be
+unpinned first.
+";
+
+    fn help() -> Self {
+        Unpin::Help
+    }
+
+    fn parse(_: Notion, Args { arg_tool }: Args) -> Fallible<Self> {
+        match registry::lookup(&arg_tool) {
+            Some(tool) => Ok(Unpin::Tool(tool)),
+            None => {
+                throw!(CliParseError {
+                    usage: None,
+                    error: format!("no such tool: `{}`", arg_tool),
+                });
+            }
+        }
+    }
+
+    fn run(self, session: &mut Session) -> Fallible<()> {
+        session.add_event_start(ActivityKind::Unpin);
+        match self {
+            Unpin::Help => Help::Command(CommandName::Unpin).run(session)?,
+            Unpin::Tool(tool) => tool.unpin(session)?,
+        };
+        session.add_event_end(ActivityKind::Unpin, ExitCode::Success);
+        Ok(())
+    }
+}
@@ -2,20 +2,23 @@
 // With https://github.com/rust-lang/rfcs/blob/master/text/2151-raw-identifiers.md we
 // could consider something like `r#use` instead.
 
+use notion_core::registry::{self, ManagedTool};
 use notion_core::session::{ActivityKind, Session};
 use notion_core::version::VersionSpec;
 use notion_fail::{ExitCode, Fallible, NotionFail};
 
+use result::ResultOptionExt;
+
 use Notion;
 use command::{Command, CommandName, Help};
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct Args {
     arg_tool: String,
-    arg_version: String,
+    arg_version: Option<String>,
 }
 
-// error message for using tools that are not node|yarn
+// error message for using tools that are not node|yarn|npm|pnpm
 #[derive(Debug, Fail, NotionFail)]
 #[fail(display = "pinning tool '{}' not yet implemented - for now you can manually edit package.json",
        name)]
@@ -30,15 +33,26 @@ impl NoCustomUseError {
     }
 }
 
+// error message for `notion use <tool>` in a project without an engines range for the tool
+#[derive(Debug, Fail, NotionFail)]
+#[fail(display = "No {} version given and package.json has no `engines.{}` range to select one",
+       display_name, name)]
+#[notion_fail(code = "ConfigurationError")]
+pub(crate) struct NoEngineError {
+    pub(crate) name: String,
+    pub(crate) display_name: String,
+}
+
 pub(crate) enum Use {
     Help,
-    Node(VersionSpec),
-    Yarn(VersionSpec),
+    /// Pins a version of a tool, or the newest version that satisfies the project's `engines`
+    /// section if no version is given.
+    Tool(Box<ManagedTool>, Option<VersionSpec>),
     Other {
         name: String,
         // not currently used
         #[allow(dead_code)]
-        version: VersionSpec,
+        version: Option<VersionSpec>,
     },
 }
 
@@ -49,11 +63,14 @@ impl Command for Use {
 Select a tool for the current project's toolchain
 
 Usage:
-    notion use <tool> <version>
+    notion use <tool> [<version>]
     notion use -h | --help
 
 Options:
     -h, --help     Display this message
+
+Without a version, the newest version that satisfies the project's `engines` range for
+the tool (e.g. `engines.node` in package.json) is selected.
 ";
 
     fn help() -> Self {
@@ -67,12 +84,13 @@ Options:
             arg_version,
         }: Args,
     ) -> Fallible<Self> {
-        match &arg_tool[..] {
-            "node" => Ok(Use::Node(VersionSpec::parse(&arg_version)?)),
-            "yarn" => Ok(Use::Yarn(VersionSpec::parse(&arg_version)?)),
-            ref tool => Ok(Use::Other {
-                name: tool.to_string(),
-                version: VersionSpec::parse(&arg_version)?,
+        let version = arg_version.map(VersionSpec::parse).invert()?;
+
+        match registry::lookup(&arg_tool) {
+            Some(tool) => Ok(Use::Tool(tool, version)),
+            None => Ok(Use::Other {
+                name: arg_tool,
+                version,
             }),
         }
     }
@@ -81,8 +99,14 @@ Options:
         session.add_event_start(ActivityKind::Use);
         match self {
             Use::Help => Help::Command(CommandName::Use).run(session)?,
-            Use::Node(spec) => session.pin_node_version(&spec)?,
-            Use::Yarn(spec) => session.pin_yarn_version(&spec)?,
+            Use::Tool(tool, Some(spec)) => tool.pin(session, &spec)?,
+            Use::Tool(tool, None) => match tool.resolve_engine(session)? {
+                Some(version) => tool.pin(session, &VersionSpec::exact(&version))?,
+                None => throw!(NoEngineError {
+                    name: tool.name().to_string(),
+                    display_name: tool.display_name().to_string(),
+                }),
+            },
             Use::Other { name, .. } => throw!(NoCustomUseError::new(name)),
         };
         session.add_event_end(ActivityKind::Use, ExitCode::Success);
@@ -64,17 +64,3 @@ impl NotionErrorExt for NotionError {
     }
 }
 
-#[derive(Debug, Fail, NotionFail)]
-#[fail(display = "command `{}` is not yet implemented", name)]
-#[notion_fail(code = "NotYetImplemented")]
-pub(crate) struct CommandUnimplementedError {
-    pub(crate) name: String,
-}
-
-impl CommandUnimplementedError {
-    pub(crate) fn new(cmd_name: &str) -> Self {
-        CommandUnimplementedError {
-            name: cmd_name.to_string(),
-        }
-    }
-}
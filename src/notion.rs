@@ -12,11 +12,13 @@ extern crate semver;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_json;
 extern crate result;
 
 mod command;
 mod error;
 
+use std::env;
 use std::string::ToString;
 
 use docopt::Docopt;
@@ -25,9 +27,9 @@ use notion_core::session::{ActivityKind, Session};
 use notion_core::style::{display_error, display_unknown_error, ErrorContext};
 use notion_fail::{ExitCode, FailExt, Fallible, NotionError};
 
-use command::{Command, CommandName, Config, Current, Deactivate, Fetch, Help, Install, Shim, Use,
-              Version};
-use error::{CliParseError, CommandUnimplementedError, DocoptExt, NotionErrorExt};
+use command::{Command, CommandName, Config, Current, Deactivate, Fetch, Help, Install, List,
+              LsRemote, Shim, Uninstall, Unpin, Use, Version};
+use error::{CliParseError, DocoptExt, NotionErrorExt};
 
 pub const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
@@ -37,6 +39,7 @@ struct Args {
     arg_args: Vec<String>,
     flag_version: bool,
     flag_verbose: bool,
+    flag_offline: bool,
 }
 
 pub(crate) struct Notion {
@@ -50,7 +53,7 @@ impl Notion {
 Notion: the hassle-free Node.js manager
 
 Usage:
-    notion [-v | --verbose] [<command> <args> ...]
+    notion [-v | --verbose] [--offline] [<command> <args> ...]
     notion -h | --help
     notion -V | --version
 
@@ -58,11 +61,16 @@ Options:
     -h, --help     Display this message
     -V, --version  Print version info and exit
     -v, --verbose  Use verbose output
+    --offline      Only use tool versions available on the local machine
 
 Some common notion commands are:
     fetch          Fetch a tool to the local machine
     install        Install a tool in the user toolchain
+    uninstall      Uninstall a tool version from the local machine
+    list           List the tool versions installed on the local machine
+    ls-remote      List the versions of a tool available for download
     use            Select a tool for the current project's toolchain
+    unpin          Remove a tool from the current project's toolchain
     config         Get or set configuration values
     current        Display the currently activated Node version
     deactivate     Remove Notion from the current shell
@@ -123,12 +131,20 @@ See 'notion help <command>' for more information on a specific command.
                 arg_command: Some(cmd),
                 arg_args,
                 flag_verbose,
+                flag_offline,
                 ..
-            }) => Notion {
-                command: cmd,
-                args: arg_args,
-                verbose: flag_verbose,
-            },
+            }) => {
+                if flag_offline {
+                    // Tool resolution consults `NOTION_OFFLINE`, so forward the flag through it.
+                    env::set_var("NOTION_OFFLINE", "1");
+                }
+
+                Notion {
+                    command: cmd,
+                    args: arg_args,
+                    verbose: flag_verbose,
+                }
+            }
 
             Err(err) => {
                 // Docopt models `-h` and `--help` as errors, so this
@@ -175,7 +191,11 @@ See 'notion help <command>' for more information on a specific command.
         match self.command {
             CommandName::Fetch => Fetch::go(self, session),
             CommandName::Install => Install::go(self, session),
+            CommandName::Uninstall => Uninstall::go(self, session),
+            CommandName::List => List::go(self, session),
+            CommandName::LsRemote => LsRemote::go(self, session),
             CommandName::Use => Use::go(self, session),
+            CommandName::Unpin => Unpin::go(self, session),
             CommandName::Config => Config::go(self, session),
             CommandName::Current => Current::go(self, session),
             CommandName::Deactivate => Deactivate::go(self, session),
@@ -0,0 +1,8 @@
+extern crate notion_core;
+
+use notion_core::tool::{Npx, Tool};
+
+/// The entry point for the `npx` shim.
+pub fn main() {
+    Npx::launch()
+}
@@ -16,6 +16,14 @@ mod support;
 
 // test files
 
+mod notion_config;
 mod notion_current;
 mod notion_deactivate;
+mod notion_install;
+mod notion_list;
+mod notion_offline;
+mod notion_shim;
+mod notion_ls_remote;
+mod notion_uninstall;
+mod notion_unpin;
 mod notion_use;
@@ -0,0 +1,415 @@
+use hamcrest2::core::Matcher;
+use support::sandbox::sandbox;
+use test_support::matchers::execs;
+
+use notion_fail::ExitCode;
+
+const CONFIG: &'static str = r#"# Notion settings
+
+[node]
+# the company mirror
+mirror = "https://mirror.example.com/node"
+
+[yarn]
+resolve = { url = "https://example.com/resolve" }
+"#;
+
+#[test]
+fn config_get() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config get node.mirror"),
+        execs()
+            .with_status(0)
+            .with_stdout("https://mirror.example.com/node")
+    );
+}
+
+#[test]
+fn config_get_unset() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config get npm.mirror"),
+        execs().with_status(0).with_stdout("")
+    );
+}
+
+#[test]
+fn config_get_unknown_key() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config get node.version"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Unknown configuration key: `node.version`")
+    );
+}
+
+#[test]
+fn config_get_default() {
+    let s = sandbox()
+        .config(CONFIG)
+        .catalog(
+            r#"[node]
+default = '9.12.11'
+versions = [ '9.12.11' ]
+"#,
+        )
+        .build();
+
+    assert_that!(
+        s.notion("config get node.default"),
+        execs().with_status(0).with_stdout("9.12.11")
+    );
+    assert_that!(
+        s.notion("config get yarn.default"),
+        execs().with_status(0).with_stdout("")
+    );
+    assert_that!(
+        s.notion("config --user get node.default"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]`node.default` is read from the catalog[..]")
+    );
+}
+
+#[test]
+fn config_set() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config set node.mirror https://other.example.com"),
+        execs().with_status(0)
+    );
+    assert_that!(
+        s.notion("config set yarn.resolve.bin /usr/bin/resolve"),
+        execs().with_status(0)
+    );
+    assert_that!(
+        s.notion("config set project.version-files true"),
+        execs().with_status(0)
+    );
+
+    assert_eq!(
+        s.read_config(),
+        r#"# Notion settings
+
+[node]
+# the company mirror
+mirror = "https://other.example.com"
+
+[yarn]
+resolve = { bin = "/usr/bin/resolve" }
+
+[project]
+version-files = true
+"#
+    );
+}
+
+#[test]
+fn config_set_invalid_value() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config set project.resolve-engines yes"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Invalid value for `project.resolve-engines`: `yes`[..]")
+    );
+    assert_eq!(s.read_config(), CONFIG);
+}
+
+#[test]
+fn config_delete() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config delete yarn.resolve.url"),
+        execs().with_status(0)
+    );
+
+    assert_eq!(
+        s.read_config(),
+        r#"# Notion settings
+
+[node]
+# the company mirror
+mirror = "https://mirror.example.com/node"
+
+[yarn]
+"#
+    );
+}
+
+#[test]
+fn config_list() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config list"),
+        execs().with_status(0).with_stdout(
+            "node.mirror = https://mirror.example.com/node\n\
+             yarn.resolve.url = https://example.com/resolve"
+        )
+    );
+}
+
+#[test]
+fn config_edit() {
+    let s = sandbox().config(CONFIG).env("EDITOR", "true").build();
+
+    assert_that!(s.notion("config edit"), execs().with_status(0));
+    assert_eq!(s.read_config(), CONFIG);
+}
+
+#[test]
+#[cfg(unix)]
+fn config_edit_invalid() {
+    let s = sandbox()
+        .config(CONFIG)
+        .env("EDITOR", "sh -c 'echo \"[node\" > \"$0\"'")
+        .build();
+
+    assert_that!(
+        s.notion("config edit"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]The edited configuration is invalid: [..]")
+            .with_stderr_contains("Your changes have been kept in [..]config.edit.toml")
+    );
+    assert_eq!(s.read_config(), CONFIG);
+}
+
+const SYSTEM_CONFIG: &'static str = r#"[node]
+mirror = "https://system.example.com/node"
+
+[npm]
+mirror = "https://system.example.com/npm"
+"#;
+
+const PROJECT_CONFIG: &'static str = r#"[yarn]
+resolve = { url = "https://project.example.com/resolve-yarn" }
+"#;
+
+const BASIC_PACKAGE_JSON: &'static str = r#"{
+  "name": "test-package"
+}"#;
+
+#[test]
+fn config_list_layers() {
+    let s = sandbox()
+        .system_config(SYSTEM_CONFIG)
+        .config(CONFIG)
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".notionrc.toml", PROJECT_CONFIG)
+        .build();
+
+    assert_that!(
+        s.notion("config list"),
+        execs().with_status(0).with_stdout(
+            "node.mirror = https://mirror.example.com/node\n\
+             npm.mirror = https://system.example.com/npm\n\
+             yarn.resolve.url = https://project.example.com/resolve-yarn"
+        )
+    );
+}
+
+#[test]
+fn config_list_show_origin() {
+    let s = sandbox()
+        .system_config(SYSTEM_CONFIG)
+        .config(CONFIG)
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".notionrc.toml", PROJECT_CONFIG)
+        .build();
+
+    assert_that!(
+        s.notion("config list --show-origin"),
+        execs().with_status(0).with_stdout(
+            "[..]config.toml\tnode.mirror = https://mirror.example.com/node\n\
+             [..]config.toml\tnpm.mirror = https://system.example.com/npm\n\
+             [..].notionrc.toml\tyarn.resolve.url = https://project.example.com/resolve-yarn"
+        )
+    );
+}
+
+#[test]
+fn config_get_level() {
+    let s = sandbox()
+        .system_config(SYSTEM_CONFIG)
+        .config(CONFIG)
+        .build();
+
+    assert_that!(
+        s.notion("config get node.mirror"),
+        execs()
+            .with_status(0)
+            .with_stdout("https://mirror.example.com/node")
+    );
+    assert_that!(
+        s.notion("config --system get node.mirror"),
+        execs()
+            .with_status(0)
+            .with_stdout("https://system.example.com/node")
+    );
+}
+
+#[test]
+fn config_set_project() {
+    let s = sandbox().package_json(BASIC_PACKAGE_JSON).build();
+
+    assert_that!(
+        s.notion("config --project set node.resolve.url https://project.example.com"),
+        execs().with_status(0)
+    );
+    assert_eq!(
+        s.read_project_file(".notionrc.toml"),
+        "[node]\nresolve = { url = \"https://project.example.com\" }\n"
+    );
+}
+
+#[test]
+fn config_set_project_restricted() {
+    let s = sandbox().package_json(BASIC_PACKAGE_JSON).build();
+
+    assert_that!(
+        s.notion("config --project set node.mirror https://project.example.com"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Invalid `node.mirror` in [..].notionrc.toml:[..]: only the `resolve.url` \
+                 and `ls-remote.url` plugins of a tool can be set in a project"
+            )
+    );
+}
+
+#[test]
+fn config_project_restricted() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".notionrc.toml", "[project]\nversion-files = false\n")
+        .build();
+
+    assert_that!(
+        s.notion("config list"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Invalid `project.version-files` in [..].notionrc.toml:2:1:[..]"
+            )
+    );
+}
+
+#[test]
+fn config_set_project_outside_project() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.notion("config --project set node.mirror https://project.example.com"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Not in a node package")
+    );
+}
+
+#[test]
+fn config_set_show_origin() {
+    let s = sandbox().config(CONFIG).build();
+
+    assert_that!(
+        s.notion("config --show-origin set node.mirror https://other.example.com"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]--show-origin cannot be used with `notion config set`")
+    );
+    assert_eq!(s.read_config(), CONFIG);
+}
+
+#[test]
+fn config_get_env_override() {
+    let s = sandbox()
+        .config(CONFIG)
+        .env("NOTION_NODE_MIRROR", "https://env.example.com/node")
+        .build();
+
+    assert_that!(
+        s.notion("config get --show-origin node.mirror"),
+        execs()
+            .with_status(0)
+            .with_stdout("NOTION_NODE_MIRROR\thttps://env.example.com/node")
+    );
+    assert_that!(
+        s.notion("config --user get node.mirror"),
+        execs()
+            .with_status(0)
+            .with_stdout("https://mirror.example.com/node")
+    );
+}
+
+#[test]
+fn config_env_invalid_value() {
+    let s = sandbox()
+        .config(CONFIG)
+        .env("NOTION_PROJECT_VERSION_FILES", "yes")
+        .build();
+
+    assert_that!(
+        s.notion("config list"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Invalid value for `NOTION_PROJECT_VERSION_FILES`: `yes`[..]"
+            )
+    );
+}
+
+#[test]
+fn config_env_conflict() {
+    let s = sandbox()
+        .config(CONFIG)
+        .env("NOTION_YARN_RESOLVE_URL", "https://env.example.com/resolve")
+        .env("NOTION_YARN_RESOLVE_BIN", "resolve-yarn")
+        .build();
+
+    assert_that!(
+        s.notion("config list"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Both NOTION_YARN_RESOLVE_BIN and NOTION_YARN_RESOLVE_URL are set"
+            )
+    );
+}
+
+#[test]
+fn config_invalid_syntax() {
+    let s = sandbox().config("[node]\nmirror = 'a'\n[yarn\n").build();
+
+    assert_that!(
+        s.notion("config list"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not parse [..]config.toml:3:6: [..]")
+    );
+}
+
+#[test]
+fn config_invalid_plugin() {
+    let s = sandbox()
+        .system_config("[yarn]\nresolve = { url = 'a', bin = 'b' }\n")
+        .build();
+
+    assert_that!(
+        s.notion("config list"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Invalid `yarn.resolve` in [..]config.toml:2:1: \
+                 Plugin contains both 'url' and 'bin' fields"
+            )
+    );
+}
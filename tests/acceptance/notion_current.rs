@@ -98,6 +98,45 @@ fn unpinned_project_with_user_node_env() {
     );
 }
 
+#[test]
+fn unpinned_project_warns_about_engines() {
+    let s = sandbox()
+        .package_json("{\n  \"name\": \"test-package\",\n  \"engines\": { \"node\": \">= 8.9 <11\" }\n}\n")
+        .env("NOTION_NODE_VERSION", "6.11.1")
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("user: v6.11.1 (active)")
+            .with_stderr_contains(
+                "[..]this project requires Node >= 8.9 <11, but the active version is 6.11.1"
+            )
+    );
+}
+
+#[test]
+fn unpinned_project_resolves_engines() {
+    let s = sandbox()
+        .config("[project]\nresolve-engines = true\n")
+        .package_json("{\n  \"name\": \"test-package\",\n  \"engines\": { \"node\": \"^9\" }\n}\n")
+        .catalog(
+            r#"[node]
+default = '10.13.12'
+versions = [ '9.13.2', '10.13.12' ]
+"#,
+        )
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("user: v9.13.2 (active, from engines.node)")
+    );
+}
+
 #[test]
 fn unpinned_project_with_user_node_default() {
     let s = sandbox()
@@ -160,3 +199,144 @@ versions = [ '9.12.11' ]
             .with_stdout_contains("user: v9.12.11 (active)")
     );
 }
+
+const VERSION_FILES_CONFIG: &'static str = r#"[project]
+version-files = true
+"#;
+
+const NODE_VERSION_INFO: &'static str = r#"[
+{"version":"v11.0.0","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"],"lts":false},
+{"version":"v10.18.11","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"],"lts":"Dubnium"},
+{"version":"v9.12.11","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"],"lts":false}
+]"#;
+
+#[test]
+fn unpinned_project_with_nvmrc() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".nvmrc", "v9.12.11\n")
+        .config(VERSION_FILES_CONFIG)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("project: v9.12.11 (active, from .nvmrc)")
+    );
+}
+
+#[test]
+fn unpinned_project_with_nvmrc_prefers_installed() {
+    // no index mocks: an installed version has to be used without asking the server
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".nvmrc", "9\n")
+        .config(VERSION_FILES_CONFIG)
+        .catalog("[node]\nversions = [ '9.3.0', '10.1.0' ]\n")
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("project: v9.3.0 (active, from .nvmrc)")
+    );
+}
+
+#[test]
+fn unpinned_project_with_node_version_lts() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".node-version", "lts/*\n")
+        .config(VERSION_FILES_CONFIG)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("project: v10.18.11 (active, from .node-version)")
+    );
+}
+
+#[test]
+fn pinned_project_ignores_nvmrc() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.7.19"))
+        .project_file(".nvmrc", "v9.12.11\n")
+        .config(VERSION_FILES_CONFIG)
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("project: v1.7.19 (active)")
+    );
+}
+
+#[test]
+fn unpinned_project_with_nvmrc_disabled() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".nvmrc", "v9.12.11\n")
+        .env("NOTION_NODE_VERSION", "2.18.5")
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("user: v2.18.5 (active)")
+    );
+}
+
+#[test]
+fn invalid_package_json() {
+    let s = sandbox()
+        .package_json("{\n  \"name\": \"test-package\",\n}\n")
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr("error: Could not parse [..]package.json:3:1: trailing comma")
+    );
+}
+
+#[test]
+fn invalid_toolchain() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("one"))
+        .build();
+
+    assert_that!(
+        s.notion("current"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("error: Invalid `toolchain` in [..]package.json:3:3: [..]")
+    );
+}
+
+#[test]
+fn invalid_catalog() {
+    let s = sandbox()
+        .catalog(
+            r#"[node]
+versions = [ '9.12.11' ]
+default = '9.12'
+"#,
+        )
+        .build();
+
+    assert_that!(
+        s.notion("current --user"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("error: Invalid `node.default` in [..]catalog.toml:3:1: [..]")
+    );
+}
@@ -0,0 +1,34 @@
+use hamcrest2::core::Matcher;
+use test_support::matchers::execs;
+use support::sandbox::sandbox;
+
+use notion_fail::ExitCode;
+
+const CATALOG: &'static str = r#"[node]
+default = '10.13.12'
+versions = [ '10.13.12' ]
+"#;
+
+#[test]
+fn install_package_no_node() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.notion("install ember-cli@3"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]No Node version selected to install ember-cli with")
+    );
+}
+
+#[test]
+fn install_package_offline() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("--offline install ember-cli@3"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]Cannot install ember-cli while offline")
+    );
+}
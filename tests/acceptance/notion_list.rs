@@ -0,0 +1,104 @@
+use hamcrest2::core::Matcher;
+use test_support::matchers::execs;
+use support::sandbox::sandbox;
+
+use notion_fail::ExitCode;
+
+const CATALOG: &'static str = r#"[node]
+default = '10.13.12'
+versions = [ '9.13.2', '10.13.12' ]
+
+[yarn]
+default = '1.4.159'
+versions = [ '1.4.159' ]
+"#;
+
+const PACKAGE_JSON_WITH_PINNED_NODE: &'static str = r#"{
+  "name": "test-package",
+  "toolchain": {
+    "node": "9.13.2"
+  }
+}"#;
+
+#[test]
+fn list_all() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(PACKAGE_JSON_WITH_PINNED_NODE)
+        .build();
+
+    assert_that!(
+        s.notion("list"),
+        execs().with_status(0).with_stdout(
+            "npm:
+    (none installed)
+node:
+    v9.13.2 (pinned) [0 B]
+    v10.13.12 (default) [0 B]
+yarn:
+    v1.4.159 (default) [0 B]
+pnpm:
+    (none installed)"
+        )
+    );
+}
+
+#[test]
+fn list_node_only() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("list node"),
+        execs().with_status(0).with_stdout(
+            "node:
+    v9.13.2 [0 B]
+    v10.13.12 (default) [0 B]"
+        )
+    );
+}
+
+#[test]
+fn list_empty() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.notion("list yarn"),
+        execs()
+            .with_status(0)
+            .with_stdout("yarn:\n    (none installed)")
+    );
+}
+
+#[test]
+fn list_json() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("list yarn --json"),
+        execs().with_status(0).with_stdout(
+            r#"{
+  "yarn": [
+    {
+      "version": "1.4.159",
+      "default": true,
+      "pinned": false,
+      "size": 0,
+      "cached": false
+    }
+  ]
+}"#
+        )
+    );
+}
+
+#[test]
+fn list_unknown_tool() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.notion("list npx"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]no such tool: `npx`")
+    );
+}
@@ -0,0 +1,138 @@
+use hamcrest2::core::Matcher;
+use mockito::{self, mock};
+use test_support::matchers::execs;
+use support::sandbox::sandbox;
+
+use notion_fail::ExitCode;
+
+const NODE_VERSION_INFO: &'static str = r#"[
+{"version":"v10.18.11","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v10.13.12","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v9.13.2","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v8.8.923","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]}
+]"#;
+
+const YARN_VERSION_INFO: &'static str = r#"[
+"1.2.42",
+"1.4.159",
+"1.12.99",
+"0.0.1"
+]"#;
+
+const CATALOG: &'static str = r#"[node]
+default = '10.13.12'
+versions = [ '10.13.12' ]
+
+[yarn]
+versions = [ '1.4.159' ]
+"#;
+
+#[test]
+fn ls_remote_node() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("ls-remote node"),
+        execs()
+            .with_status(0)
+            .with_stdout("v8.8.923\nv9.13.2\nv10.13.12 (installed)\nv10.18.11")
+    );
+}
+
+#[test]
+fn ls_remote_node_range() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("ls-remote node 10"),
+        execs()
+            .with_status(0)
+            .with_stdout("v10.13.12 (installed)\nv10.18.11")
+    );
+}
+
+#[test]
+fn ls_remote_yarn() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .yarn_available_versions(YARN_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("ls-remote yarn >=1.4"),
+        execs()
+            .with_status(0)
+            .with_stdout("v1.4.159 (installed)\nv1.12.99")
+    );
+}
+
+#[test]
+fn ls_remote_node_mirror() {
+    let _mock = mock("GET", "/node-mirror/index.json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(NODE_VERSION_INFO)
+        .create();
+
+    let s = sandbox()
+        .env(
+            "NOTION_NODE_MIRROR",
+            &format!("{}/node-mirror/", mockito::SERVER_URL),
+        )
+        .build();
+
+    assert_that!(
+        s.notion("ls-remote node 9"),
+        execs().with_status(0).with_stdout("v9.13.2")
+    );
+}
+
+#[test]
+fn ls_remote_node_cached_index() {
+    let s = sandbox().node_cache(NODE_VERSION_INFO, false).build();
+
+    assert_that!(
+        s.notion("ls-remote node 9"),
+        execs().with_status(0).with_stdout("v9.13.2")
+    );
+}
+
+#[test]
+fn ls_remote_node_mirror_ignores_cached_index() {
+    let _mock = mock("GET", "/node-mirror/index.json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[{"version":"v9.14.1","files":["linux-x64","osx-x64-tar","win-x64-zip"]}]"#)
+        .create();
+
+    let s = sandbox()
+        .node_cache(NODE_VERSION_INFO, false)
+        .env(
+            "NOTION_NODE_MIRROR",
+            &format!("{}/node-mirror/", mockito::SERVER_URL),
+        )
+        .build();
+
+    assert_that!(
+        s.notion("ls-remote node 9"),
+        execs().with_status(0).with_stdout("v9.14.1")
+    );
+}
+
+#[test]
+fn ls_remote_unknown_tool() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.notion("ls-remote npx"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]no such tool: `npx`")
+    );
+}
@@ -0,0 +1,157 @@
+use hamcrest2::core::Matcher;
+use test_support::matchers::execs;
+use support::sandbox::sandbox;
+
+use notion_fail::ExitCode;
+
+const BASIC_PACKAGE_JSON: &'static str = r#"{
+  "name": "test-package"
+}"#;
+
+const PACKAGE_JSON_WITH_ENGINES: &'static str = r#"{
+  "name": "test-package",
+  "engines": {
+    "node": "^9 || 8"
+  }
+}"#;
+
+const PACKAGE_JSON_WITH_OLD_ENGINES: &'static str = r#"{
+  "name": "test-package",
+  "engines": {
+    "node": "8"
+  }
+}"#;
+
+fn package_json_with_pinned_node(version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "test-package",
+  "toolchain": {{
+    "node": "{}"
+  }}
+}}"#,
+        version
+    )
+}
+
+const CATALOG: &'static str = r#"[node]
+default = '10.13.12'
+versions = [ '9.13.2', '10.13.12' ]
+"#;
+
+const NODE_VERSION_INFO: &'static str = r#"[
+{"version":"v10.18.11","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v10.13.12","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v9.13.2","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v8.8.923","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]}
+]"#;
+
+const NODE_LTS_INFO: &'static str = r#"[
+{"version":"v10.18.11","lts":"Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v10.13.12","lts":false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
+{"version":"v9.13.2","lts":"Carbon","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]}
+]"#;
+
+#[test]
+fn use_node_offline_prefers_installed() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("--offline use node 10"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned node to version 10.13.12 in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("10.13.12"),
+    )
+}
+
+#[test]
+fn use_node_offline_ignores_index() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_cache(NODE_VERSION_INFO, true)
+        .env("NOTION_OFFLINE", "1")
+        .build();
+
+    assert_that!(
+        s.notion("use node 8"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]No Node version found for [..] while offline")
+    );
+}
+
+#[test]
+fn use_node_offline_lts_from_expired_index() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_cache(NODE_LTS_INFO, true)
+        .env("NOTION_OFFLINE", "1")
+        .build();
+
+    assert_that!(
+        s.notion("use node lts"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned node to version 9.13.2 in package.json")
+    );
+}
+
+#[test]
+fn use_node_offline_no_match() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(BASIC_PACKAGE_JSON)
+        .build();
+
+    assert_that!(
+        s.notion("--offline use node 12"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]No Node version found for [..] while offline")
+            .with_stderr_contains("Available locally: 9.13.2, 10.13.12")
+    );
+}
+
+#[test]
+fn use_node_offline_engines() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(PACKAGE_JSON_WITH_ENGINES)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("--offline use node"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned node to version 9.13.2 in package.json")
+    );
+}
+
+#[test]
+fn use_node_offline_engines_no_match() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(PACKAGE_JSON_WITH_OLD_ENGINES)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("--offline use node"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains(
+                "[..]No Node version available locally satisfies engines range 8 while offline"
+            )
+    );
+}
@@ -0,0 +1,72 @@
+use hamcrest2::core::Matcher;
+use test_support::matchers::execs;
+use support::sandbox::sandbox;
+
+const CATALOG: &'static str = r#"[node]
+default = '10.13.12'
+versions = [ '10.13.12' ]
+"#;
+
+const PACKAGE_JSON_WITH_PINNED_NODE: &'static str = r#"{
+  "name": "test-package",
+  "toolchain": {
+    "node": "9.13.2"
+  }
+}"#;
+
+// the shim directory is only inside the sandbox on unix
+#[cfg(unix)]
+#[test]
+fn shim_npx_user_node() {
+    let s = sandbox().catalog(CATALOG).shim("npx").build();
+
+    assert_that!(
+        s.notion("shim -v"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("npx -> [..]10.13.12[..]npx")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn shim_npx_pinned_node_not_installed() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(PACKAGE_JSON_WITH_PINNED_NODE)
+        .shim("npx")
+        .build();
+
+    assert_that!(
+        s.notion("shim -v"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("npx -> [will install version 9.13.2]")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn shim_package_bin() {
+    let s = sandbox()
+        .catalog(
+            r#"[node]
+default = '10.13.12'
+versions = [ '8.9.4', '10.13.12' ]
+
+[packages.ember-cli]
+version = '3.5.0'
+node = '8.9.4'
+bins = [ 'ember' ]
+"#,
+        )
+        .shim("ember")
+        .build();
+
+    assert_that!(
+        s.notion("shim -v"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("ember -> [..]ember-cli[..]3.5.0[..]ember (node v8.9.4)")
+    );
+}
@@ -0,0 +1,114 @@
+use hamcrest2::core::Matcher;
+use test_support::matchers::execs;
+use support::sandbox::sandbox;
+
+use notion_fail::ExitCode;
+
+const CATALOG: &'static str = r#"[node]
+default = '10.13.12'
+versions = [ '9.13.2', '10.13.12' ]
+
+[yarn]
+default = '1.4.159'
+versions = [ '1.2.42', '1.4.159' ]
+"#;
+
+fn package_json_with_pinned_node_yarn(node_version: &str, yarn_version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "test-package",
+  "toolchain": {{
+    "node": "{}",
+    "yarn": "{}"
+  }}
+}}"#,
+        node_version, yarn_version
+    )
+}
+
+#[test]
+fn uninstall_node() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("uninstall node 9.13.2"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Uninstalled node version 9.13.2")
+    );
+
+    let catalog = s.read_catalog();
+    assert!(!catalog.contains("9.13.2"));
+    assert!(catalog.contains("10.13.12"));
+}
+
+#[test]
+fn uninstall_yarn() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("uninstall yarn 1.2.42"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Uninstalled yarn version 1.2.42")
+    );
+
+    assert!(!s.read_catalog().contains("1.2.42"));
+}
+
+#[test]
+fn uninstall_not_installed() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("uninstall node 8.8.923"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]Node version 8.8.923 is not installed")
+    );
+}
+
+#[test]
+fn uninstall_user_default() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("uninstall node 10.13.12"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Node version 10.13.12 is the user default")
+    );
+
+    assert!(s.read_catalog().contains("10.13.12"));
+}
+
+#[test]
+fn uninstall_user_default_force() {
+    let s = sandbox().catalog(CATALOG).build();
+
+    assert_that!(
+        s.notion("uninstall --force node 10.13.12"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Uninstalled node version 10.13.12")
+    );
+
+    assert!(!s.read_catalog().contains("10.13.12"));
+}
+
+#[test]
+fn uninstall_project_pinned() {
+    let s = sandbox()
+        .catalog(CATALOG)
+        .package_json(&package_json_with_pinned_node_yarn("9.13.2", "1.2.42"))
+        .build();
+
+    assert_that!(
+        s.notion("uninstall yarn 1.2.42"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Yarn version 1.2.42 is pinned by the current project")
+    );
+
+    assert!(s.read_catalog().contains("1.2.42"));
+}
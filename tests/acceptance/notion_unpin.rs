@@ -0,0 +1,91 @@
+use hamcrest2::core::Matcher;
+use test_support::matchers::execs;
+use support::sandbox::sandbox;
+
+use notion_fail::ExitCode;
+
+const BASIC_PACKAGE_JSON: &'static str = r#"{
+  "name": "test-package"
+}"#;
+
+const PACKAGE_JSON_WITH_PINNED_NODE: &'static str = r#"{
+  "name": "test-package",
+  "toolchain": {
+    "node": "10.18.11"
+  },
+  "private": true
+}
+"#;
+
+const PACKAGE_JSON_WITH_PINNED_NODE_YARN: &'static str = r#"{
+  "name": "test-package",
+  "toolchain": {
+    "node": "10.18.11",
+    "yarn": "1.4.159"
+  },
+  "private": true
+}
+"#;
+
+#[test]
+fn unpin_node() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_PINNED_NODE)
+        .build();
+
+    assert_that!(
+        s.notion("unpin node"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Unpinned node in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        "{\n  \"name\": \"test-package\",\n  \"private\": true\n}\n"
+    );
+}
+
+#[test]
+fn unpin_yarn() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_PINNED_NODE_YARN)
+        .build();
+
+    assert_that!(
+        s.notion("unpin yarn"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Unpinned yarn in package.json")
+    );
+
+    assert_eq!(s.read_package_json(), PACKAGE_JSON_WITH_PINNED_NODE);
+}
+
+#[test]
+fn unpin_node_with_yarn_pinned() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_PINNED_NODE_YARN)
+        .build();
+
+    assert_that!(
+        s.notion("unpin node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Cannot unpin node while yarn is pinned[..]")
+    );
+
+    assert_eq!(s.read_package_json(), PACKAGE_JSON_WITH_PINNED_NODE_YARN);
+}
+
+#[test]
+fn unpin_not_pinned() {
+    let s = sandbox().package_json(BASIC_PACKAGE_JSON).build();
+
+    assert_that!(
+        s.notion("unpin yarn"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]yarn is not pinned in this project's toolchain")
+    );
+}
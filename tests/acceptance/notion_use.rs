@@ -33,6 +33,50 @@ fn package_json_with_pinned_node_yarn(node_version: &str, yarn_version: &str) ->
     )
 }
 
+fn package_json_with_pinned_node_npm(node_version: &str, npm_version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "test-package",
+  "toolchain": {{
+    "node": "{}",
+    "npm": "{}"
+  }}
+}}"#,
+        node_version, npm_version
+    )
+}
+
+fn package_json_with_pinned_node_pnpm(node_version: &str, pnpm_version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "test-package",
+  "toolchain": {{
+    "node": "{}",
+    "pnpm": "{}"
+  }}
+}}"#,
+        node_version, pnpm_version
+    )
+}
+
+const PACKAGE_JSON_WITH_ENGINES: &'static str = r#"{
+  "name": "test-package",
+  "engines": {
+    "node": "^9 || 8"
+  }
+}"#;
+
+// four-space indentation, an inline array, a key after the toolchain and a final newline
+const FORMATTED_PACKAGE_JSON: &'static str = r#"{
+    "name": "test-package",
+    "files": ["lib", "bin"],
+    "toolchain": {
+        "node": "8.8.923"
+    },
+    "private": true
+}
+"#;
+
 const NODE_VERSION_INFO: &'static str = r#"[
 {"version":"v10.18.11","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
 {"version":"v10.13.12","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]},
@@ -40,6 +84,25 @@ const NODE_VERSION_INFO: &'static str = r#"[
 {"version":"v8.8.923","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"]}
 ]"#;
 
+const NODE_VERSION_INFO_LTS: &'static str = r#"[
+{"version":"v11.0.0","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"],"lts":false},
+{"version":"v10.18.11","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"],"lts":"Dubnium"},
+{"version":"v9.13.2","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"],"lts":false},
+{"version":"v8.8.923","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip"],"lts":"Carbon"}
+]"#;
+
+const NPM_PACKAGE_INFO: &'static str = r#"{
+"name":"npm",
+"dist-tags":{"latest":"6.1.0","next":"6.2.0-next.1"},
+"versions":{"5.10.0":{},"6.0.1":{},"6.1.0":{},"6.2.0-next.1":{}}
+}"#;
+
+const PNPM_PACKAGE_INFO: &'static str = r#"{
+"name":"pnpm",
+"dist-tags":{"latest":"2.9.0"},
+"versions":{"1.43.1":{},"2.8.0":{},"2.9.0":{}}
+}"#;
+
 #[test]
 fn use_node() {
     let s = sandbox()
@@ -61,6 +124,60 @@ fn use_node() {
     )
 }
 
+#[test]
+fn use_node_engines() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_ENGINES)
+        .node_available_versions(NODE_VERSION_INFO)
+        .node_archive_mocks()
+        .build();
+
+    assert_that!(
+        s.notion("use node"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned node to version 9.13.2 in package.json")
+    );
+}
+
+#[test]
+fn use_node_no_engines() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("use node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]No Node version given and package.json has no `engines.node` range[..]"
+            )
+    );
+}
+
+#[test]
+fn use_node_preserves_formatting() {
+    let s = sandbox()
+        .package_json(FORMATTED_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .node_archive_mocks()
+        .build();
+
+    assert_that!(
+        s.notion("use node 10"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned node to version 10.18.11 in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        FORMATTED_PACKAGE_JSON.replace("8.8.923", "10.18.11"),
+    )
+}
+
 #[test]
 fn use_node_latest() {
     let s = sandbox()
@@ -82,6 +199,63 @@ fn use_node_latest() {
     )
 }
 
+#[test]
+fn use_node_lts() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO_LTS)
+        .node_archive_mocks()
+        .build();
+
+    assert_that!(
+        s.notion("use node lts"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned node to version 10.18.11 in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("10.18.11"),
+    )
+}
+
+#[test]
+fn use_node_lts_line() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO_LTS)
+        .node_archive_mocks()
+        .build();
+
+    assert_that!(
+        s.notion("use node lts/carbon"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned node to version 8.8.923 in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("8.8.923"),
+    )
+}
+
+#[test]
+fn use_node_unknown_lts_line() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO_LTS)
+        .build();
+
+    assert_that!(
+        s.notion("use node lts/argon"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]No Node version found for lts/argon")
+    );
+}
+
 #[test]
 fn use_yarn_no_node() {
     let s = sandbox()
@@ -141,3 +315,97 @@ fn use_yarn_latest() {
         package_json_with_pinned_node_yarn("1.2.3", "1.2.0"),
     )
 }
+
+#[test]
+fn use_npm_no_node() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .npm_available_versions(NPM_PACKAGE_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("use npm 6"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("error: There is no pinned node version for this project")
+    );
+
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON,)
+}
+
+#[test]
+fn use_npm() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .npm_available_versions(NPM_PACKAGE_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("use npm 6"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned npm to version 6.1.0 in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_npm("1.2.3", "6.1.0"),
+    )
+}
+
+#[test]
+fn use_npm_latest() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .npm_available_versions(NPM_PACKAGE_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("use npm latest"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned npm to version 6.1.0 in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_npm("1.2.3", "6.1.0"),
+    )
+}
+
+#[test]
+fn use_pnpm() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .pnpm_available_versions(PNPM_PACKAGE_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("use pnpm 2.8"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Pinned pnpm to version 2.8.0 in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_pnpm("1.2.3", "2.8.0"),
+    )
+}
+
+#[test]
+fn use_pnpm_no_node() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .pnpm_available_versions(PNPM_PACKAGE_INFO)
+        .build();
+
+    assert_that!(
+        s.notion("use pnpm latest"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("error: There is no pinned node version for this project")
+    );
+
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON,)
+}
@@ -12,7 +12,7 @@ thread_local!(static TASK_ID: usize = NEXT_ID.fetch_add(1, Ordering::SeqCst));
 
 
 // creates the root directory for the acceptancce tests (once), and
-// initializes the root and home directories for the current task
+// initializes the root and Notion home directories for the current task
 fn init() {
     static GLOBAL_INIT: Once = ONCE_INIT;
     thread_local!(static LOCAL_INIT: Cell<bool> = Cell::new(false));
@@ -25,7 +25,7 @@ fn init() {
         }
         i.set(true);
         root().rm_rf();
-        home().mkdir_p();
+        notion_home().mkdir_p();
     })
 }
 
@@ -52,8 +52,12 @@ pub fn root() -> PathBuf {
     global_root().join(&TASK_ID.with(|my_id| format!("t{}", my_id)))
 }
 
-pub fn home() -> PathBuf {
-    root().join("home")
+pub fn notion_home() -> PathBuf {
+    root().join("notion")
+}
+
+pub fn data_root() -> PathBuf {
+    root().join("data")
 }
 
 enum Remove { File, Dir }
@@ -22,15 +22,26 @@ use mockito::{self, mock, Matcher};
 struct CacheBuilder {
     path: PathBuf,
     expiry_path: PathBuf,
+    source_path: PathBuf,
+    source: String,
     contents: String,
     expired: bool,
 }
 
 impl CacheBuilder {
-    pub fn new(path: PathBuf, expiry_path: PathBuf, contents: &str, expired: bool) -> CacheBuilder {
+    pub fn new(
+        path: PathBuf,
+        expiry_path: PathBuf,
+        source_path: PathBuf,
+        source: String,
+        contents: &str,
+        expired: bool,
+    ) -> CacheBuilder {
         CacheBuilder {
             path,
             expiry_path,
+            source_path,
+            source,
             contents: contents.to_string(),
             expired,
         }
@@ -60,6 +71,16 @@ impl CacheBuilder {
             )
         });
         ok_or_panic!{ expiry_file.write_all(expiry_date.to_string().as_bytes()) };
+
+        // write the file recording where the cache was fetched from
+        let mut source_file = File::create(&self.source_path).unwrap_or_else(|e| {
+            panic!(
+                "could not create cache source file {}: {}",
+                self.source_path.display(),
+                e
+            )
+        });
+        ok_or_panic!{ source_file.write_all(self.source.as_bytes()) };
     }
 
     fn dirname(&self) -> &Path {
@@ -157,6 +178,8 @@ impl SandboxBuilder {
         self.caches.push(CacheBuilder::new(
             node_index_file(),
             node_index_expiry_file(),
+            node_index_source_file(),
+            format!("{}/node-dist/index.json", mockito::SERVER_URL),
             cache,
             expired,
         ));
@@ -170,6 +193,27 @@ impl SandboxBuilder {
         self
     }
 
+    /// Add a file to the root of the project, such as `.nvmrc` (chainable)
+    pub fn project_file(mut self, name: &str, contents: &str) -> Self {
+        let file = self.root().join(name);
+        self.files.push(FileBuilder::new(file, contents));
+        self
+    }
+
+    /// Set the config.toml for the sandbox (chainable)
+    pub fn config(mut self, contents: &str) -> Self {
+        self.files
+            .push(FileBuilder::new(user_config_file(), contents));
+        self
+    }
+
+    /// Set the system-wide config.toml for the sandbox (chainable)
+    pub fn system_config(mut self, contents: &str) -> Self {
+        self.files
+            .push(FileBuilder::new(system_config_file(), contents));
+        self
+    }
+
     /// Set the catalog.toml for the sandbox (chainable)
     pub fn catalog(mut self, contents: &str) -> Self {
         self.files
@@ -177,6 +221,12 @@ impl SandboxBuilder {
         self
     }
 
+    /// Add a shim to the Notion bin directory (chainable)
+    pub fn shim(mut self, name: &str) -> Self {
+        self.files.push(FileBuilder::new(shim_file(name), ""));
+        self
+    }
+
     /// Set the shell for the sandbox (chainable)
     pub fn notion_shell(mut self, shell_name: &str) -> Self {
         self.root
@@ -310,6 +360,30 @@ impl SandboxBuilder {
         self
     }
 
+    /// Setup mock to return the metadata of the npm package, listing the available
+    /// npm versions (chainable)
+    pub fn npm_available_versions(mut self, body: &str) -> Self {
+        let mock = mock(method_name("GET"), "/npm-registry/npm")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        self.root.mocks.push(mock);
+        self
+    }
+
+    /// Setup mock to return the metadata of the pnpm package, listing the available
+    /// pnpm versions (chainable)
+    pub fn pnpm_available_versions(mut self, body: &str) -> Self {
+        let mock = mock(method_name("GET"), "/npm-registry/pnpm")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        self.root.mocks.push(mock);
+        self
+    }
+
     /// Create the project
     pub fn build(mut self) -> Sandbox {
         // First, clean the directory if it already exists
@@ -347,11 +421,8 @@ impl SandboxBuilder {
 
 // files and dirs in the sandbox
 
-fn home_dir() -> PathBuf {
-    paths::home()
-}
 fn notion_home() -> PathBuf {
-    home_dir().join(".notion")
+    paths::notion_home()
 }
 fn notion_tmp_dir() -> PathBuf {
     notion_home().join("tmp")
@@ -359,17 +430,15 @@ fn notion_tmp_dir() -> PathBuf {
 fn notion_bin_dir() -> PathBuf {
     notion_home().join("bin")
 }
+fn shim_file(name: &str) -> PathBuf {
+    notion_bin_dir().join(name)
+}
 fn notion_postscript() -> PathBuf {
     notion_tmp_dir().join("notion_tmp_1234.sh")
 }
-#[cfg(unix)]
 fn cache_dir() -> PathBuf {
     notion_home().join("cache")
 }
-#[cfg(windows)]
-fn cache_dir() -> PathBuf {
-    home_dir().join("Notion").join("cache")
-}
 fn node_cache_dir() -> PathBuf {
     cache_dir().join("node")
 }
@@ -382,21 +451,29 @@ fn node_index_file() -> PathBuf {
 fn node_index_expiry_file() -> PathBuf {
     node_cache_dir().join("index.json.expires")
 }
+fn node_index_source_file() -> PathBuf {
+    node_cache_dir().join("index.json.source")
+}
 fn package_json_file(mut root: PathBuf) -> PathBuf {
     root.push("package.json");
     root
 }
-#[cfg(unix)]
 fn user_catalog_file() -> PathBuf {
     notion_home().join("catalog.toml")
 }
-#[cfg(windows)]
-fn local_data_root() -> PathBuf {
-    home_dir().join("AppData").join("Local").join("Notion")
+fn user_config_file() -> PathBuf {
+    notion_home().join("config.toml")
+}
+#[cfg(unix)]
+fn system_config_file() -> PathBuf {
+    paths::data_root()
+        .join("etc")
+        .join("notion")
+        .join("config.toml")
 }
 #[cfg(windows)]
-fn user_catalog_file() -> PathBuf {
-    local_data_root().join("catalog.toml")
+fn system_config_file() -> PathBuf {
+    paths::data_root().join("Notion").join("config.toml")
 }
 
 pub struct Sandbox {
@@ -423,15 +500,15 @@ impl Sandbox {
         p.cwd(self.root())
             // sandbox the Notion environment
             .env("NOTION_SANDBOX", "true") // used to indicate that Notion is running sandboxed, for directory logic in Windows
-            .env("HOME", home_dir())
-            .env("USERPROFILE", home_dir()) // windows
             .env("NOTION_HOME", notion_home())
-            .env("NOTION_DATA_ROOT", notion_home()) // windows
+            .env("NOTION_DATA_ROOT", paths::data_root()) // the system-wide directories
             .env("PATH", &self.path)
             .env("NOTION_POSTSCRIPT", notion_postscript())
             .env_remove("NOTION_DEV")
             .env_remove("NOTION_NODE_VERSION")
             .env_remove("NOTION_SHELL")
+            .env_remove("VISUAL")
+            .env_remove("EDITOR")
             .env_remove("MSYSTEM"); // assume cmd.exe everywhere on windows
 
         // overrides for env vars
@@ -457,6 +534,18 @@ impl Sandbox {
         read_file_to_string(package_file)
     }
 
+    pub fn read_catalog(&self) -> String {
+        read_file_to_string(user_catalog_file())
+    }
+
+    pub fn read_config(&self) -> String {
+        read_file_to_string(user_config_file())
+    }
+
+    pub fn read_project_file(&self, name: &str) -> String {
+        read_file_to_string(self.root().join(name))
+    }
+
     pub fn read_postscript(&self) -> String {
         let postscript_file = notion_postscript();
         read_file_to_string(postscript_file)